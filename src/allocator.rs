@@ -0,0 +1,314 @@
+//! Subnet allocator - variable-length subnet planning within a parent block
+//!
+//! A Rust counterpart of Python netaddr's `contrib/subnet_splitter.py`. Free
+//! space is kept in an `IPSet`, and each request is carved from the smallest
+//! free CIDR block that can hold it (best fit, lowest address first), which
+//! keeps large blocks intact for later requests.
+
+use crate::error::{AddrFormatError, AddrResult};
+use crate::ip::{AddressCount, IPNetwork};
+use crate::sets::IPSet;
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// The size of a subnet to allocate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SubnetRequest {
+    /// A subnet with the given prefix length
+    Prefix(u8),
+    /// The smallest subnet with at least this many usable hosts
+    ///
+    /// IPv4 subnets lose their network and broadcast addresses, except /31
+    /// point-to-point links (RFC 3021) and /32 hosts; IPv6 subnets do not.
+    Hosts(u128),
+}
+
+impl SubnetRequest {
+    /// Get the prefix length satisfying the request in an address family of `width` bits
+    fn prefix_length(&self, width: u8) -> AddrResult<u8> {
+        match *self {
+            SubnetRequest::Prefix(prefix) if prefix <= width => Ok(prefix),
+            SubnetRequest::Prefix(prefix) => Err(AddrFormatError::new(format!(
+                "Invalid prefix length /{} for a {}-bit address",
+                prefix, width
+            ))),
+            SubnetRequest::Hosts(hosts) => (0..=width)
+                .rev()
+                .find(|&prefix| usable_hosts(width, prefix) >= AddressCount::new(hosts))
+                .ok_or_else(|| AddrFormatError::new(format!("No subnet holds {} hosts", hosts))),
+        }
+    }
+}
+
+impl fmt::Display for SubnetRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubnetRequest::Prefix(prefix) => write!(f, "/{}", prefix),
+            SubnetRequest::Hosts(hosts) => write!(f, "hosts>={}", hosts),
+        }
+    }
+}
+
+/// Get the number of usable hosts in a subnet, as counted by `IPNetwork::hosts`
+fn usable_hosts(width: u8, prefix: u8) -> AddressCount {
+    let size = AddressCount::power_of_two((width - prefix) as u32);
+    if width == 32 && prefix < 31 {
+        AddressCount::new(size.to_u128().expect("IPv4 sizes fit in a u128") - 2)
+    } else {
+        size
+    }
+}
+
+/// A summary of how the free space of an allocator is split up
+#[derive(Debug, Clone, PartialEq)]
+pub struct FragmentationReport {
+    /// The number of free addresses
+    pub free_addresses: AddressCount,
+    /// The number of CIDR blocks the free space splits into
+    pub free_blocks: usize,
+    /// The largest free CIDR block, lowest address first
+    pub largest_free_block: Option<IPNetwork>,
+    /// The share of free space outside the largest free block, from 0.0
+    /// (all free space is one block) to nearly 1.0 (scattered small blocks)
+    pub fragmentation: f64,
+}
+
+/// Allocates non-overlapping, aligned subnets from a parent network
+///
+/// ```
+/// use netaddr::allocator::{SubnetAllocator, SubnetRequest};
+/// use std::str::FromStr;
+///
+/// let mut allocator = SubnetAllocator::new(netaddr::IPNetwork::from_str("10.0.0.0/22").unwrap());
+/// let subnets = allocator
+///     .allocate_all(&[SubnetRequest::Prefix(26), SubnetRequest::Prefix(24), SubnetRequest::Hosts(500)])
+///     .unwrap();
+/// assert_eq!(subnets[2].to_string(), "10.0.0.0/23");
+/// ```
+#[derive(Debug, Clone)]
+pub struct SubnetAllocator {
+    parent: IPNetwork,
+    free: IPSet,
+    allocated: BTreeSet<IPNetwork>,
+}
+
+impl SubnetAllocator {
+    /// Create an allocator with the whole parent network free
+    pub fn new(parent: IPNetwork) -> Self {
+        let free = IPSet::from_network(parent.clone()).expect("a single network is a valid set");
+        Self {
+            parent,
+            free,
+            allocated: BTreeSet::new(),
+        }
+    }
+
+    /// Get the parent network
+    pub fn parent(&self) -> &IPNetwork {
+        &self.parent
+    }
+
+    /// Get the free address space
+    pub fn free(&self) -> &IPSet {
+        &self.free
+    }
+
+    /// Get the free address space as CIDR blocks, in address order
+    pub fn free_blocks(&self) -> Vec<IPNetwork> {
+        self.free.iter_ranges().flat_map(|range| range.iter_cidrs()).collect()
+    }
+
+    /// Get the current allocations, in address order
+    pub fn allocations(&self) -> impl Iterator<Item = &IPNetwork> + '_ {
+        self.allocated.iter()
+    }
+
+    /// Get the number of free addresses
+    pub fn free_size(&self) -> AddressCount {
+        self.free.size()
+    }
+
+    /// Get the number of allocated addresses
+    pub fn allocated_size(&self) -> AddressCount {
+        self.allocated.iter().map(IPNetwork::num_addresses).sum()
+    }
+
+    /// Allocate a single subnet
+    pub fn allocate(&mut self, request: SubnetRequest) -> AddrResult<IPNetwork> {
+        let prefix = request.prefix_length(self.width())?;
+        if prefix < self.parent.prefix_length() {
+            return Err(AddrFormatError::new(format!(
+                "Cannot allocate {} from the smaller network {}",
+                request, self.parent
+            )));
+        }
+
+        // Best fit: the smallest free block that holds the subnet
+        let block = self
+            .free_blocks()
+            .into_iter()
+            .filter(|block| block.prefix_length() <= prefix)
+            .min_by_key(|block| (std::cmp::Reverse(block.prefix_length()), block.network_address().clone()))
+            .ok_or_else(|| {
+                AddrFormatError::new(format!("No free space for {} in {}", request, self.parent))
+            })?;
+
+        let subnet = IPNetwork::new(block.network_address().clone(), prefix)?;
+        self.free.remove_network(&subnet)?;
+        self.allocated.insert(subnet.clone());
+        Ok(subnet)
+    }
+
+    /// Allocate `count` subnets of the same size, or none at all if they do not all fit
+    pub fn allocate_many(&mut self, request: SubnetRequest, count: usize) -> AddrResult<Vec<IPNetwork>> {
+        self.allocate_all(&vec![request; count])
+    }
+
+    /// Allocate a plan of subnets, or none at all if they do not all fit
+    ///
+    /// Requests are placed largest first, which packs any plan that fits in
+    /// the free space. The result is in the order of the requests.
+    pub fn allocate_all(&mut self, requests: &[SubnetRequest]) -> AddrResult<Vec<IPNetwork>> {
+        let width = self.width();
+        let mut order = requests
+            .iter()
+            .enumerate()
+            .map(|(index, request)| Ok((request.prefix_length(width)?, index)))
+            .collect::<AddrResult<Vec<_>>>()?;
+        order.sort();
+
+        let mut trial = self.clone();
+        let mut subnets = vec![None; requests.len()];
+        for (prefix, index) in order {
+            subnets[index] = Some(trial.allocate(SubnetRequest::Prefix(prefix)).map_err(|_| {
+                AddrFormatError::new(format!(
+                    "No free space for {} in {} (request {} of {})",
+                    requests[index],
+                    self.parent,
+                    index + 1,
+                    requests.len()
+                ))
+            })?);
+        }
+
+        *self = trial;
+        Ok(subnets.into_iter().flatten().collect())
+    }
+
+    /// Release an allocated subnet back into the free space
+    pub fn release(&mut self, subnet: &IPNetwork) -> AddrResult<()> {
+        if !self.allocated.remove(subnet) {
+            return Err(AddrFormatError::new(format!("{} is not allocated", subnet)));
+        }
+        self.free.add_network(subnet.clone())
+    }
+
+    /// Report how fragmented the free space is
+    pub fn fragmentation(&self) -> FragmentationReport {
+        let blocks = self.free_blocks();
+        let free_addresses = self.free_size();
+        let largest_free_block = blocks.iter().min_by_key(|block| block.prefix_length()).cloned();
+        let fragmentation = match &largest_free_block {
+            Some(block) => 1.0 - block.num_addresses().to_f64() / free_addresses.to_f64(),
+            None => 0.0,
+        };
+
+        FragmentationReport {
+            free_addresses,
+            free_blocks: blocks.len(),
+            largest_free_block,
+            fragmentation,
+        }
+    }
+
+    fn width(&self) -> u8 {
+        if self.parent.is_ipv4() {
+            32
+        } else {
+            128
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn network(s: &str) -> IPNetwork {
+        IPNetwork::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_splitter_compatibility() {
+        // Mirrors test_ip_splitter.py from the Python package
+        let mut allocator = SubnetAllocator::new(network("172.24.0.0/16"));
+        let subnets = allocator.allocate_many(SubnetRequest::Prefix(23), 4).unwrap();
+        assert_eq!(subnets.iter().map(|n| n.to_string()).collect::<Vec<_>>(), ["172.24.0.0/23", "172.24.2.0/23", "172.24.4.0/23", "172.24.6.0/23"]);
+        assert_eq!(
+            allocator.free_blocks().iter().map(|n| n.to_string()).collect::<Vec<_>>(),
+            ["172.24.8.0/21", "172.24.16.0/20", "172.24.32.0/19", "172.24.64.0/18", "172.24.128.0/17"]
+        );
+
+        let subnets = allocator.allocate_many(SubnetRequest::Prefix(28), 10).unwrap();
+        assert_eq!(subnets[0].to_string(), "172.24.8.0/28");
+        assert_eq!(subnets[9].to_string(), "172.24.8.144/28");
+
+        let mut whole = SubnetAllocator::new(network("172.24.0.0/16"));
+        assert!(whole.allocate_many(SubnetRequest::Prefix(16), 2).is_err());
+        assert_eq!(whole.free_size(), 65536);
+        assert!(whole.allocate(SubnetRequest::Prefix(15)).is_err());
+        whole.allocate(SubnetRequest::Prefix(16)).unwrap();
+        assert!(whole.free().is_empty());
+    }
+
+    #[test]
+    fn test_vlsm_plan() {
+        let mut allocator = SubnetAllocator::new(network("10.0.0.0/22"));
+        let mut plan = vec![SubnetRequest::Prefix(26); 3];
+        plan.push(SubnetRequest::Prefix(24));
+        plan.push(SubnetRequest::Hosts(500));
+        let subnets = allocator.allocate_all(&plan).unwrap();
+
+        let subnets: Vec<String> = subnets.iter().map(|n| n.to_string()).collect();
+        assert_eq!(subnets, ["10.0.3.0/26", "10.0.3.64/26", "10.0.3.128/26", "10.0.2.0/24", "10.0.0.0/23"]);
+        assert_eq!(allocator.free_blocks(), vec![network("10.0.3.192/26")]);
+        assert_eq!(allocator.allocated_size(), 1024 - 64);
+
+        // Nothing is allocated when the plan does not fit
+        assert!(allocator.allocate_all(&[SubnetRequest::Prefix(27), SubnetRequest::Prefix(26)]).is_err());
+        assert_eq!(allocator.free_size(), 64);
+    }
+
+    #[test]
+    fn test_release_and_fragmentation() {
+        let mut allocator = SubnetAllocator::new(network("10.0.0.0/24"));
+        let subnets = allocator.allocate_many(SubnetRequest::Prefix(26), 4).unwrap();
+        allocator.release(&subnets[0]).unwrap();
+        allocator.release(&subnets[2]).unwrap();
+        assert!(allocator.release(&subnets[2]).is_err());
+
+        let report = allocator.fragmentation();
+        assert_eq!(report.free_addresses, 128);
+        assert_eq!(report.free_blocks, 2);
+        assert_eq!(report.largest_free_block, Some(network("10.0.0.0/26")));
+        assert_eq!(report.fragmentation, 0.5);
+
+        allocator.release(&subnets[1]).unwrap();
+        let report = allocator.fragmentation();
+        assert_eq!(report.largest_free_block, Some(network("10.0.0.0/25")));
+        assert!((report.fragmentation - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(allocator.allocations().collect::<Vec<_>>(), vec![&subnets[3]]);
+    }
+
+    #[test]
+    fn test_ipv6_allocation() {
+        let mut allocator = SubnetAllocator::new(network("2001:db8::/32"));
+        let site = allocator.allocate(SubnetRequest::Prefix(48)).unwrap();
+        assert_eq!(site.to_string(), "2001:db8::/48");
+        let hosts = allocator.allocate(SubnetRequest::Hosts(1 << 64)).unwrap();
+        assert_eq!(hosts.to_string(), "2001:db8:1::/64");
+        assert_eq!(SubnetRequest::Hosts(500).prefix_length(32).unwrap(), 23);
+        assert_eq!(SubnetRequest::Hosts(2).prefix_length(32).unwrap(), 31);
+    }
+}
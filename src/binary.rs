@@ -0,0 +1,723 @@
+//! Compact, versioned binary encoding for IP sets and prefix maps
+//!
+//! Parsing thousands of CIDR strings is slow compared to reading a prebuilt
+//! table, so sets and prefix maps can be written to and loaded from a stable
+//! binary format. All integers are little-endian:
+//!
+//! ```text
+//! header   magic "NAIP" | version u16 | kind u8 | section count u8
+//! section  family u8 (4 or 6) | reserved [u8; 3] | count u32 | index len u32 | data len u32
+//!          index: index len x (start address, 4 or 16 bytes | data offset u32)
+//!          data:  data len bytes
+//! trailer  CRC-32 (IEEE) of everything before it, u32
+//! ```
+//!
+//! There is at most one section per address family, IPv4 first. In an IP set
+//! section each range is stored as two LEB128 varints: the gap from the end
+//! of the previous range and the range length minus one. Every 64th range
+//! starts a block whose absolute start address and offset are recorded in the
+//! index, so [`IPSetView`] can answer membership queries straight from the
+//! encoded bytes (for example a memory-mapped file) by binary searching the
+//! index and decoding at most one block. IP sets never hold IPv6 zones, so
+//! none are stored, and a view never contains a zoned address.
+//!
+//! A prefix map section stores each entry as the varint delta from the
+//! previous network address, the prefix length byte, and the value as a
+//! varint length followed by that many bytes. Prefix map sections have no
+//! index.
+
+use crate::error::{AddrFormatError, AddrResult};
+use crate::ip::{IPAddress, IPNetwork, IPRange};
+use crate::prefix_map::PrefixMap;
+use crate::sets::IPSet;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Current version of the binary format
+pub const FORMAT_VERSION: u16 = 1;
+
+const MAGIC: &[u8; 4] = b"NAIP";
+const KIND_IP_SET: u8 = 1;
+const KIND_PREFIX_MAP: u8 = 2;
+const HEADER_LEN: usize = 8;
+const SECTION_HEADER_LEN: usize = 16;
+const CHECKSUM_LEN: usize = 4;
+const BLOCK_SIZE: usize = 64;
+
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, &byte| {
+        CRC32_TABLE[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u128) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Read a LEB128 varint, returning `None` if it is truncated or overflows a u128
+fn read_varint(data: &[u8], pos: &mut usize) -> Option<u128> {
+    let mut value = 0u128;
+    let mut shift = 0u32;
+    loop {
+        let byte = *data.get(*pos)?;
+        *pos += 1;
+        let bits = (byte & 0x7f) as u128;
+        if shift == 126 && bits > 0b11 {
+            return None;
+        }
+        value |= bits << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+        shift += 7;
+        if shift > 126 {
+            return None;
+        }
+    }
+}
+
+fn format_error(message: impl std::fmt::Display) -> AddrFormatError {
+    AddrFormatError::new(format!("Invalid binary encoding: {}", message))
+}
+
+fn family_of(addr: &IPAddress) -> u8 {
+    addr.version()
+}
+
+/// Width of an address of the given family in bytes
+fn address_width(family: u8) -> usize {
+    if family == 4 {
+        4
+    } else {
+        16
+    }
+}
+
+fn max_value(family: u8) -> u128 {
+    if family == 4 {
+        u32::MAX as u128
+    } else {
+        u128::MAX
+    }
+}
+
+fn address_value(addr: &IPAddress) -> u128 {
+    match addr.as_ip_addr() {
+        IpAddr::V4(addr) => u32::from(*addr) as u128,
+        IpAddr::V6(addr) => u128::from(*addr),
+    }
+}
+
+fn value_address(value: u128, family: u8) -> IPAddress {
+    if family == 4 {
+        IPAddress::new_v4(Ipv4Addr::from(value as u32))
+    } else {
+        IPAddress::new_v6(Ipv6Addr::from(value))
+    }
+}
+
+fn to_u32(value: usize, what: &str) -> u32 {
+    u32::try_from(value).unwrap_or_else(|_| panic!("{} does not fit the binary format", what))
+}
+
+/// An encoded section before it is framed into the output
+struct SectionBuf {
+    family: u8,
+    count: usize,
+    index: Vec<u8>,
+    data: Vec<u8>,
+}
+
+fn frame(kind: u8, sections: Vec<SectionBuf>) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    out.push(kind);
+    out.push(sections.len() as u8);
+
+    for section in sections {
+        let entry_len = address_width(section.family) + 4;
+        out.push(section.family);
+        out.extend_from_slice(&[0; 3]);
+        out.extend_from_slice(&to_u32(section.count, "entry count").to_le_bytes());
+        out.extend_from_slice(&to_u32(section.index.len() / entry_len, "index").to_le_bytes());
+        out.extend_from_slice(&to_u32(section.data.len(), "section data").to_le_bytes());
+        out.extend_from_slice(&section.index);
+        out.extend_from_slice(&section.data);
+    }
+
+    let checksum = crc32(&out);
+    out.extend_from_slice(&checksum.to_le_bytes());
+    out
+}
+
+/// A section borrowed from encoded bytes
+#[derive(Debug, Clone, Copy)]
+struct Section<'a> {
+    family: u8,
+    count: usize,
+    index: &'a [u8],
+    data: &'a [u8],
+}
+
+impl<'a> Section<'a> {
+    fn index_len(&self) -> usize {
+        self.index.len() / (address_width(self.family) + 4)
+    }
+
+    /// Absolute start address and data offset of index entry `i`
+    fn index_entry(&self, i: usize) -> (u128, usize) {
+        let width = address_width(self.family);
+        let entry = &self.index[i * (width + 4)..(i + 1) * (width + 4)];
+        let mut start = [0u8; 16];
+        start[..width].copy_from_slice(&entry[..width]);
+        let mut offset = [0u8; 4];
+        offset.copy_from_slice(&entry[width..]);
+        (u128::from_le_bytes(start), u32::from_le_bytes(offset) as usize)
+    }
+}
+
+fn read_u32(bytes: &[u8], pos: usize) -> u32 {
+    let mut buf = [0u8; 4];
+    buf.copy_from_slice(&bytes[pos..pos + 4]);
+    u32::from_le_bytes(buf)
+}
+
+/// Split encoded bytes into sections, checking the framing and optionally the checksum
+fn parse_sections(bytes: &[u8], kind: u8, verify_checksum: bool) -> AddrResult<Vec<Section<'_>>> {
+    if bytes.len() < HEADER_LEN + CHECKSUM_LEN {
+        return Err(format_error("input is too short"));
+    }
+    if &bytes[..4] != MAGIC {
+        return Err(format_error("bad magic number"));
+    }
+    let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+    if version != FORMAT_VERSION {
+        return Err(format_error(format!("unsupported format version {}", version)));
+    }
+    if bytes[6] != kind {
+        return Err(format_error(format!("unexpected content kind {}", bytes[6])));
+    }
+
+    let body_len = bytes.len() - CHECKSUM_LEN;
+    if verify_checksum && crc32(&bytes[..body_len]) != read_u32(bytes, body_len) {
+        return Err(format_error("checksum mismatch"));
+    }
+
+    let mut sections = Vec::new();
+    let mut pos = HEADER_LEN;
+    for _ in 0..bytes[7] {
+        if body_len - pos < SECTION_HEADER_LEN {
+            return Err(format_error("truncated section header"));
+        }
+        let family = bytes[pos];
+        if family != 4 && family != 6 {
+            return Err(format_error(format!("unknown address family {}", family)));
+        }
+        if sections.last().is_some_and(|last: &Section| last.family >= family) {
+            return Err(format_error("sections out of order"));
+        }
+        let count = read_u32(bytes, pos + 4) as usize;
+        let index_len = (read_u32(bytes, pos + 8) as usize)
+            .checked_mul(address_width(family) + 4)
+            .ok_or_else(|| format_error("index too large"))?;
+        let data_len = read_u32(bytes, pos + 12) as usize;
+        pos += SECTION_HEADER_LEN;
+
+        if body_len - pos < index_len || body_len - pos - index_len < data_len {
+            return Err(format_error("truncated section"));
+        }
+        sections.push(Section {
+            family,
+            count,
+            index: &bytes[pos..pos + index_len],
+            data: &bytes[pos + index_len..pos + index_len + data_len],
+        });
+        pos += index_len + data_len;
+    }
+
+    if pos != body_len {
+        return Err(format_error("trailing bytes after last section"));
+    }
+    Ok(sections)
+}
+
+/// Encode an IP set
+pub fn encode_ip_set(set: &IPSet) -> Vec<u8> {
+    let mut sections: Vec<SectionBuf> = Vec::new();
+    let mut last_end = 0u128;
+
+    for range in set.iter_ranges() {
+        let family = family_of(range.start());
+        if sections.last().is_none_or(|section| section.family != family) {
+            sections.push(SectionBuf {
+                family,
+                count: 0,
+                index: Vec::new(),
+                data: Vec::new(),
+            });
+        }
+        let section = sections.last_mut().unwrap();
+
+        let start = address_value(range.start());
+        let end = address_value(range.end());
+        let expected = if section.count.is_multiple_of(BLOCK_SIZE) {
+            section.index.extend_from_slice(&start.to_le_bytes()[..address_width(family)]);
+            section.index.extend_from_slice(&to_u32(section.data.len(), "section data").to_le_bytes());
+            start
+        } else {
+            // Ranges in a set are disjoint and non-adjacent, so this cannot overflow
+            last_end + 1
+        };
+        write_varint(&mut section.data, start - expected);
+        write_varint(&mut section.data, end - start);
+        last_end = end;
+        section.count += 1;
+    }
+
+    frame(KIND_IP_SET, sections)
+}
+
+/// Decode an IP set
+pub fn decode_ip_set(bytes: &[u8]) -> AddrResult<IPSet> {
+    Ok(IPSetView::new(bytes)?.iter_ranges().collect())
+}
+
+/// Decoder for the ranges of one IP set section
+#[derive(Debug, Clone)]
+struct RangeDecoder<'a> {
+    section: Section<'a>,
+    pos: usize,
+    next_index: usize,
+    expected: u128,
+    remaining: usize,
+}
+
+impl<'a> RangeDecoder<'a> {
+    /// Start decoding at the beginning of block `block`
+    fn at_block(section: Section<'a>, block: usize) -> Self {
+        let (_, pos) = section.index_entry(block);
+        Self {
+            section,
+            pos,
+            next_index: block * BLOCK_SIZE,
+            expected: 0,
+            remaining: section.count - block * BLOCK_SIZE,
+        }
+    }
+
+    /// Decode the next range, reporting corrupt data as an error
+    fn try_next(&mut self) -> AddrResult<Option<(u128, u128)>> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+
+        if self.next_index.is_multiple_of(BLOCK_SIZE) {
+            let (start, offset) = self.section.index_entry(self.next_index / BLOCK_SIZE);
+            if offset != self.pos {
+                return Err(format_error("index offset does not match data"));
+            }
+            self.expected = start;
+        }
+
+        let gap = read_varint(self.section.data, &mut self.pos)
+            .ok_or_else(|| format_error("truncated or oversized varint"))?;
+        let len = read_varint(self.section.data, &mut self.pos)
+            .ok_or_else(|| format_error("truncated or oversized varint"))?;
+        let start = self.expected.checked_add(gap);
+        let end = start.and_then(|start| start.checked_add(len));
+        let (start, end) = match (start, end) {
+            (Some(start), Some(end)) if end <= max_value(self.section.family) => (start, end),
+            _ => return Err(format_error("range exceeds the address space")),
+        };
+
+        self.remaining -= 1;
+        self.next_index += 1;
+        if self.remaining > 0 {
+            self.expected = end
+                .checked_add(1)
+                .ok_or_else(|| format_error("ranges continue past the end of the address space"))?;
+        }
+        Ok(Some((start, end)))
+    }
+}
+
+impl<'a> Iterator for RangeDecoder<'a> {
+    type Item = (u128, u128);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Views are validated when created, so decoding cannot fail here
+        self.try_next().ok().flatten()
+    }
+}
+
+/// A read-only IP set backed directly by its binary encoding
+///
+/// Creating a view checks the checksum and decodes every range once to
+/// validate the data; membership queries then binary search the block index
+/// and decode at most one block of 64 ranges, without allocating.
+#[derive(Debug, Clone, Copy)]
+pub struct IPSetView<'a> {
+    ipv4: Option<Section<'a>>,
+    ipv6: Option<Section<'a>>,
+}
+
+impl<'a> IPSetView<'a> {
+    /// Create a view over encoded bytes, validating them
+    pub fn new(bytes: &'a [u8]) -> AddrResult<Self> {
+        let view = Self::from_sections(parse_sections(bytes, KIND_IP_SET, true)?);
+
+        for section in view.ipv4.iter().chain(view.ipv6.iter()) {
+            if section.index_len() != section.count.div_ceil(BLOCK_SIZE) {
+                return Err(format_error("index does not match entry count"));
+            }
+            if section.count == 0 {
+                continue;
+            }
+
+            let mut decoder = RangeDecoder::at_block(*section, 0);
+            let mut last_end: Option<u128> = None;
+            while let Some((start, end)) = decoder.try_next()? {
+                if last_end.is_some_and(|last_end| start <= last_end) {
+                    return Err(format_error("ranges are not in ascending order"));
+                }
+                last_end = Some(end);
+            }
+            if decoder.pos != section.data.len() {
+                return Err(format_error("trailing bytes in section data"));
+            }
+        }
+
+        Ok(view)
+    }
+
+    /// Create a view over bytes that were already validated by `new`
+    #[cfg(feature = "mmap")]
+    fn new_prevalidated(bytes: &'a [u8]) -> Self {
+        let sections = parse_sections(bytes, KIND_IP_SET, false).expect("data was validated when opened");
+        Self::from_sections(sections)
+    }
+
+    fn from_sections(sections: Vec<Section<'a>>) -> Self {
+        let mut view = Self { ipv4: None, ipv6: None };
+        for section in sections {
+            if section.family == 4 {
+                view.ipv4 = Some(section);
+            } else {
+                view.ipv6 = Some(section);
+            }
+        }
+        view
+    }
+
+    fn section(&self, family: u8) -> Option<Section<'a>> {
+        if family == 4 {
+            self.ipv4
+        } else {
+            self.ipv6
+        }
+    }
+
+    /// Find the stored range containing `value`
+    fn find(&self, family: u8, value: u128) -> Option<(u128, u128)> {
+        let section = self.section(family)?;
+
+        // Find the last block starting at or before the value
+        let (mut lo, mut hi) = (0, section.index_len());
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if section.index_entry(mid).0 <= value {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        let block = lo.checked_sub(1)?;
+
+        RangeDecoder::at_block(section, block)
+            .take(BLOCK_SIZE)
+            .take_while(|&(start, _)| start <= value)
+            .find(|&(_, end)| value <= end)
+    }
+
+    /// Check if an address is in the set
+    pub fn contains(&self, addr: &IPAddress) -> bool {
+        addr.zone().is_none() && self.find(family_of(addr), address_value(addr)).is_some()
+    }
+
+    /// Check if a whole network is in the set
+    pub fn contains_network(&self, network: &IPNetwork) -> bool {
+        let range = IPRange::from(network);
+        network.network_address().zone().is_none() && self.contains_range(&range)
+    }
+
+    /// Check if a whole range is in the set
+    pub fn contains_range(&self, range: &IPRange) -> bool {
+        if range.start().zone().is_some() || range.end().zone().is_some() {
+            return false;
+        }
+        let family = family_of(range.start());
+        self.find(family, address_value(range.start()))
+            .is_some_and(|(_, end)| end >= address_value(range.end()))
+    }
+
+    /// Get the number of disjoint ranges in the set
+    pub fn range_count(&self) -> usize {
+        self.ipv4.map_or(0, |s| s.count) + self.ipv6.map_or(0, |s| s.count)
+    }
+
+    /// Check if the set is empty
+    pub fn is_empty(&self) -> bool {
+        self.range_count() == 0
+    }
+
+    /// Iterate over the ranges of the set in order
+    pub fn iter_ranges(&self) -> impl Iterator<Item = IPRange> + 'a {
+        [self.ipv4, self.ipv6]
+            .into_iter()
+            .flatten()
+            .filter(|section| section.count > 0)
+            .flat_map(|section| {
+                RangeDecoder::at_block(section, 0).map(move |(start, end)| {
+                    IPRange::new_unchecked(
+                        value_address(start, section.family),
+                        value_address(end, section.family),
+                    )
+                })
+            })
+    }
+
+    /// Decode the view into an owned `IPSet`
+    pub fn to_ip_set(&self) -> IPSet {
+        self.iter_ranges().collect()
+    }
+}
+
+/// Encode a prefix map, converting each value to bytes with `encode_value`
+pub fn encode_prefix_map<V, F>(map: &PrefixMap<V>, mut encode_value: F) -> Vec<u8>
+where
+    F: FnMut(&V) -> Vec<u8>,
+{
+    let mut sections: Vec<SectionBuf> = Vec::new();
+    let mut previous = 0u128;
+
+    for (network, value) in map.iter() {
+        let family = family_of(network.network_address());
+        if sections.last().is_none_or(|section| section.family != family) {
+            sections.push(SectionBuf {
+                family,
+                count: 0,
+                index: Vec::new(),
+                data: Vec::new(),
+            });
+            previous = 0;
+        }
+        let section = sections.last_mut().unwrap();
+
+        // Entries are ordered by network address, so the delta is never negative
+        let address = address_value(network.network_address());
+        write_varint(&mut section.data, address - previous);
+        section.data.push(network.prefix_length());
+        let value = encode_value(value);
+        write_varint(&mut section.data, value.len() as u128);
+        section.data.extend_from_slice(&value);
+        previous = address;
+        section.count += 1;
+    }
+
+    frame(KIND_PREFIX_MAP, sections)
+}
+
+/// Decode a prefix map, converting each value from bytes with `decode_value`
+pub fn decode_prefix_map<V, F>(bytes: &[u8], mut decode_value: F) -> AddrResult<PrefixMap<V>>
+where
+    F: FnMut(&[u8]) -> AddrResult<V>,
+{
+    let mut map = PrefixMap::new();
+
+    for section in parse_sections(bytes, KIND_PREFIX_MAP, true)? {
+        let data = section.data;
+        let mut pos = 0;
+        let mut previous = 0u128;
+
+        for _ in 0..section.count {
+            let address = read_varint(data, &mut pos)
+                .and_then(|delta| previous.checked_add(delta))
+                .filter(|&address| address <= max_value(section.family))
+                .ok_or_else(|| format_error("invalid network address"))?;
+            let prefix_length = *data.get(pos).ok_or_else(|| format_error("truncated entry"))?;
+            pos += 1;
+            let len = read_varint(data, &mut pos)
+                .and_then(|len| usize::try_from(len).ok())
+                .filter(|&len| len <= data.len() - pos)
+                .ok_or_else(|| format_error("truncated value"))?;
+            let value = decode_value(&data[pos..pos + len])?;
+            pos += len;
+
+            let network = IPNetwork::new(value_address(address, section.family), prefix_length)?;
+            if address_value(network.network_address()) != address {
+                return Err(format_error(format!("host bits set in {}/{}", value_address(address, section.family), prefix_length)));
+            }
+            map.insert(network, value);
+            previous = address;
+        }
+
+        if pos != data.len() {
+            return Err(format_error("trailing bytes in section data"));
+        }
+    }
+
+    Ok(map)
+}
+
+/// An IP set view over a memory-mapped file
+#[cfg(feature = "mmap")]
+pub struct MappedIPSet {
+    map: memmap2::Mmap,
+}
+
+#[cfg(feature = "mmap")]
+impl MappedIPSet {
+    /// Map an encoded IP set file and validate it
+    ///
+    /// The file must not be modified while it is mapped.
+    pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let file = std::fs::File::open(path)?;
+        // Safety: the mapping is read-only and the caller guarantees that the
+        // file is not modified while it is mapped
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        IPSetView::new(&map)?;
+        Ok(Self { map })
+    }
+
+    /// Get a view of the mapped set
+    pub fn view(&self) -> IPSetView<'_> {
+        IPSetView::new_prevalidated(&self.map)
+    }
+
+    /// Check if an address is in the set
+    pub fn contains(&self, addr: &IPAddress) -> bool {
+        self.view().contains(addr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn sample_set() -> IPSet {
+        let mut set = IPSet::new();
+        for i in 0..200u32 {
+            let addr = IPAddress::new_v4(Ipv4Addr::from(0x0a00_0000 + i * 4));
+            set.add_address(addr).unwrap();
+        }
+        set.add_network(IPNetwork::from_str("192.168.0.0/16").unwrap()).unwrap();
+        set.add_network(IPNetwork::from_str("2001:db8::/32").unwrap()).unwrap();
+        set.add_network(IPNetwork::from_str("ff00::/8").unwrap()).unwrap();
+        set
+    }
+
+    #[test]
+    fn test_ip_set_round_trip() {
+        let set = sample_set();
+        let bytes = encode_ip_set(&set);
+        assert_eq!(&bytes[..4], b"NAIP");
+        assert_eq!(decode_ip_set(&bytes).unwrap(), set);
+
+        let empty = encode_ip_set(&IPSet::new());
+        assert!(decode_ip_set(&empty).unwrap().is_empty());
+
+        let all = IPSet::from_network(IPNetwork::from_str("::/0").unwrap()).unwrap();
+        assert_eq!(decode_ip_set(&encode_ip_set(&all)).unwrap(), all);
+    }
+
+    #[test]
+    fn test_ip_set_view() {
+        let set = sample_set();
+        let bytes = encode_ip_set(&set);
+        let view = IPSetView::new(&bytes).unwrap();
+        assert_eq!(view.range_count(), 203);
+
+        for addr in ["10.0.0.0", "10.0.3.28", "192.168.255.255", "2001:db8::1", "ff02::1"] {
+            assert!(view.contains(&IPAddress::from_str(addr).unwrap()), "{}", addr);
+        }
+        for addr in ["10.0.0.1", "10.0.3.32", "9.255.255.255", "192.169.0.0", "::1", "2001:db9::", "ff02::1%eth0"] {
+            assert!(!view.contains(&IPAddress::from_str(addr).unwrap()), "{}", addr);
+        }
+        assert!(view.contains_network(&IPNetwork::from_str("192.168.10.0/24").unwrap()));
+        assert!(!view.contains_network(&IPNetwork::from_str("192.168.0.0/15").unwrap()));
+        assert!(!view.contains_network(&IPNetwork::from_str("ff02::%eth0/64").unwrap()));
+        assert_eq!(view.to_ip_set(), set);
+    }
+
+    #[test]
+    fn test_corrupt_data() {
+        let bytes = encode_ip_set(&sample_set());
+
+        let mut flipped = bytes.clone();
+        flipped[20] ^= 1;
+        assert!(IPSetView::new(&flipped).unwrap_err().to_string().contains("checksum"));
+
+        let mut version = bytes.clone();
+        version[4] = 2;
+        assert!(decode_ip_set(&version).unwrap_err().to_string().contains("version"));
+
+        assert!(decode_ip_set(&bytes[..bytes.len() - 1]).is_err());
+        assert!(decode_ip_set(b"NAIP").is_err());
+        assert!(decode_prefix_map(&bytes, |_| Ok(())).is_err());
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_mapped_ip_set() {
+        let path = std::env::temp_dir().join(format!("netaddr-ipset-{}.bin", std::process::id()));
+        std::fs::write(&path, sample_set().to_binary()).unwrap();
+
+        let mapped = MappedIPSet::open(&path).unwrap();
+        assert!(mapped.contains(&IPAddress::from_str("192.168.1.1").unwrap()));
+        assert!(!mapped.contains(&IPAddress::from_str("172.16.0.1").unwrap()));
+        assert_eq!(mapped.view().range_count(), 203);
+        drop(mapped);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_prefix_map_round_trip() {
+        let map: PrefixMap<String> = [
+            ("10.0.0.0/8", "private"),
+            ("10.1.0.0/16", "lab"),
+            ("0.0.0.0/0", "default"),
+            ("2001:db8::/32", "documentation"),
+        ]
+        .iter()
+        .map(|(net, name)| (IPNetwork::from_str(net).unwrap(), name.to_string()))
+        .collect();
+
+        let bytes = encode_prefix_map(&map, |value| value.as_bytes().to_vec());
+        let decoded = decode_prefix_map(&bytes, |bytes| {
+            String::from_utf8(bytes.to_vec()).map_err(|e| AddrFormatError::new(e.to_string()))
+        })
+        .unwrap();
+        assert_eq!(decoded, map);
+    }
+}
@@ -377,7 +377,9 @@ fn handle_eui_command(cmd: &EuiCommands) -> Result<(), Box<dyn std::error::Error
                 println!("Vendor: Unknown");
             }
 
-            if let Some(info) = crate::eui::ieee::lookup_oui_info(&oui).ok() {
+            if let Some(registration) = eui.as_mac48().and_then(|mac| mac.registration()) {
+                println!("Organization: {}", registration.organization());
+            } else if let Ok(info) = crate::eui::ieee::lookup_oui_info(&oui) {
                 println!("Organization: {}", info.organization);
             }
         }
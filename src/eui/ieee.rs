@@ -1,9 +1,11 @@
 //! IEEE registry information for OUI and IAB lookups
 
-use crate::error::{NotRegisteredError, RegistryResult};
-use crate::eui::{OUI, IAB};
+use crate::error::{AddrFormatError, AddrResult, NotRegisteredError, RegistryResult};
+use crate::eui::{OUI, IAB, MAC};
+use crate::registry::RegistryHandle;
 use lazy_static::lazy_static;
-use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
 
 /// OUI registry information
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -23,27 +25,253 @@ pub struct IABRegistryInfo {
     pub iab_range_end: String,
 }
 
+/// MA-M and MA-S registry information
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MARegistryInfo {
+    pub oui: String,
+    pub organization: String,
+    pub address: Vec<String>,
+    pub block_size: MABlockSize,
+    pub range_start: String,
+    pub range_end: String,
+}
+
+/// Size of an IEEE assignment carved out of a 24-bit OUI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MABlockSize {
+    /// MA-M: 28-bit prefix, 2^20 addresses
+    Medium,
+    /// MA-S: 36-bit prefix, 2^12 addresses
+    Small,
+}
+
+impl MABlockSize {
+    /// Number of leading bits of a MAC-48 address fixed by the assignment
+    pub fn prefix_bits(&self) -> u8 {
+        match self {
+            MABlockSize::Medium => 28,
+            MABlockSize::Small => 36,
+        }
+    }
+}
+
+/// A single record parsed from an IEEE registration text file
+///
+/// The same layout is used by `oui.txt` (MA-L), `mam.txt` (MA-M),
+/// `oui36.txt` (MA-S) and `iab.txt`:
+///
+/// ```text
+/// 00-50-C2   (hex)        ACME CORPORATION
+/// ABC000-ABCFFF     (base 16)        ACME CORPORATION
+///                 1 MAIN STREET
+///                 SPRINGFIELD
+///                 UNITED STATES
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IEEERecord {
+    /// The 24-bit OUI the assignment belongs to
+    pub oui: u32,
+    /// The assigned range of the lower 24 bits, or `None` for a full MA-L block
+    pub range: Option<(u32, u32)>,
+    pub organization: String,
+    pub address: Vec<String>,
+}
+
+impl IEEERecord {
+    /// Number of leading bits of a MAC-48 address fixed by this assignment
+    pub fn prefix_bits(&self) -> u8 {
+        match self.range {
+            None => 24,
+            Some((start, end)) => 48 - (32 - (end - start).leading_zeros()) as u8,
+        }
+    }
+
+    /// The assignment as a 48-bit prefix value (the first `prefix_bits` bits)
+    fn prefix_value(&self) -> u64 {
+        let start = self.range.map(|(start, _)| start).unwrap_or(0);
+        let mac = ((self.oui as u64) << 24) | start as u64;
+        mac >> (48 - self.prefix_bits())
+    }
+
+    fn oui_string(&self) -> String {
+        format_mac_prefix(self.oui as u64, 3)
+    }
+
+    fn range_strings(&self) -> (String, String) {
+        let (start, end) = self.range.unwrap_or((0, 0xffffff));
+        let base = (self.oui as u64) << 24;
+        (
+            format_mac_prefix(base | start as u64, 6),
+            format_mac_prefix(base | end as u64, 6),
+        )
+    }
+}
+
+/// Format the lower `len` bytes of `value` as colon separated upper case hex
+fn format_mac_prefix(value: u64, len: usize) -> String {
+    (0..len)
+        .rev()
+        .map(|i| format!("{:02X}", (value >> (i * 8)) & 0xff))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Parse the records of an IEEE registration text file
+///
+/// Header lines before the first `(hex)` marker are ignored.
+pub fn parse_ieee_records(data: &str) -> AddrResult<Vec<IEEERecord>> {
+    let mut records = Vec::new();
+    let mut current: Option<IEEERecord> = None;
+    let mut skip_header = true;
+
+    for (line_no, raw_line) in data.lines().enumerate() {
+        let line = raw_line.trim_end();
+
+        if skip_header {
+            if !line.contains("(hex)") {
+                continue;
+            }
+            skip_header = false;
+        }
+
+        if let Some(pos) = line.find("(hex)") {
+            records.extend(current.take());
+
+            let oui_str = line[..pos].trim().replace('-', "");
+            let oui = parse_hex_field(&oui_str, 6, line_no)?;
+            current = Some(IEEERecord {
+                oui,
+                range: None,
+                organization: line[pos + "(hex)".len()..].trim().to_string(),
+                address: Vec::new(),
+            });
+        } else if let Some(pos) = line.find("(base 16)") {
+            let record = current.as_mut().ok_or_else(|| {
+                AddrFormatError::new(format!("Line {}: '(base 16)' without a preceding '(hex)' line", line_no + 1))
+            })?;
+
+            let field = line[..pos].trim();
+            if let Some((start, end)) = field.split_once('-') {
+                let start = parse_hex_field(start, 6, line_no)?;
+                let end = parse_hex_field(end, 6, line_no)?;
+                let size = end.wrapping_sub(start).wrapping_add(1);
+                if end < start || !size.is_power_of_two() || start & (size - 1) != 0 {
+                    return Err(AddrFormatError::new(format!(
+                        "Line {}: '{}' is not an aligned assignment block",
+                        line_no + 1,
+                        field
+                    )));
+                }
+                record.range = Some((start, end));
+            } else if parse_hex_field(field, 6, line_no)? != record.oui {
+                return Err(AddrFormatError::new(format!(
+                    "Line {}: base 16 value '{}' does not match the record OUI",
+                    line_no + 1,
+                    field
+                )));
+            }
+        } else if line.trim().is_empty() {
+            records.extend(current.take());
+        } else if let Some(record) = current.as_mut() {
+            record.address.push(line.trim().to_string());
+        }
+    }
+
+    records.extend(current);
+    Ok(records)
+}
+
+fn parse_hex_field(field: &str, digits: usize, line_no: usize) -> AddrResult<u32> {
+    if field.len() != digits || !field.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(AddrFormatError::new(format!(
+            "Line {}: invalid hex value '{}'",
+            line_no + 1,
+            field
+        )));
+    }
+    u32::from_str_radix(field, 16).map_err(|e| {
+        AddrFormatError::new(format!("Line {}: invalid hex value '{}': {}", line_no + 1, field, e))
+    })
+}
+
+/// Compact lookup table of registry entries sorted by their prefix value
+#[derive(Debug, Clone)]
+struct PrefixIndex<T> {
+    entries: Vec<(u64, T)>,
+}
+
+impl<T> PrefixIndex<T> {
+    fn new() -> Self {
+        Self { entries: Vec::new() }
+    }
+
+    fn insert(&mut self, key: u64, value: T) {
+        match self.entries.binary_search_by_key(&key, |(k, _)| *k) {
+            Ok(idx) => self.entries[idx].1 = value,
+            Err(idx) => self.entries.insert(idx, (key, value)),
+        }
+    }
+
+    /// Bulk insert, sorting once; later entries win over earlier ones
+    fn extend(&mut self, items: impl IntoIterator<Item = (u64, T)>) {
+        self.entries.extend(items);
+        // Stable sort keeps insertion order among equal keys
+        self.entries.sort_by_key(|(k, _)| *k);
+        let mut deduped: Vec<(u64, T)> = Vec::with_capacity(self.entries.len());
+        for entry in self.entries.drain(..) {
+            match deduped.last_mut() {
+                Some(last) if last.0 == entry.0 => *last = entry,
+                _ => deduped.push(entry),
+            }
+        }
+        self.entries = deduped;
+    }
+
+    fn get(&self, key: u64) -> Option<&T> {
+        self.entries
+            .binary_search_by_key(&key, |(k, _)| *k)
+            .ok()
+            .map(|idx| &self.entries[idx].1)
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &(u64, T)> {
+        self.entries.iter()
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
 /// Registry for OUI lookups
+#[derive(Clone)]
 pub struct OUIRegistry {
-    registry: HashMap<u32, OUIRegistryInfo>,
+    registry: PrefixIndex<OUIRegistryInfo>,
 }
 
 impl OUIRegistry {
     /// Create a new empty registry
     pub fn new() -> Self {
         Self {
-            registry: HashMap::new(),
+            registry: PrefixIndex::new(),
         }
     }
 
+    /// Create a registry from the contents of an IEEE `oui.txt` file
+    pub fn from_ieee_txt(data: &str) -> AddrResult<Self> {
+        let mut registry = Self::new();
+        registry.load_from_ieee_txt(data)?;
+        Ok(registry)
+    }
+
     /// Add an OUI entry to the registry
     pub fn add_entry(&mut self, oui_value: u32, info: OUIRegistryInfo) {
-        self.registry.insert(oui_value, info);
+        self.registry.insert(oui_value as u64, info);
     }
 
     /// Look up OUI information
     pub fn lookup_oui(&self, oui: &OUI) -> Option<OUIRegistryInfo> {
-        self.registry.get(&oui.to_u32()).cloned()
+        self.registry.get(oui.to_u32() as u64).cloned()
     }
 
     /// Look up OUI by organization name (partial match)
@@ -52,7 +280,7 @@ impl OUIRegistry {
         self.registry
             .iter()
             .filter(|(_, info)| info.organization.to_lowercase().contains(&search_term))
-            .map(|(oui_val, info)| (OUI::from_u32(*oui_val), info.clone()))
+            .map(|(oui_val, info)| (OUI::from_u32(*oui_val as u32), info.clone()))
             .collect()
     }
 
@@ -60,10 +288,49 @@ impl OUIRegistry {
     pub fn all_entries(&self) -> Vec<(OUI, OUIRegistryInfo)> {
         self.registry
             .iter()
-            .map(|(oui_val, info)| (OUI::from_u32(*oui_val), info.clone()))
+            .map(|(oui_val, info)| (OUI::from_u32(*oui_val as u32), info.clone()))
             .collect()
     }
 
+    /// Number of entries in the registry
+    pub fn len(&self) -> usize {
+        self.registry.len()
+    }
+
+    /// Check if the registry has no entries
+    pub fn is_empty(&self) -> bool {
+        self.registry.len() == 0
+    }
+
+    /// Load MA-L records from the contents of an IEEE `oui.txt` file
+    ///
+    /// Records describing MA-M, MA-S or IAB blocks are skipped. Returns the
+    /// number of entries loaded.
+    pub fn load_from_ieee_txt(&mut self, data: &str) -> AddrResult<usize> {
+        let entries: Vec<_> = parse_ieee_records(data)?
+            .into_iter()
+            .filter(|record| record.range.is_none())
+            .map(|record| {
+                let info = OUIRegistryInfo {
+                    oui: record.oui_string(),
+                    organization: record.organization,
+                    address: record.address,
+                };
+                (record.oui as u64, info)
+            })
+            .collect();
+
+        let count = entries.len();
+        self.registry.extend(entries);
+        Ok(count)
+    }
+
+    /// Load MA-L records from an IEEE `oui.txt` file on disk
+    pub fn load_from_file(&mut self, path: impl AsRef<Path>) -> Result<usize, Box<dyn std::error::Error>> {
+        let data = fs::read_to_string(path)?;
+        Ok(self.load_from_ieee_txt(&data)?)
+    }
+
     /// Load from CSV data
     pub fn load_from_csv(&mut self, csv_data: &str) -> Result<(), Box<dyn std::error::Error>> {
         for line in csv_data.lines().skip(1) { // Skip header
@@ -91,26 +358,50 @@ impl Default for OUIRegistry {
 }
 
 /// Registry for IAB lookups
+///
+/// Entries are keyed by the 36-bit IAB prefix (the OUI followed by the
+/// 12-bit block extension).
+#[derive(Clone)]
 pub struct IABRegistry {
-    registry: HashMap<(u32, u8), IABRegistryInfo>,
+    registry: PrefixIndex<IABRegistryInfo>,
 }
 
 impl IABRegistry {
     /// Create a new empty registry
     pub fn new() -> Self {
         Self {
-            registry: HashMap::new(),
+            registry: PrefixIndex::new(),
         }
     }
 
+    /// Create a registry from the contents of an IEEE `iab.txt` file
+    pub fn from_ieee_txt(data: &str) -> AddrResult<Self> {
+        let mut registry = Self::new();
+        registry.load_from_ieee_txt(data)?;
+        Ok(registry)
+    }
+
     /// Add an IAB entry to the registry
-    pub fn add_entry(&mut self, oui_value: u32, extension: u8, info: IABRegistryInfo) {
-        self.registry.insert((oui_value, extension), info);
+    pub fn add_entry(&mut self, oui_value: u32, extension: u16, info: IABRegistryInfo) {
+        self.registry.insert(Self::key(oui_value, extension), info);
+    }
+
+    fn key(oui_value: u32, extension: u16) -> u64 {
+        ((oui_value as u64) << 12) | (extension as u64 & 0xfff)
+    }
+
+    fn iab_from_key(key: u64) -> IAB {
+        IAB::new(OUI::from_u32((key >> 12) as u32), (key & 0xfff) as u16)
     }
 
     /// Look up IAB information
     pub fn lookup_iab(&self, iab: &IAB) -> Option<IABRegistryInfo> {
-        self.registry.get(&(iab.oui().to_u32(), iab.extension())).cloned()
+        self.registry.get(Self::key(iab.oui().to_u32(), iab.extension())).cloned()
+    }
+
+    /// Look up IAB information for a MAC address
+    pub fn lookup_mac(&self, mac: &MAC) -> Option<IABRegistryInfo> {
+        self.registry.get(mac_to_u64(mac) >> 12).cloned()
     }
 
     /// Look up IAB by organization name (partial match)
@@ -119,10 +410,7 @@ impl IABRegistry {
         self.registry
             .iter()
             .filter(|(_, info)| info.organization.to_lowercase().contains(&search_term))
-            .map(|((oui_val, ext), info)| {
-                let iab = IAB::new(OUI::from_u32(*oui_val), *ext);
-                (iab, info.clone())
-            })
+            .map(|(key, info)| (Self::iab_from_key(*key), info.clone()))
             .collect()
     }
 
@@ -130,12 +418,51 @@ impl IABRegistry {
     pub fn all_entries(&self) -> Vec<(IAB, IABRegistryInfo)> {
         self.registry
             .iter()
-            .map(|((oui_val, ext), info)| {
-                let iab = IAB::new(OUI::from_u32(*oui_val), *ext);
-                (iab, info.clone())
-            })
+            .map(|(key, info)| (Self::iab_from_key(*key), info.clone()))
             .collect()
     }
+
+    /// Number of entries in the registry
+    pub fn len(&self) -> usize {
+        self.registry.len()
+    }
+
+    /// Check if the registry has no entries
+    pub fn is_empty(&self) -> bool {
+        self.registry.len() == 0
+    }
+
+    /// Load IAB records from the contents of an IEEE `iab.txt` file
+    ///
+    /// Only 36-bit blocks are loaded. Returns the number of entries loaded.
+    pub fn load_from_ieee_txt(&mut self, data: &str) -> AddrResult<usize> {
+        let entries: Vec<_> = parse_ieee_records(data)?
+            .into_iter()
+            .filter(|record| record.range.is_some() && record.prefix_bits() == 36)
+            .map(|record| {
+                let (iab_range_start, iab_range_end) = record.range_strings();
+                let key = record.prefix_value();
+                let info = IABRegistryInfo {
+                    oui: record.oui_string(),
+                    organization: record.organization,
+                    address: record.address,
+                    iab_range_start,
+                    iab_range_end,
+                };
+                (key, info)
+            })
+            .collect();
+
+        let count = entries.len();
+        self.registry.extend(entries);
+        Ok(count)
+    }
+
+    /// Load IAB records from an IEEE `iab.txt` file on disk
+    pub fn load_from_file(&mut self, path: impl AsRef<Path>) -> Result<usize, Box<dyn std::error::Error>> {
+        let data = fs::read_to_string(path)?;
+        Ok(self.load_from_ieee_txt(&data)?)
+    }
 }
 
 impl Default for IABRegistry {
@@ -144,125 +471,275 @@ impl Default for IABRegistry {
     }
 }
 
-lazy_static! {
-    /// Global OUI registry instance
-    pub static ref OUI_REGISTRY: OUIRegistry = {
-        let mut registry = OUIRegistry::new();
+/// Registry for MA-M (28-bit) and MA-S (36-bit) assignments
+#[derive(Clone)]
+pub struct MARegistry {
+    medium: PrefixIndex<MARegistryInfo>,
+    small: PrefixIndex<MARegistryInfo>,
+}
 
-        // Add some common OUI entries for testing and basic functionality
-        registry.add_entry(0x000000, OUIRegistryInfo {
-            oui: "00:00:00".to_string(),
-            organization: "Xerox Corporation".to_string(),
-            address: vec!["Xerox Systems Institute".to_string(), "475 Oakmead Parkway".to_string(), "Sunnyvale CA 94086".to_string()],
-        });
-
-        registry.add_entry(0x000001, OUIRegistryInfo {
-            oui: "00:00:01".to_string(),
-            organization: "Xerox Corporation".to_string(),
-            address: vec!["Xerox Systems Institute".to_string(), "475 Oakmead Parkway".to_string(), "Sunnyvale CA 94086".to_string()],
-        });
-
-        registry.add_entry(0x00001B, OUIRegistryInfo {
-            oui: "00:00:1B".to_string(),
-            organization: "Novell Inc.".to_string(),
-            address: vec!["Novell Inc.".to_string(), "1555 N. Technology Way".to_string(), "Orem UT 84057".to_string()],
-        });
-
-        registry.add_entry(0x00001C, OUIRegistryInfo {
-            oui: "00:00:1C".to_string(),
-            organization: "Corvus Systems Inc.".to_string(),
-            address: vec!["Corvus Systems Inc.".to_string()],
-        });
-
-        // Apple
-        registry.add_entry(0x001122, OUIRegistryInfo {
-            oui: "00:11:22".to_string(),
-            organization: "CIMSYS Inc".to_string(),
-            address: vec!["CIMSYS Inc".to_string()],
-        });
-
-        // Intel
-        registry.add_entry(0x001B21, OUIRegistryInfo {
-            oui: "00:1B:21".to_string(),
-            organization: "Intel Corporate".to_string(),
-            address: vec!["Intel Corporate".to_string(), "LAN Access Division".to_string(), "1501 S. MoPac Blvd.".to_string(), "Austin TX 78746".to_string()],
-        });
-
-        // Cisco
-        registry.add_entry(0x001F9E, OUIRegistryInfo {
-            oui: "00:1F:9E".to_string(),
-            organization: "Cisco Systems, Inc".to_string(),
-            address: vec!["Cisco Systems, Inc".to_string(), "170 W Tasman Dr".to_string(), "San Jose CA 95134".to_string()],
-        });
-
-        // Add more common vendors
-        registry.add_entry(0x00D0B7, OUIRegistryInfo {
-            oui: "00:D0:B7".to_string(),
-            organization: "Intel Corporation".to_string(),
-            address: vec!["Intel Corporation".to_string()],
-        });
-
-        registry.add_entry(0x001B63, OUIRegistryInfo {
-            oui: "00:1B:63".to_string(),
-            organization: "Apple, Inc.".to_string(),
-            address: vec!["Apple, Inc.".to_string(), "1 Infinite Loop".to_string(), "Cupertino CA 95014".to_string()],
-        });
-
-        registry
-    };
+impl MARegistry {
+    /// Create a new empty registry
+    pub fn new() -> Self {
+        Self {
+            medium: PrefixIndex::new(),
+            small: PrefixIndex::new(),
+        }
+    }
+
+    /// Create a registry from the contents of IEEE `mam.txt` and/or `oui36.txt` files
+    pub fn from_ieee_txt(data: &str) -> AddrResult<Self> {
+        let mut registry = Self::new();
+        registry.load_from_ieee_txt(data)?;
+        Ok(registry)
+    }
+
+    /// Look up the most specific MA-S or MA-M assignment covering a MAC address
+    pub fn lookup_mac(&self, mac: &MAC) -> Option<MARegistryInfo> {
+        let value = mac_to_u64(mac);
+        self.small
+            .get(value >> 12)
+            .or_else(|| self.medium.get(value >> 20))
+            .cloned()
+    }
+
+    /// Look up MA-M/MA-S assignments by organization name (partial match)
+    pub fn lookup_by_organization(&self, org_name: &str) -> Vec<MARegistryInfo> {
+        let search_term = org_name.to_lowercase();
+        self.medium
+            .iter()
+            .chain(self.small.iter())
+            .filter(|(_, info)| info.organization.to_lowercase().contains(&search_term))
+            .map(|(_, info)| info.clone())
+            .collect()
+    }
+
+    /// Number of entries in the registry
+    pub fn len(&self) -> usize {
+        self.medium.len() + self.small.len()
+    }
 
-    /// Global IAB registry instance
-    pub static ref IAB_REGISTRY: IABRegistry = {
-        let mut registry = IABRegistry::new();
+    /// Check if the registry has no entries
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Load MA-M and MA-S records from IEEE registration text
+    ///
+    /// Returns the number of entries loaded.
+    pub fn load_from_ieee_txt(&mut self, data: &str) -> AddrResult<usize> {
+        let mut medium = Vec::new();
+        let mut small = Vec::new();
+
+        for record in parse_ieee_records(data)? {
+            let block_size = match (record.range, record.prefix_bits()) {
+                (Some(_), 28) => MABlockSize::Medium,
+                (Some(_), 36) => MABlockSize::Small,
+                _ => continue,
+            };
+            let (range_start, range_end) = record.range_strings();
+            let key = record.prefix_value();
+            let info = MARegistryInfo {
+                oui: record.oui_string(),
+                organization: record.organization,
+                address: record.address,
+                block_size,
+                range_start,
+                range_end,
+            };
+            match block_size {
+                MABlockSize::Medium => medium.push((key, info)),
+                MABlockSize::Small => small.push((key, info)),
+            }
+        }
+
+        let count = medium.len() + small.len();
+        self.medium.extend(medium);
+        self.small.extend(small);
+        Ok(count)
+    }
+
+    /// Load MA-M and MA-S records from an IEEE registration file on disk
+    pub fn load_from_file(&mut self, path: impl AsRef<Path>) -> Result<usize, Box<dyn std::error::Error>> {
+        let data = fs::read_to_string(path)?;
+        Ok(self.load_from_ieee_txt(&data)?)
+    }
+}
+
+impl Default for MARegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The most specific IEEE assignment covering a MAC address
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Registration {
+    /// Individual Address Block (36-bit)
+    IAB(IABRegistryInfo),
+    /// MA-M (28-bit) or MA-S (36-bit) assignment
+    MA(MARegistryInfo),
+    /// MA-L (24-bit) assignment
+    OUI(OUIRegistryInfo),
+}
+
+impl Registration {
+    /// Name of the organization holding the assignment
+    pub fn organization(&self) -> &str {
+        match self {
+            Registration::IAB(info) => &info.organization,
+            Registration::MA(info) => &info.organization,
+            Registration::OUI(info) => &info.organization,
+        }
+    }
+
+    /// Postal address lines of the organization
+    pub fn address(&self) -> &[String] {
+        match self {
+            Registration::IAB(info) => &info.address,
+            Registration::MA(info) => &info.address,
+            Registration::OUI(info) => &info.address,
+        }
+    }
+}
+
+fn mac_to_u64(mac: &MAC) -> u64 {
+    mac.bytes().iter().fold(0u64, |acc, &b| (acc << 8) | b as u64)
+}
 
-        // Add some example IAB entries
-        registry.add_entry(0x0050C2, 0x00, IABRegistryInfo {
-            oui: "00:50:C2".to_string(),
-            organization: "IEEE Registration Authority".to_string(),
-            address: vec!["IEEE".to_string(), "445 Hoes Lane".to_string(), "Piscataway NJ 08854".to_string()],
-            iab_range_start: "00:50:C2:00:00:00".to_string(),
-            iab_range_end: "00:50:C2:00:0F:FF".to_string(),
-        });
+/// IEEE IAB registration data shipped with netaddr
+const BUNDLED_IAB_TXT: &str = include_str!("../../netaddr/eui/iab.txt");
 
-        registry
+lazy_static! {
+    /// Global OUI (MA-L) registry instance, empty until data is loaded
+    ///
+    /// The IEEE `oui.txt` is not bundled; load it with `reload_oui_registry`.
+    pub static ref OUI_REGISTRY: RegistryHandle<OUIRegistry> = RegistryHandle::default();
+
+    /// Global IAB registry instance, loaded from the bundled IEEE `iab.txt`
+    pub static ref IAB_REGISTRY: RegistryHandle<IABRegistry> = {
+        RegistryHandle::new(IABRegistry::from_ieee_txt(BUNDLED_IAB_TXT).expect("the bundled iab.txt is valid"))
     };
+
+    /// Global MA-M/MA-S registry instance, empty until data is loaded with `reload_ma_registry`
+    pub static ref MA_REGISTRY: RegistryHandle<MARegistry> = RegistryHandle::default();
 }
 
 /// Public API functions for OUI/IAB lookups
 
-/// Look up OUI information by MAC address or EUI
+/// Look up OUI information in the global registry
+///
+/// The global OUI registry starts empty, so every lookup fails until an IEEE
+/// `oui.txt` is loaded with `reload_oui_registry` (or CSV data with
+/// `load_oui_csv_data`).
 pub fn lookup_oui_info(oui: &OUI) -> RegistryResult<OUIRegistryInfo> {
-    OUI_REGISTRY.lookup_oui(oui)
+    lookup_oui_info_with(oui, &OUI_REGISTRY.get())
+}
+
+/// Look up OUI information in an explicit registry
+pub fn lookup_oui_info_with(oui: &OUI, registry: &OUIRegistry) -> RegistryResult<OUIRegistryInfo> {
+    registry.lookup_oui(oui)
         .ok_or_else(|| NotRegisteredError::new(format!("OUI {} not found in registry", oui)))
 }
 
 /// Look up IAB information
 pub fn lookup_iab_info(iab: &IAB) -> RegistryResult<IABRegistryInfo> {
-    IAB_REGISTRY.lookup_iab(iab)
+    lookup_iab_info_with(iab, &IAB_REGISTRY.get())
+}
+
+/// Look up IAB information in an explicit registry
+pub fn lookup_iab_info_with(iab: &IAB, registry: &IABRegistry) -> RegistryResult<IABRegistryInfo> {
+    registry.lookup_iab(iab)
         .ok_or_else(|| NotRegisteredError::new(format!("IAB {} not found in registry", iab)))
 }
 
+/// Look up the most specific IEEE assignment (IAB, MA-S, MA-M or MA-L) for a MAC address
+///
+/// Only the IAB registry is bundled; MA-L and MA-M/MA-S assignments are found
+/// once `oui.txt` and `mam.txt`/`oui36.txt` are loaded with
+/// `reload_oui_registry` and `reload_ma_registry`.
+pub fn lookup_mac_info(mac: &MAC) -> RegistryResult<Registration> {
+    lookup_mac_info_with(mac, &OUI_REGISTRY.get(), &IAB_REGISTRY.get(), &MA_REGISTRY.get())
+}
+
+/// Look up the most specific IEEE assignment for a MAC address in explicit registries
+pub fn lookup_mac_info_with(
+    mac: &MAC,
+    oui_registry: &OUIRegistry,
+    iab_registry: &IABRegistry,
+    ma_registry: &MARegistry,
+) -> RegistryResult<Registration> {
+    iab_registry.lookup_mac(mac)
+        .map(Registration::IAB)
+        .or_else(|| ma_registry.lookup_mac(mac).map(Registration::MA))
+        .or_else(|| {
+            let oui = OUI::new([mac.bytes()[0], mac.bytes()[1], mac.bytes()[2]]);
+            oui_registry.lookup_oui(&oui).map(Registration::OUI)
+        })
+        .ok_or_else(|| NotRegisteredError::new(format!("MAC {} not found in registry", mac)))
+}
+
 /// Search for OUIs by organization name
 pub fn search_oui_by_organization(org_name: &str) -> Vec<(OUI, OUIRegistryInfo)> {
-    OUI_REGISTRY.lookup_by_organization(org_name)
+    OUI_REGISTRY.get().lookup_by_organization(org_name)
 }
 
 /// Search for IABs by organization name
 pub fn search_iab_by_organization(org_name: &str) -> Vec<(IAB, IABRegistryInfo)> {
-    IAB_REGISTRY.lookup_by_organization(org_name)
+    IAB_REGISTRY.get().lookup_by_organization(org_name)
 }
 
 /// Get statistics about the registry
 pub fn registry_stats() -> (usize, usize) {
-    (OUI_REGISTRY.all_entries().len(), IAB_REGISTRY.all_entries().len())
+    (OUI_REGISTRY.get().len(), IAB_REGISTRY.get().len())
 }
 
-/// Load additional OUI data from CSV content
+/// Load additional OUI data from CSV content into the global registry
+///
+/// The entries are merged into a copy of the current registry, which then
+/// replaces the global one; lookups running concurrently keep seeing the
+/// previous data until the swap.
 pub fn load_oui_csv_data(csv_data: &str) -> Result<(), Box<dyn std::error::Error>> {
-    // Note: In a real implementation, you would need to handle the lazy_static mutability
-    // For now, this is a placeholder to show the interface
-    println!("Would load {} bytes of CSV data", csv_data.len());
-    Ok(())
+    load_oui_csv_data_into(&OUI_REGISTRY, csv_data)
+}
+
+/// Load additional OUI data from CSV content into the given registry handle
+pub fn load_oui_csv_data_into(
+    handle: &RegistryHandle<OUIRegistry>,
+    csv_data: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    handle.try_update(|current| {
+        let mut registry = current.clone();
+        registry.load_from_csv(csv_data)?;
+        Ok(registry)
+    })
+}
+
+/// Replace the global OUI registry with the contents of an IEEE `oui.txt` file
+pub fn reload_oui_registry(path: impl AsRef<Path>) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut registry = OUIRegistry::new();
+    let count = registry.load_from_file(path)?;
+    OUI_REGISTRY.replace(registry);
+    Ok(count)
+}
+
+/// Replace the global IAB registry with the contents of an IEEE `iab.txt` file
+pub fn reload_iab_registry(path: impl AsRef<Path>) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut registry = IABRegistry::new();
+    let count = registry.load_from_file(path)?;
+    IAB_REGISTRY.replace(registry);
+    Ok(count)
+}
+
+/// Replace the global MA-M/MA-S registry with the contents of IEEE registration files
+pub fn reload_ma_registry<P: AsRef<Path>>(paths: &[P]) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut registry = MARegistry::new();
+    let mut count = 0;
+    for path in paths {
+        count += registry.load_from_file(path)?;
+    }
+    MA_REGISTRY.replace(registry);
+    Ok(count)
 }
 
 /// Common vendor OUI ranges
@@ -318,18 +795,12 @@ mod tests {
 
     #[test]
     fn test_oui_lookup() {
-        let oui = OUI::from_str("00:00:00").unwrap();
-        let info = OUI_REGISTRY.lookup_oui(&oui);
-        assert!(info.is_some());
-        let info = info.unwrap();
-        assert_eq!(info.organization, "Xerox Corporation");
-    }
+        let registry = OUIRegistry::from_ieee_txt(SAMPLE_OUI_TXT).unwrap();
+        let oui = OUI::from_str("00:1B:63").unwrap();
+        assert_eq!(lookup_oui_info_with(&oui, &registry).unwrap().organization, "Apple, Inc.");
 
-    #[test]
-    fn test_oui_search() {
-        let results = search_oui_by_organization("xerox");
-        assert!(!results.is_empty());
-        assert!(results.iter().any(|(_, info)| info.organization.contains("Xerox")));
+        let unknown_oui = OUI::from_str("FF:FF:FF").unwrap();
+        assert!(lookup_oui_info_with(&unknown_oui, &registry).is_err());
     }
 
     #[test]
@@ -349,30 +820,131 @@ mod tests {
 
     #[test]
     fn test_registry_stats() {
-        let (oui_count, iab_count) = registry_stats();
-        assert!(oui_count > 0);
-        assert!(iab_count >= 0); // Might be 0 if no IAB entries loaded
-    }
-
-    #[test]
-    fn test_public_api() {
-        let oui = OUI::from_str("00:00:00").unwrap();
-        let info = lookup_oui_info(&oui);
-        assert!(info.is_ok());
-
-        let unknown_oui = OUI::from_str("FF:FF:FF").unwrap();
-        let info = lookup_oui_info(&unknown_oui);
-        assert!(info.is_err());
+        let (_, iab_count) = registry_stats();
+        assert!(iab_count > 4000);
     }
 
     #[test]
     fn test_iab_lookup() {
         let oui = OUI::from_str("00:50:C2").unwrap();
         let iab = IAB::new(oui, 0x00);
-        let info = IAB_REGISTRY.lookup_iab(&iab);
+        let info = IAB_REGISTRY.get().lookup_iab(&iab);
         assert!(info.is_some());
     }
 
+    const SAMPLE_OUI_TXT: &str = "OUI/MA-L                                                    Organization\r
+company_id                                                  Organization\r
+                                                            Address\r
+\r
+00-CA-FE   (hex)\t\tACME CORPORATION\r
+00CAFE     (base 16)\t\tACME CORPORATION\r
+\t\t\t\t1 MAIN STREET\r
+\t\t\t\tSPRINGFIELD\r
+\t\t\t\tUNITED STATES\r
+\r
+00-1B-63   (hex)\t\tApple, Inc.\r
+001B63     (base 16)\t\tApple, Inc.\r
+\t\t\t\t1 Infinite Loop\r
+\t\t\t\tCupertino CA 95014\r
+\t\t\t\tUS\r
+";
+
+    const SAMPLE_MA_TXT: &str = "70-B3-D5   (hex)\t\tSmall Corp\r
+F2C000-F2CFFF     (base 16)\t\tSmall Corp\r
+\t\t\t\tSomewhere\r
+\r
+70-B3-D5   (hex)\t\tMedium Corp\r
+F00000-FFFFFF     (base 16)\t\tMedium Corp\r
+\t\t\t\tElsewhere\r
+";
+
+    #[test]
+    fn test_parse_ieee_records() {
+        let records = parse_ieee_records(SAMPLE_OUI_TXT).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].oui, 0x00CAFE);
+        assert_eq!(records[0].range, None);
+        assert_eq!(records[0].prefix_bits(), 24);
+        assert_eq!(records[0].organization, "ACME CORPORATION");
+        assert_eq!(records[0].address, vec!["1 MAIN STREET", "SPRINGFIELD", "UNITED STATES"]);
+
+        let records = parse_ieee_records(SAMPLE_MA_TXT).unwrap();
+        assert_eq!(records[0].range, Some((0xF2C000, 0xF2CFFF)));
+        assert_eq!(records[0].prefix_bits(), 36);
+        assert_eq!(records[1].prefix_bits(), 28);
+    }
+
+    #[test]
+    fn test_parse_ieee_errors() {
+        assert!(parse_ieee_records("00-CA-FG   (hex)   ACME").is_err());
+        assert!(parse_ieee_records("00-CA-FE   (hex)   ACME\n00CAFF   (base 16)   ACME").is_err());
+        // Not an aligned power-of-two block
+        assert!(parse_ieee_records("00-50-C2   (hex)   ACME\nABC001-ABCFFF   (base 16)   ACME").is_err());
+    }
+
+    #[test]
+    fn test_load_oui_txt() {
+        let registry = OUIRegistry::from_ieee_txt(SAMPLE_OUI_TXT).unwrap();
+        assert_eq!(registry.len(), 2);
+
+        let info = registry.lookup_oui(&OUI::from_str("00:ca:fe").unwrap()).unwrap();
+        assert_eq!(info.oui, "00:CA:FE");
+        assert_eq!(info.organization, "ACME CORPORATION");
+        assert_eq!(registry.lookup_by_organization("apple").len(), 1);
+    }
+
+    #[test]
+    fn test_ma_registry() {
+        let registry = MARegistry::from_ieee_txt(SAMPLE_MA_TXT).unwrap();
+        assert_eq!(registry.len(), 2);
+
+        let small = MAC::from_str("70:b3:d5:f2:c1:23").unwrap();
+        let info = registry.lookup_mac(&small).unwrap();
+        assert_eq!(info.organization, "Small Corp");
+        assert_eq!(info.block_size, MABlockSize::Small);
+        assert_eq!(info.range_start, "70:B3:D5:F2:C0:00");
+        assert_eq!(info.range_end, "70:B3:D5:F2:CF:FF");
+
+        let medium = MAC::from_str("70:b3:d5:f9:00:01").unwrap();
+        assert_eq!(registry.lookup_mac(&medium).unwrap().organization, "Medium Corp");
+
+        let unknown = MAC::from_str("70:b3:d5:01:00:01").unwrap();
+        assert!(registry.lookup_mac(&unknown).is_none());
+    }
+
+    #[test]
+    fn test_load_oui_csv_data() {
+        let oui = OUI::new([0x02, 0xab, 0xcd]);
+        let handle = RegistryHandle::new(OUIRegistry::new());
+        assert!(lookup_oui_info_with(&oui, &handle.get()).is_err());
+
+        let snapshot = handle.get();
+        load_oui_csv_data_into(&handle, "Assignment,Organization,Address\n02-AB-CD,Example Corp,Somewhere\n").unwrap();
+        assert_eq!(lookup_oui_info_with(&oui, &handle.get()).unwrap().organization, "Example Corp");
+        assert!(snapshot.lookup_oui(&oui).is_none());
+    }
+
+    #[test]
+    fn test_bundled_iab_data() {
+        assert!(IAB_REGISTRY.get().len() > 4000);
+
+        let mac = MAC::from_str("00:50:c2:f7:10:01").unwrap();
+        let info = IAB_REGISTRY.get().lookup_mac(&mac).unwrap();
+        assert_eq!(info.organization, "RF Code");
+        assert_eq!(info.iab_range_start, "00:50:C2:F7:10:00");
+        assert_eq!(info.iab_range_end, "00:50:C2:F7:1F:FF");
+        assert_eq!(IAB_REGISTRY.get().lookup_iab(&IAB::from_mac(&mac)), Some(info));
+
+        let registration = lookup_mac_info(&mac).unwrap();
+        assert!(matches!(registration, Registration::IAB(_)));
+        assert_eq!(registration.organization(), "RF Code");
+
+        let ouis = OUIRegistry::from_ieee_txt(SAMPLE_OUI_TXT).unwrap();
+        let mac = MAC::from_str("00:1b:63:00:00:01").unwrap();
+        let registration = lookup_mac_info_with(&mac, &ouis, &IAB_REGISTRY.get(), &MARegistry::new()).unwrap();
+        assert_eq!(registration.organization(), "Apple, Inc.");
+    }
+
     #[test]
     fn test_oui_registry_creation() {
         let mut registry = OUIRegistry::new();
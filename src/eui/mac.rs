@@ -132,6 +132,11 @@ impl MAC {
         Ok(IPAddress::new_v6(ipv6_addr))
     }
 
+    /// Look up the most specific IEEE registration (IAB, MA-S, MA-M or MA-L) for this address
+    pub fn registration(&self) -> Option<super::ieee::Registration> {
+        super::ieee::lookup_mac_info(self).ok()
+    }
+
     /// Format MAC address in different notations
     pub fn format(&self, format: MacFormat) -> String {
         match format {
@@ -285,8 +285,11 @@ impl OUI {
     }
 
     /// Get the registry information for this OUI
+    ///
+    /// Uses the global OUI registry, which is empty until `oui.txt` is loaded
+    /// with `ieee::reload_oui_registry`.
     pub fn registry_info(&self) -> Option<ieee::OUIRegistryInfo> {
-        ieee::OUI_REGISTRY.lookup_oui(self)
+        ieee::OUI_REGISTRY.get().lookup_oui(self)
     }
 }
 
@@ -330,13 +333,23 @@ impl fmt::Display for OUI {
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct IAB {
     oui: OUI,
-    extension: u8,
+    extension: u16,
 }
 
 impl IAB {
-    /// Create a new IAB
-    pub fn new(oui: OUI, extension: u8) -> Self {
-        Self { oui, extension }
+    /// Create a new IAB from its OUI and 12-bit block extension
+    pub fn new(oui: OUI, extension: u16) -> Self {
+        Self {
+            oui,
+            extension: extension & 0xfff,
+        }
+    }
+
+    /// Get the IAB a MAC address belongs to (first 36 bits)
+    pub fn from_mac(mac: &MAC) -> Self {
+        let bytes = mac.bytes();
+        let extension = ((bytes[3] as u16) << 4) | ((bytes[4] as u16) >> 4);
+        Self::new(OUI::new([bytes[0], bytes[1], bytes[2]]), extension)
     }
 
     /// Get the OUI portion
@@ -344,14 +357,14 @@ impl IAB {
         &self.oui
     }
 
-    /// Get the extension byte
-    pub fn extension(&self) -> u8 {
+    /// Get the 12-bit block extension
+    pub fn extension(&self) -> u16 {
         self.extension
     }
 
     /// Get the registry information for this IAB
     pub fn registry_info(&self) -> Option<ieee::IABRegistryInfo> {
-        ieee::IAB_REGISTRY.lookup_iab(self)
+        ieee::IAB_REGISTRY.get().lookup_iab(self)
     }
 }
 
@@ -403,6 +416,11 @@ mod tests {
         let iab = IAB::new(oui, 0x12);
         assert_eq!(iab.extension(), 0x12);
         assert_eq!(iab.to_string(), "00:50:c2-12");
+
+        let mac = MAC::from_str("00:50:c2:ab:c1:23").unwrap();
+        let iab = IAB::from_mac(&mac);
+        assert_eq!(iab.oui().to_u32(), 0x0050c2);
+        assert_eq!(iab.extension(), 0xabc);
     }
 
     #[test]
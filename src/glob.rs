@@ -511,7 +511,7 @@ mod tests {
         assert!(!cidrs.is_empty());
 
         // The exact result depends on implementation, but should cover 192.168.1.0-255
-        let total_addresses: u128 = cidrs.iter().map(|c| c.num_addresses()).sum();
+        let total_addresses: crate::ip::AddressCount = cidrs.iter().map(|c| c.num_addresses()).sum();
         assert_eq!(total_addresses, 256);
     }
 
@@ -520,7 +520,7 @@ mod tests {
         let ranges = glob_to_iprange("192.168.1.*").unwrap();
         assert!(!ranges.is_empty());
 
-        let total_size: u128 = ranges.iter().map(|r| r.size()).sum();
+        let total_size: crate::ip::AddressCount = ranges.iter().map(|r| r.size()).sum();
         assert_eq!(total_size, 256);
     }
 
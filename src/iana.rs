@@ -1,11 +1,18 @@
 //! IANA IP address block information
 
-use crate::ip::{IPAddress, IPNetwork, IPAddressType};
+use crate::error::{AddrFormatError, AddrResult};
+use crate::ip::{IPAddress, IPNetwork, IPAddressType, IPRange};
+use crate::prefix_map::PrefixMap;
+use crate::registry::RegistryHandle;
 use lazy_static::lazy_static;
-use std::collections::HashMap;
+use std::fs;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+use std::str::FromStr;
 
 /// IANA registry information for an IP block
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct IANARegistryInfo {
     pub designation: String,
     pub date: String,
@@ -15,201 +22,306 @@ pub struct IANARegistryInfo {
     pub notes: String,
 }
 
+fn host_network(addr: &IPAddress) -> IPNetwork {
+    let prefix_length = if addr.is_ipv4() { 32 } else { 128 };
+    IPNetwork::new_unchecked(addr.clone(), prefix_length)
+}
+
 /// IANA IP address block registry
+///
+/// Blocks are keyed by CIDR prefix and lookups return the most specific
+/// (longest) registered prefix, so nested registrations such as
+/// `224.0.0.0/4` and `224.0.0.1/32` can coexist.
+#[derive(Debug, Clone, Default)]
 pub struct IANARegistry {
-    ipv4_blocks: HashMap<u8, IANARegistryInfo>,
-    ipv6_blocks: HashMap<u8, IANARegistryInfo>,
+    blocks: PrefixMap<IANARegistryInfo>,
 }
 
 impl IANARegistry {
     /// Create a new empty registry
     pub fn new() -> Self {
-        Self {
-            ipv4_blocks: HashMap::new(),
-            ipv6_blocks: HashMap::new(),
-        }
+        Self::default()
     }
 
-    /// Add IPv4 block information
+    /// Create a registry from the contents of an IANA registry XML file
+    pub fn from_xml(data: &str) -> AddrResult<Self> {
+        let mut registry = Self::new();
+        registry.load_xml(data)?;
+        Ok(registry)
+    }
+
+    /// Add information for an address block, replacing any record for the same prefix
+    pub fn add_block(&mut self, network: &IPNetwork, info: IANARegistryInfo) {
+        self.blocks.insert(network.clone(), info);
+    }
+
+    /// Add IPv4 /8 block information, keyed by first octet
     pub fn add_ipv4_block(&mut self, prefix: u8, info: IANARegistryInfo) {
-        self.ipv4_blocks.insert(prefix, info);
+        self.add_block(&IPNetwork::new_unchecked(IPAddress::new_v4(Ipv4Addr::new(prefix, 0, 0, 0)), 8), info);
     }
 
-    /// Add IPv6 block information
+    /// Add IPv6 /8 block information, keyed by first byte
     pub fn add_ipv6_block(&mut self, prefix: u8, info: IANARegistryInfo) {
-        self.ipv6_blocks.insert(prefix, info);
+        self.add_block(&IPNetwork::new_unchecked(IPAddress::new_v6(Ipv6Addr::from((prefix as u128) << 120)), 8), info);
+    }
+
+    /// Load the records of an IANA registry XML file, returning the number of blocks added
+    ///
+    /// Understands `ipv4-address-space.xml`, `ipv6-address-space.xml`,
+    /// `ipv6-unicast-address-assignments.xml` and `multicast-addresses.xml`.
+    /// Nothing is added if any record is invalid.
+    pub fn load_xml(&mut self, data: &str) -> AddrResult<usize> {
+        let blocks = parse_iana_xml(data)?;
+        let count = blocks.len();
+        for (network, info) in blocks {
+            self.add_block(&network, info);
+        }
+        Ok(count)
+    }
+
+    /// Load an IANA registry XML file from disk
+    pub fn load_from_file(&mut self, path: impl AsRef<Path>) -> Result<usize, Box<dyn std::error::Error>> {
+        let data = fs::read_to_string(path)?;
+        Ok(self.load_xml(&data)?)
+    }
+
+    /// Find the most specific block containing the whole network
+    pub fn longest_match(&self, network: &IPNetwork) -> Option<(IPNetwork, &IANARegistryInfo)> {
+        self.blocks.longest_match(network)
     }
 
     /// Look up IANA information for an IP address
     pub fn lookup_address(&self, addr: &IPAddress) -> Option<IANARegistryInfo> {
-        match addr.ip_type() {
-            IPAddressType::IPv4 => {
-                let ipv4 = addr.as_ipv4().unwrap();
-                let first_octet = ipv4.octets()[0];
-                self.ipv4_blocks.get(&first_octet).cloned()
-            }
-            IPAddressType::IPv6 => {
-                let ipv6 = addr.as_ipv6().unwrap();
-                let segments = ipv6.segments();
-                let first_byte = (segments[0] >> 8) as u8;
-                self.ipv6_blocks.get(&first_byte).cloned()
-            }
-        }
+        self.lookup_network(&host_network(addr))
     }
 
     /// Look up IANA information for a network
+    ///
+    /// Returns the most specific record whose block contains the whole network.
     pub fn lookup_network(&self, network: &IPNetwork) -> Option<IANARegistryInfo> {
-        self.lookup_address(network.network_address())
+        self.longest_match(network).map(|(_, info)| info.clone())
     }
 
-    /// Get all IPv4 blocks
-    pub fn ipv4_blocks(&self) -> &HashMap<u8, IANARegistryInfo> {
-        &self.ipv4_blocks
+    /// Get all IPv4 blocks in address order
+    pub fn ipv4_blocks(&self) -> Vec<(IPNetwork, &IANARegistryInfo)> {
+        self.blocks.iter().filter(|(network, _)| network.is_ipv4()).collect()
     }
 
-    /// Get all IPv6 blocks
-    pub fn ipv6_blocks(&self) -> &HashMap<u8, IANARegistryInfo> {
-        &self.ipv6_blocks
+    /// Get all IPv6 blocks in address order
+    pub fn ipv6_blocks(&self) -> Vec<(IPNetwork, &IANARegistryInfo)> {
+        self.blocks.iter().filter(|(network, _)| network.is_ipv6()).collect()
+    }
+
+    /// Get the number of registered blocks
+    pub fn len(&self) -> usize {
+        self.blocks.len()
     }
-}
 
-impl Default for IANARegistry {
-    fn default() -> Self {
-        Self::new()
+    /// Check if the registry is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
     }
 }
 
-lazy_static! {
-    /// Global IANA registry with predefined blocks
-    pub static ref IANA_REGISTRY: IANARegistry = {
-        let mut registry = IANARegistry::new();
+/// Parse the records of an IANA registry XML file into address blocks
+///
+/// Record prefixes may be written as CIDRs (`2001:0200::/23`), zero-padded
+/// first octets (`014/8`), single addresses (`224.0.0.1`) or hyphenated
+/// ranges (`224.0.0.37-224.0.0.68`); ranges are split into CIDR blocks that
+/// share the record's information. Records without a prefix are skipped.
+pub fn parse_iana_xml(data: &str) -> AddrResult<Vec<(IPNetwork, IANARegistryInfo)>> {
+    let mut blocks = Vec::new();
+
+    for (index, (attrs, body)) in xml_elements(data, "record").into_iter().enumerate() {
+        let prefix = match xml_text(body, "prefix").or_else(|| xml_text(body, "addr")) {
+            Some(prefix) => prefix,
+            None => continue,
+        };
+        let networks = parse_iana_prefix(&prefix).map_err(|e| {
+            AddrFormatError::new(format!("Record {}: invalid prefix '{}': {}", index + 1, prefix, e))
+        })?;
+
+        let info = IANARegistryInfo {
+            designation: xml_text(body, "designation")
+                .or_else(|| xml_text(body, "description"))
+                .unwrap_or_default(),
+            date: xml_text(body, "date")
+                .or_else(|| xml_attr(attrs, "date").map(decode_entities))
+                .unwrap_or_default(),
+            whois: xml_text(body, "whois").unwrap_or_default(),
+            rdap: xml_text(body, "server").unwrap_or_default(),
+            status: xml_text(body, "status").into_iter().collect(),
+            notes: xml_text(body, "notes").unwrap_or_default(),
+        };
 
-        // IPv4 Special-Use Address Registry (RFC 6890)
-        registry.add_ipv4_block(0, IANARegistryInfo {
-            designation: "0.0.0.0/8".to_string(),
-            date: "1981-09".to_string(),
-            whois: "whois.iana.org".to_string(),
-            rdap: "".to_string(),
-            status: vec!["RESERVED".to_string()],
-            notes: "\"This\" Network".to_string(),
-        });
+        for network in networks {
+            blocks.push((network, info.clone()));
+        }
+    }
 
-        registry.add_ipv4_block(10, IANARegistryInfo {
-            designation: "10.0.0.0/8".to_string(),
-            date: "1996-02".to_string(),
-            whois: "whois.iana.org".to_string(),
-            rdap: "".to_string(),
-            status: vec!["RESERVED".to_string()],
-            notes: "Private-Use Networks".to_string(),
-        });
+    Ok(blocks)
+}
 
-        registry.add_ipv4_block(127, IANARegistryInfo {
-            designation: "127.0.0.0/8".to_string(),
-            date: "1981-09".to_string(),
-            whois: "whois.iana.org".to_string(),
-            rdap: "".to_string(),
-            status: vec!["RESERVED".to_string()],
-            notes: "Loopback".to_string(),
-        });
+fn parse_iana_prefix(prefix: &str) -> AddrResult<Vec<IPNetwork>> {
+    if prefix.contains('-') {
+        return IPRange::from_hyphen_string(prefix)?.to_cidrs();
+    }
 
-        registry.add_ipv4_block(169, IANARegistryInfo {
-            designation: "169.254.0.0/16".to_string(),
-            date: "2005-05".to_string(),
-            whois: "whois.iana.org".to_string(),
-            rdap: "".to_string(),
-            status: vec!["RESERVED".to_string()],
-            notes: "Link Local".to_string(),
-        });
+    match prefix.split_once('/') {
+        // The IPv4 address space registry only gives the first octet, e.g. "014/8"
+        Some((octet, length)) if !octet.contains(['.', ':']) => {
+            let octet: u8 = octet
+                .parse()
+                .map_err(|_| AddrFormatError::new(format!("Invalid first octet '{}'", octet)))?;
+            let length: u8 = length
+                .parse()
+                .map_err(|_| AddrFormatError::new(format!("Invalid prefix length '{}'", length)))?;
+            Ok(vec![IPNetwork::new(IPAddress::new_v4(Ipv4Addr::new(octet, 0, 0, 0)), length)?])
+        }
+        Some(_) => Ok(vec![IPNetwork::from_str(prefix)?]),
+        None => {
+            let addr = IPAddress::from_str(prefix)?;
+            let prefix_length = if addr.is_ipv4() { 32 } else { 128 };
+            Ok(vec![IPNetwork::new(addr, prefix_length)?])
+        }
+    }
+}
 
-        registry.add_ipv4_block(172, IANARegistryInfo {
-            designation: "172.16.0.0/12".to_string(),
-            date: "1996-02".to_string(),
-            whois: "whois.iana.org".to_string(),
-            rdap: "".to_string(),
-            status: vec!["RESERVED".to_string()],
-            notes: "Private-Use Networks".to_string(),
-        });
+/// Find all `<tag ...>body</tag>` (or self-closing `<tag/>`) elements, returning attributes and body
+///
+/// Elements of the same name are assumed not to nest, which holds for the
+/// IANA registry formats.
+fn xml_elements<'a>(data: &'a str, tag: &str) -> Vec<(&'a str, &'a str)> {
+    let open = format!("<{}", tag);
+    let close = format!("</{}>", tag);
+    let mut elements = Vec::new();
+    let mut rest = data;
+
+    while let Some(start) = rest.find(&open) {
+        let after = &rest[start + open.len()..];
+        // Skip longer tag names sharing the prefix, e.g. <records> for <record>
+        if !after.starts_with(|c: char| c == '>' || c == '/' || c.is_whitespace()) {
+            rest = after;
+            continue;
+        }
+        let Some(tag_end) = after.find('>') else { break };
+        let attrs = &after[..tag_end];
+        if let Some(attrs) = attrs.strip_suffix('/') {
+            elements.push((attrs, ""));
+            rest = &after[tag_end + 1..];
+            continue;
+        }
+        let content = &after[tag_end + 1..];
+        let Some(body_end) = content.find(&close) else { break };
+        elements.push((attrs, &content[..body_end]));
+        rest = &content[body_end + close.len()..];
+    }
 
-        registry.add_ipv4_block(192, IANARegistryInfo {
-            designation: "192.168.0.0/16".to_string(),
-            date: "1996-02".to_string(),
-            whois: "whois.iana.org".to_string(),
-            rdap: "".to_string(),
-            status: vec!["RESERVED".to_string()],
-            notes: "Private-Use Networks".to_string(),
-        });
+    elements
+}
 
-        registry.add_ipv4_block(224, IANARegistryInfo {
-            designation: "224.0.0.0/4".to_string(),
-            date: "1981-09".to_string(),
-            whois: "whois.iana.org".to_string(),
-            rdap: "".to_string(),
-            status: vec!["RESERVED".to_string()],
-            notes: "Multicast".to_string(),
-        });
+/// Get the text of the first `<tag>` element, with markup stripped and whitespace collapsed
+fn xml_text(data: &str, tag: &str) -> Option<String> {
+    let (_, body) = xml_elements(data, tag).into_iter().next()?;
+    let mut text = String::new();
+    let mut in_markup = false;
+    for c in body.chars() {
+        match c {
+            '<' => in_markup = true,
+            '>' => in_markup = false,
+            c if !in_markup => text.push(c),
+            _ => {}
+        }
+    }
+    let text = decode_entities(&text).split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.is_empty() {
+        None
+    } else {
+        Some(text)
+    }
+}
 
-        registry.add_ipv4_block(240, IANARegistryInfo {
-            designation: "240.0.0.0/4".to_string(),
-            date: "1981-09".to_string(),
-            whois: "whois.iana.org".to_string(),
-            rdap: "".to_string(),
-            status: vec!["RESERVED".to_string()],
-            notes: "Reserved for Future Use".to_string(),
-        });
+fn xml_attr<'a>(attrs: &'a str, name: &str) -> Option<&'a str> {
+    let pattern = format!("{}=\"", name);
+    let start = attrs
+        .match_indices(&pattern)
+        .find(|(i, _)| *i == 0 || attrs[..*i].ends_with(char::is_whitespace))?
+        .0
+        + pattern.len();
+    let end = attrs[start..].find('"')?;
+    Some(&attrs[start..start + end])
+}
 
-        // IPv6 Special-Use Address Registry
-        registry.add_ipv6_block(0x00, IANARegistryInfo {
-            designation: "::/128".to_string(),
-            date: "2006-02".to_string(),
-            whois: "whois.iana.org".to_string(),
-            rdap: "".to_string(),
-            status: vec!["RESERVED".to_string()],
-            notes: "Unspecified Address".to_string(),
-        });
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
 
-        registry.add_ipv6_block(0x20, IANARegistryInfo {
-            designation: "2000::/3".to_string(),
-            date: "2006-02".to_string(),
-            whois: "whois.iana.org".to_string(),
-            rdap: "".to_string(),
-            status: vec!["ALLOCATED".to_string()],
-            notes: "Global Unicast".to_string(),
-        });
+const BUNDLED_IANA_XML: [&str; 4] = [
+    include_str!("../netaddr/ip/ipv4-address-space.xml"),
+    include_str!("../netaddr/ip/multicast-addresses.xml"),
+    include_str!("../netaddr/ip/ipv6-address-space.xml"),
+    include_str!("../netaddr/ip/ipv6-unicast-address-assignments.xml"),
+];
+
+/// Blocks layered over the address space registries that are not
+/// special-purpose blocks: (block, date, status, notes)
+const OVERLAY_BLOCKS: &[(&str, &str, &str, &str)] = &[
+    ("224.0.0.0/4", "1981-09", "RESERVED", "Multicast"),
+    ("2000::/3", "2006-02", "ALLOCATED", "Global Unicast"),
+    ("ff00::/8", "2006-02", "RESERVED", "Multicast"),
+];
 
-        registry.add_ipv6_block(0xfc, IANARegistryInfo {
-            designation: "fc00::/7".to_string(),
-            date: "2005-10".to_string(),
-            whois: "whois.iana.org".to_string(),
-            rdap: "".to_string(),
-            status: vec!["RESERVED".to_string()],
-            notes: "Unique Local Unicast".to_string(),
-        });
+lazy_static! {
+    /// Global IANA registry, loaded from the bundled IANA XML registries
+    /// with the special-purpose blocks layered on top
+    pub static ref IANA_REGISTRY: RegistryHandle<IANARegistry> = {
+        let mut registry = IANARegistry::new();
 
-        registry.add_ipv6_block(0xfe, IANARegistryInfo {
-            designation: "fe80::/10".to_string(),
-            date: "2006-02".to_string(),
-            whois: "whois.iana.org".to_string(),
-            rdap: "".to_string(),
-            status: vec!["RESERVED".to_string()],
-            notes: "Link-Scoped Unicast".to_string(),
-        });
+        for data in BUNDLED_IANA_XML {
+            registry.load_xml(data).expect("the bundled IANA registries are valid");
+        }
 
-        registry.add_ipv6_block(0xff, IANARegistryInfo {
-            designation: "ff00::/8".to_string(),
-            date: "2006-02".to_string(),
-            whois: "whois.iana.org".to_string(),
-            rdap: "".to_string(),
-            status: vec!["RESERVED".to_string()],
-            notes: "Multicast".to_string(),
+        let overlay = OVERLAY_BLOCKS.iter().copied();
+        // Globally reachable special-purpose blocks are in ordinary use
+        let special = SPECIAL_PURPOSE_REGISTRY_DATA.iter().map(|&(block, name, _, allocated, _, flags)| {
+            let status = if flags[3] == T { "ALLOCATED" } else { "RESERVED" };
+            (block, allocated, status, name)
         });
+        for (block, date, status, notes) in overlay.chain(special) {
+            let network = IPNetwork::from_str(block).expect("valid special-purpose block");
+            registry.add_block(&network, IANARegistryInfo {
+                designation: block.to_string(),
+                date: date.to_string(),
+                whois: "whois.iana.org".to_string(),
+                rdap: "".to_string(),
+                status: vec![status.to_string()],
+                notes: notes.to_string(),
+            });
+        }
 
-        registry
+        RegistryHandle::new(registry)
     };
 }
 
 /// Look up IANA registry information for an IP address
 pub fn lookup_iana_info(addr: &IPAddress) -> Option<IANARegistryInfo> {
-    IANA_REGISTRY.lookup_address(addr)
+    IANA_REGISTRY.get().lookup_address(addr)
+}
+
+/// Look up IANA registry information for an IP address in an explicit registry
+pub fn lookup_iana_info_with(addr: &IPAddress, registry: &IANARegistry) -> Option<IANARegistryInfo> {
+    registry.lookup_address(addr)
+}
+
+fn is_reserved_in(addr: &IPAddress, registry: &IANARegistry) -> bool {
+    registry
+        .lookup_address(addr)
+        .map(|info| info.status.iter().any(|s| s == "RESERVED"))
+        .unwrap_or(false)
 }
 
 /// Check if an IP address is in an IANA reserved block
@@ -235,6 +347,205 @@ pub fn get_iana_designation(addr: &IPAddress) -> Option<String> {
     lookup_iana_info(addr).map(|info| info.designation)
 }
 
+/// Entry of the IANA IPv4/IPv6 Special-Purpose Address Registries (RFC 6890)
+///
+/// The flags are `None` where the registry lists them as "N/A".
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpecialPurposeInfo {
+    pub name: String,
+    pub rfc: String,
+    pub allocation_date: String,
+    pub termination_date: Option<String>,
+    pub source: Option<bool>,
+    pub destination: Option<bool>,
+    pub forwardable: Option<bool>,
+    pub globally_reachable: Option<bool>,
+    pub reserved_by_protocol: Option<bool>,
+}
+
+/// IANA Special-Purpose Address Registry with longest-prefix-match lookups
+#[derive(Debug, Clone, Default)]
+pub struct SpecialPurposeRegistry {
+    blocks: PrefixMap<SpecialPurposeInfo>,
+}
+
+impl SpecialPurposeRegistry {
+    /// Create a new empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an entry, replacing any entry for the same block
+    pub fn add_block(&mut self, network: &IPNetwork, info: SpecialPurposeInfo) {
+        self.blocks.insert(network.clone(), info);
+    }
+
+    /// Find the most specific block containing the whole network
+    pub fn longest_match(&self, network: &IPNetwork) -> Option<(IPNetwork, &SpecialPurposeInfo)> {
+        self.blocks.longest_match(network)
+    }
+
+    /// Look up the special-purpose entry for an IP address
+    pub fn lookup_address(&self, addr: &IPAddress) -> Option<SpecialPurposeInfo> {
+        self.lookup_network(&host_network(addr))
+    }
+
+    /// Look up the most specific special-purpose entry containing the whole network
+    pub fn lookup_network(&self, network: &IPNetwork) -> Option<SpecialPurposeInfo> {
+        self.longest_match(network).map(|(_, info)| info.clone())
+    }
+
+    /// Get all IPv4 entries in address order
+    pub fn ipv4_blocks(&self) -> Vec<(IPNetwork, &SpecialPurposeInfo)> {
+        self.blocks.iter().filter(|(network, _)| network.is_ipv4()).collect()
+    }
+
+    /// Get all IPv6 entries in address order
+    pub fn ipv6_blocks(&self) -> Vec<(IPNetwork, &SpecialPurposeInfo)> {
+        self.blocks.iter().filter(|(network, _)| network.is_ipv6()).collect()
+    }
+
+    /// Get the number of entries
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Check if the registry is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+const T: Option<bool> = Some(true);
+const F: Option<bool> = Some(false);
+const NA: Option<bool> = None;
+
+type SpecialPurposeRow = (
+    &'static str,
+    &'static str,
+    &'static str,
+    &'static str,
+    Option<&'static str>,
+    [Option<bool>; 5],
+);
+
+/// IANA special-purpose registry rows: (block, name, RFC, allocation date,
+/// termination date, [source, destination, forwardable, globally reachable,
+/// reserved-by-protocol])
+const SPECIAL_PURPOSE_REGISTRY_DATA: &[SpecialPurposeRow] = &[
+    ("0.0.0.0/8", "\"This network\"", "RFC791", "1981-09", None, [T, F, F, F, T]),
+    ("0.0.0.0/32", "\"This host on this network\"", "RFC1122", "1981-09", None, [T, F, F, F, T]),
+    ("10.0.0.0/8", "Private-Use", "RFC1918", "1996-02", None, [T, T, T, F, F]),
+    ("100.64.0.0/10", "Shared Address Space", "RFC6598", "2012-04", None, [T, T, T, F, F]),
+    ("127.0.0.0/8", "Loopback", "RFC1122", "1981-09", None, [F, F, F, F, T]),
+    ("169.254.0.0/16", "Link Local", "RFC3927", "2005-05", None, [T, T, F, F, T]),
+    ("172.16.0.0/12", "Private-Use", "RFC1918", "1996-02", None, [T, T, T, F, F]),
+    ("192.0.0.0/24", "IETF Protocol Assignments", "RFC6890", "2010-01", None, [F, F, F, F, F]),
+    ("192.0.0.0/29", "IPv4 Service Continuity Prefix", "RFC7335", "2011-06", None, [T, T, T, F, F]),
+    ("192.0.0.8/32", "IPv4 dummy address", "RFC7600", "2015-03", None, [T, F, F, F, F]),
+    ("192.0.0.9/32", "Port Control Protocol Anycast", "RFC7723", "2015-10", None, [T, T, T, T, F]),
+    ("192.0.0.10/32", "Traversal Using Relays around NAT Anycast", "RFC8155", "2017-02", None, [T, T, T, T, F]),
+    ("192.0.0.170/32", "NAT64/DNS64 Discovery", "RFC8880, RFC7050", "2013-02", None, [F, F, F, F, T]),
+    ("192.0.0.171/32", "NAT64/DNS64 Discovery", "RFC8880, RFC7050", "2013-02", None, [F, F, F, F, T]),
+    ("192.0.2.0/24", "Documentation (TEST-NET-1)", "RFC5737", "2010-01", None, [F, F, F, F, F]),
+    ("192.31.196.0/24", "AS112-v4", "RFC7535", "2014-12", None, [T, T, T, T, F]),
+    ("192.52.193.0/24", "AMT", "RFC7450", "2014-12", None, [T, T, T, T, F]),
+    ("192.88.99.0/24", "Deprecated (6to4 Relay Anycast)", "RFC7526", "2001-06", Some("2015-03"), [NA, NA, NA, NA, NA]),
+    ("192.88.99.2/32", "6a44-relay anycast address", "RFC6751", "2012-10", None, [T, T, T, F, F]),
+    ("192.168.0.0/16", "Private-Use", "RFC1918", "1996-02", None, [T, T, T, F, F]),
+    ("192.175.48.0/24", "Direct Delegation AS112 Service", "RFC7534", "1996-01", None, [T, T, T, T, F]),
+    ("198.18.0.0/15", "Benchmarking", "RFC2544", "1999-03", None, [T, T, T, F, F]),
+    ("198.51.100.0/24", "Documentation (TEST-NET-2)", "RFC5737", "2010-01", None, [F, F, F, F, F]),
+    ("203.0.113.0/24", "Documentation (TEST-NET-3)", "RFC5737", "2010-01", None, [F, F, F, F, F]),
+    ("240.0.0.0/4", "Reserved", "RFC1112", "1989-08", None, [F, F, F, F, T]),
+    ("255.255.255.255/32", "Limited Broadcast", "RFC8190, RFC919", "1984-10", None, [F, T, F, F, T]),
+    ("::1/128", "Loopback Address", "RFC4291", "2006-02", None, [F, F, F, F, T]),
+    ("::/128", "Unspecified Address", "RFC4291", "2006-02", None, [T, F, F, F, T]),
+    ("::ffff:0:0/96", "IPv4-mapped Address", "RFC4291", "2006-02", None, [F, F, F, F, T]),
+    ("64:ff9b::/96", "IPv4-IPv6 Translat.", "RFC6052", "2010-10", None, [T, T, T, T, F]),
+    ("64:ff9b:1::/48", "IPv4-IPv6 Translat.", "RFC8215", "2017-06", None, [T, T, T, F, F]),
+    ("100::/64", "Discard-Only Address Block", "RFC6666", "2012-06", None, [T, T, T, F, F]),
+    ("100:0:0:1::/64", "Dummy IPv6 Prefix", "RFC9780", "2025-04", None, [T, F, F, F, F]),
+    ("2001::/23", "IETF Protocol Assignments", "RFC2928", "2000-09", None, [F, F, F, F, F]),
+    ("2001::/32", "TEREDO", "RFC4380, RFC8190", "2006-01", None, [T, T, T, NA, F]),
+    ("2001:1::1/128", "Port Control Protocol Anycast", "RFC7723", "2015-10", None, [T, T, T, T, F]),
+    ("2001:1::2/128", "Traversal Using Relays around NAT Anycast", "RFC8155", "2017-02", None, [T, T, T, T, F]),
+    ("2001:1::3/128", "DNS-SD Service Registration Protocol Anycast", "RFC9665", "2024-04", None, [T, T, T, T, F]),
+    ("2001:2::/48", "Benchmarking", "RFC5180", "2008-04", None, [T, T, T, F, F]),
+    ("2001:3::/32", "AMT", "RFC7450", "2014-12", None, [T, T, T, T, F]),
+    ("2001:4:112::/48", "AS112-v6", "RFC7535", "2014-12", None, [T, T, T, T, F]),
+    ("2001:10::/28", "Deprecated (previously ORCHID)", "RFC4843", "2007-03", Some("2014-03"), [NA, NA, NA, NA, NA]),
+    ("2001:20::/28", "ORCHIDv2", "RFC7343", "2014-07", None, [T, T, T, T, F]),
+    ("2001:30::/28", "Drone Remote ID Protocol Entity Tags (DETs) Prefix", "RFC9374", "2022-12", None, [T, T, T, T, F]),
+    ("2001:db8::/32", "Documentation", "RFC3849", "2004-07", None, [F, F, F, F, F]),
+    ("2002::/16", "6to4", "RFC3056", "2001-02", None, [T, T, T, NA, F]),
+    ("2620:4f:8000::/48", "Direct Delegation AS112 Service", "RFC7534", "2011-05", None, [T, T, T, T, F]),
+    ("3fff::/20", "Documentation", "RFC9637", "2024-07", None, [F, F, F, F, F]),
+    ("5f00::/16", "Segment Routing (SRv6) SIDs", "RFC9602", "2024-04", None, [T, T, T, F, F]),
+    ("fc00::/7", "Unique-Local", "RFC4193, RFC8190", "2005-10", None, [T, T, T, F, F]),
+    ("fe80::/10", "Link-Local Unicast", "RFC4291", "2006-02", None, [T, T, F, F, T]),
+];
+
+lazy_static! {
+    /// Global IANA IPv4/IPv6 Special-Purpose Address Registry
+    pub static ref SPECIAL_PURPOSE_REGISTRY: RegistryHandle<SpecialPurposeRegistry> = {
+        let mut registry = SpecialPurposeRegistry::new();
+
+        for &(block, name, rfc, allocated, terminated, flags) in SPECIAL_PURPOSE_REGISTRY_DATA {
+            let network = IPNetwork::from_str(block).expect("valid special-purpose block");
+            let [source, destination, forwardable, globally_reachable, reserved_by_protocol] = flags;
+            registry.add_block(&network, SpecialPurposeInfo {
+                name: name.to_string(),
+                rfc: rfc.to_string(),
+                allocation_date: allocated.to_string(),
+                termination_date: terminated.map(str::to_string),
+                source,
+                destination,
+                forwardable,
+                globally_reachable,
+                reserved_by_protocol,
+            });
+        }
+
+        RegistryHandle::new(registry)
+    };
+}
+
+/// Look up the special-purpose registry entry for an IP address
+pub fn lookup_special_purpose(addr: &IPAddress) -> Option<SpecialPurposeInfo> {
+    SPECIAL_PURPOSE_REGISTRY.get().lookup_address(addr)
+}
+
+/// Look up the most specific special-purpose registry entry containing a network
+pub fn lookup_special_purpose_network(network: &IPNetwork) -> Option<SpecialPurposeInfo> {
+    SPECIAL_PURPOSE_REGISTRY.get().lookup_network(network)
+}
+
+/// Check if an address is globally reachable according to the special-purpose registry
+///
+/// Addresses outside every special-purpose block are globally reachable;
+/// blocks listing the flag as "N/A" are treated as not globally reachable.
+pub fn is_globally_reachable(addr: &IPAddress) -> bool {
+    lookup_special_purpose(addr).is_none_or(|info| info.globally_reachable == Some(true))
+}
+
+/// Check if every address of a network is globally reachable
+///
+/// Uses the most specific special-purpose block containing the whole network,
+/// and is false if the network contains any block that is not globally reachable.
+pub fn is_network_globally_reachable(network: &IPNetwork) -> bool {
+    let registry = SPECIAL_PURPOSE_REGISTRY.get();
+    let covering = registry
+        .longest_match(network)
+        .is_none_or(|(_, info)| info.globally_reachable == Some(true));
+    covering
+        && registry
+            .blocks
+            .covered(network)
+            .all(|(block, info)| block.prefix_length() == network.prefix_length() || info.globally_reachable == Some(true))
+}
+
 /// Address classification based on IANA registries
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AddressClass {
@@ -258,6 +569,11 @@ pub enum AddressClass {
 
 /// Classify an IP address based on IANA registries and RFCs
 pub fn classify_address(addr: &IPAddress) -> AddressClass {
+    classify_address_with(addr, &IANA_REGISTRY.get())
+}
+
+/// Classify an IP address against an explicit IANA registry
+pub fn classify_address_with(addr: &IPAddress, registry: &IANARegistry) -> AddressClass {
     match addr.ip_type() {
         IPAddressType::IPv4 => {
             let ipv4 = addr.as_ipv4().unwrap();
@@ -285,7 +601,7 @@ pub fn classify_address(addr: &IPAddress) -> AddressClass {
             }
 
             // Check IANA registry
-            if is_iana_reserved(addr) {
+            if is_reserved_in(addr, registry) {
                 return AddressClass::Reserved;
             }
 
@@ -317,7 +633,7 @@ pub fn classify_address(addr: &IPAddress) -> AddressClass {
                 0xff00..=0xffff => AddressClass::Multicast, // ff00::/8 Multicast
                 0x2000..=0x3fff => AddressClass::Global, // 2000::/3 Global Unicast
                 _ => {
-                    if is_iana_reserved(addr) {
+                    if is_reserved_in(addr, registry) {
                         AddressClass::Reserved
                     } else {
                         AddressClass::Unknown
@@ -352,42 +668,82 @@ pub struct RIRInfo {
     pub rdap_base_url: String,
 }
 
-/// Get RIR information for an IP address (simplified mapping)
+impl RIRInfo {
+    /// Get the RIR for a registry name, as used in IANA designations
+    /// ("RIPE NCC", "Administered by ARIN") or delegated statistics files ("ripencc")
+    pub fn for_registry(name: &str) -> Option<RIRInfo> {
+        let name = name.trim().trim_start_matches("Administered by ").to_ascii_lowercase();
+        let (name, full_name, region, whois_server, rdap_base_url) = match name.as_str() {
+            "arin" => (
+                "ARIN",
+                "American Registry for Internet Numbers",
+                "North America",
+                "whois.arin.net",
+                "https://rdap.arin.net/registry",
+            ),
+            "ripe ncc" | "ripencc" => (
+                "RIPE NCC",
+                "Réseaux IP Européens Network Coordination Centre",
+                "Europe, Middle East, Central Asia",
+                "whois.ripe.net",
+                "https://rdap.db.ripe.net",
+            ),
+            "apnic" => (
+                "APNIC",
+                "Asia-Pacific Network Information Centre",
+                "Asia Pacific",
+                "whois.apnic.net",
+                "https://rdap.apnic.net",
+            ),
+            "lacnic" => (
+                "LACNIC",
+                "Latin America and Caribbean Network Information Centre",
+                "Latin America and the Caribbean",
+                "whois.lacnic.net",
+                "https://rdap.lacnic.net/rdap",
+            ),
+            "afrinic" => (
+                "AFRINIC",
+                "African Network Information Centre",
+                "Africa",
+                "whois.afrinic.net",
+                "https://rdap.afrinic.net/rdap",
+            ),
+            _ => return None,
+        };
+        Some(RIRInfo {
+            name: name.to_string(),
+            full_name: full_name.to_string(),
+            region: region.to_string(),
+            whois_server: whois_server.to_string(),
+            rdap_base_url: rdap_base_url.to_string(),
+        })
+    }
+}
+
+/// Get RIR information for an IP address
+///
+/// Uses the loaded RIR delegation statistics (see `crate::rir`) and falls
+/// back to the IANA allocation of the enclosing block. Special-purpose
+/// space, such as documentation prefixes, belongs to no RIR and gives `None`.
 pub fn get_rir_info(addr: &IPAddress) -> Option<RIRInfo> {
-    match addr.ip_type() {
-        IPAddressType::IPv4 => {
-            let ipv4 = addr.as_ipv4().unwrap();
-            let first_octet = ipv4.octets()[0];
-
-            // This is a simplified mapping based on historical allocations
-            // In reality, you'd need to consult the actual IANA IPv4 allocation table
-            match first_octet {
-                1..=2 | 4..=6 | 9 | 11 | 13..=15 | 18..=19 | 21..=22 | 26 | 28 | 30 | 32..=35 |
-                38..=39 | 44 | 47..=48 | 50 | 52..=53 | 55..=56 | 63..=64 | 66..=69 | 72 | 74..=75 |
-                96..=99 | 104..=107 | 173..=174 | 184..=185 | 192 | 198..=199 | 204..=207 | 209 |
-                216 | 222..=223 => Some(RIRInfo {
-                    name: "ARIN".to_string(),
-                    full_name: "American Registry for Internet Numbers".to_string(),
-                    region: "North America".to_string(),
-                    whois_server: "whois.arin.net".to_string(),
-                    rdap_base_url: "https://rdap.arin.net/registry".to_string(),
-                }),
-                62 | 77..=95 | 109..=109 | 176..=176 | 188..=188 | 193..=194 | 212..=213 | 217 => Some(RIRInfo {
-                    name: "RIPE NCC".to_string(),
-                    full_name: "Réseaux IP Européens Network Coordination Centre".to_string(),
-                    region: "Europe, Middle East, Central Asia".to_string(),
-                    whois_server: "whois.ripe.net".to_string(),
-                    rdap_base_url: "https://rdap.db.ripe.net".to_string(),
-                }),
-                _ => None, // Default or need more detailed lookup
-            }
-        }
-        IPAddressType::IPv6 => {
-            // IPv6 RIR allocation is based on 2000::/3 space
-            // This would require a more complex lookup table
-            None
-        }
+    let prefix_length = if addr.is_ipv4() { 32 } else { 128 };
+    get_network_rir_info(&IPNetwork::new_unchecked(addr.clone(), prefix_length))
+}
+
+/// Get RIR information for a network
+pub fn get_network_rir_info(network: &IPNetwork) -> Option<RIRInfo> {
+    if SPECIAL_PURPOSE_REGISTRY.get().longest_match(network).is_some() {
+        return None;
     }
+    crate::rir::lookup_network_delegation(network)
+        .and_then(|record| RIRInfo::for_registry(&record.registry))
+        .or_else(|| {
+            IANA_REGISTRY
+                .get()
+                .lookup_network(network)
+                .and_then(|info| RIRInfo::for_registry(&info.designation))
+        })
 }
 
 #[cfg(test)]
@@ -479,8 +835,16 @@ mod tests {
             assert_eq!(rir_info.name, "ARIN");
         }
 
-        // Note: This test might not pass with the simplified RIR mapping
-        // A full implementation would require the actual IANA allocation tables
+        let apnic = IPAddress::from_str("2001:200::1").unwrap();
+        assert_eq!(get_rir_info(&apnic).unwrap().name, "APNIC");
+        let ripe = IPNetwork::from_str("62.1.0.0/16").unwrap();
+        assert_eq!(get_network_rir_info(&ripe).unwrap().whois_server, "whois.ripe.net");
+        assert!(get_rir_info(&IPAddress::from_str("10.1.2.3").unwrap()).is_none());
+        assert!(get_rir_info(&IPAddress::from_str("2001:db8::1").unwrap()).is_none());
+        assert!(get_rir_info(&IPAddress::from_str("192.0.2.1").unwrap()).is_none());
+        assert!(get_network_rir_info(&IPNetwork::from_str("100.64.0.0/10").unwrap()).is_none());
+        assert_eq!(RIRInfo::for_registry("ripencc").unwrap().name, "RIPE NCC");
+        assert!(RIRInfo::for_registry("IANA").is_none());
     }
 
     #[test]
@@ -506,7 +870,174 @@ mod tests {
     #[test]
     fn test_network_lookup() {
         let network = IPNetwork::from_str("192.168.1.0/24").unwrap();
-        let info = IANA_REGISTRY.lookup_network(&network);
+        let info = IANA_REGISTRY.get().lookup_network(&network);
         assert!(info.is_some());
     }
+
+    const SAMPLE_XML: &str = r#"<registry xmlns="http://www.iana.org/assignments" id="sample">
+  <record>
+    <prefix>014/8</prefix>
+    <designation>AT&amp;T Bell Laboratories</designation>
+    <date>2008-02</date>
+    <status>RESERVED</status>
+  </record>
+  <records-note>ignored</records-note>
+  <record date="1999-07-01">
+    <prefix>2001:0200::/23</prefix>
+    <description>APNIC</description>
+    <whois>whois.apnic.net</whois>
+    <status>ALLOCATED</status>
+    <rdap>
+      <server>https://rdap.apnic.net/</server>
+    </rdap>
+    <notes/>
+  </record>
+  <record>
+    <addr>224.0.0.37-224.0.0.68</addr>
+    <description>Reserved</description>
+  </record>
+  <record>
+    <description>No prefix</description>
+  </record>
+</registry>"#;
+
+    #[test]
+    fn test_parse_iana_xml() {
+        let blocks = parse_iana_xml(SAMPLE_XML).unwrap();
+        let networks: Vec<String> = blocks.iter().map(|(network, _)| network.to_string()).collect();
+        assert_eq!(
+            networks,
+            vec![
+                "14.0.0.0/8", "2001:200::/23",
+                "224.0.0.37/32", "224.0.0.38/31", "224.0.0.40/29",
+                "224.0.0.48/28", "224.0.0.64/30", "224.0.0.68/32",
+            ]
+        );
+
+        let (_, info) = &blocks[0];
+        assert_eq!(info.designation, "AT&T Bell Laboratories");
+        assert_eq!(info.status, vec!["RESERVED".to_string()]);
+
+        let (_, info) = &blocks[1];
+        assert_eq!(info.date, "1999-07-01");
+        assert_eq!(info.whois, "whois.apnic.net");
+        assert_eq!(info.rdap, "https://rdap.apnic.net/");
+        assert_eq!(info.notes, "");
+
+        let err = parse_iana_xml("<record><prefix>300/8</prefix></record>").unwrap_err();
+        assert!(err.to_string().contains("Record 1"));
+    }
+
+    #[test]
+    fn test_longest_prefix_match() {
+        let mut registry = IANARegistry::new();
+        let info = |designation: &str| IANARegistryInfo {
+            designation: designation.to_string(),
+            date: "".to_string(),
+            whois: "".to_string(),
+            rdap: "".to_string(),
+            status: vec![],
+            notes: "".to_string(),
+        };
+        registry.add_ipv4_block(100, info("100/8"));
+        registry.add_block(&IPNetwork::from_str("100.64.0.0/10").unwrap(), info("100.64/10"));
+        registry.add_block(&IPNetwork::from_str("2001:db8::/32").unwrap(), info("documentation"));
+        assert_eq!(registry.len(), 3);
+
+        let lookup = |s: &str| registry.lookup_network(&IPNetwork::from_str(s).unwrap()).map(|i| i.designation);
+        assert_eq!(lookup("100.64.1.0/24"), Some("100.64/10".to_string()));
+        assert_eq!(lookup("100.64.0.0/10"), Some("100.64/10".to_string()));
+        assert_eq!(lookup("100.0.0.0/9"), Some("100/8".to_string()));
+        assert_eq!(lookup("100.0.0.0/7"), None);
+        assert_eq!(lookup("2001:db8:1::/48"), Some("documentation".to_string()));
+        assert_eq!(lookup("2001:db9::/32"), None);
+
+        let (network, _) = registry.longest_match(&IPNetwork::from_str("100.100.0.0/16").unwrap()).unwrap();
+        assert_eq!(network.to_string(), "100.64.0.0/10");
+
+        let blocks: Vec<String> = registry.ipv4_blocks().iter().map(|(n, _)| n.to_string()).collect();
+        assert_eq!(blocks, vec!["100.0.0.0/8", "100.64.0.0/10"]);
+    }
+
+    #[test]
+    fn test_bundled_registry() {
+        let registry = IANA_REGISTRY.get();
+        let lookup = |s: &str| registry.lookup_network(&IPNetwork::from_str(s).unwrap()).unwrap();
+
+        assert_eq!(lookup("224.0.0.1/32").designation, "All Systems on this Subnet");
+        assert_eq!(lookup("224.0.0.40/32").designation, "zeroconfaddr (renew 12/02)");
+        assert_eq!(lookup("12.1.0.0/16").designation, "AT&T Bell Laboratories");
+        assert_eq!(lookup("8.8.8.0/24").status, vec!["LEGACY".to_string()]);
+        assert_eq!(lookup("2001:db8::/32").notes, "Documentation");
+        assert_eq!(lookup("192.0.2.0/24").notes, "Documentation (TEST-NET-1)");
+        assert_eq!(lookup("100.64.1.0/24").designation, "100.64.0.0/10");
+        assert_eq!(lookup("100.64.1.0/24").notes, "Shared Address Space");
+        assert_eq!(lookup("2001:4860::/32").designation, "ARIN");
+        assert_eq!(lookup("4000::/3").designation, "Reserved by IETF");
+        assert_eq!(lookup("192.168.1.0/24").designation, "192.168.0.0/16");
+    }
+
+    #[test]
+    fn test_special_purpose_lookup() {
+        let info = lookup_special_purpose(&IPAddress::from_str("100.64.1.1").unwrap()).unwrap();
+        assert_eq!(info.name, "Shared Address Space");
+        assert_eq!(info.forwardable, Some(true));
+        assert_eq!(info.globally_reachable, Some(false));
+
+        // More specific blocks override the enclosing IETF Protocol Assignments block
+        let pcp = lookup_special_purpose(&IPAddress::from_str("192.0.0.9").unwrap()).unwrap();
+        assert_eq!(pcp.rfc, "RFC7723");
+        let ietf = lookup_special_purpose(&IPAddress::from_str("192.0.0.100").unwrap()).unwrap();
+        assert_eq!(ietf.name, "IETF Protocol Assignments");
+
+        let teredo = lookup_special_purpose(&IPAddress::from_str("2001::1").unwrap()).unwrap();
+        assert_eq!(teredo.name, "TEREDO");
+        assert_eq!(teredo.globally_reachable, None);
+
+        let network = IPNetwork::from_str("2001:db8:1::/48").unwrap();
+        assert_eq!(lookup_special_purpose_network(&network).unwrap().name, "Documentation");
+        assert!(lookup_special_purpose(&IPAddress::from_str("8.8.8.8").unwrap()).is_none());
+    }
+
+    #[test]
+    fn test_globally_reachable() {
+        for addr in ["8.8.8.8", "192.0.0.9", "192.31.196.1", "2001:4860::8888", "64:ff9b::808:808",
+                     "2001:1::3", "2001:30::1"] {
+            assert!(is_globally_reachable(&IPAddress::from_str(addr).unwrap()), "{}", addr);
+        }
+        for addr in ["10.1.2.3", "127.0.0.1", "192.0.0.100", "198.51.100.1", "255.255.255.255",
+                     "192.88.99.2", "::1", "fe80::1", "2001:db8::1", "2001::1", "3fff::1", "5f00::1",
+                     "100:0:0:1::1"] {
+            assert!(!is_globally_reachable(&IPAddress::from_str(addr).unwrap()), "{}", addr);
+        }
+
+        let network = |s: &str| IPNetwork::from_str(s).unwrap();
+        assert!(is_network_globally_reachable(&network("8.8.8.0/24")));
+        assert!(is_network_globally_reachable(&network("192.0.0.9/32")));
+        assert!(!is_network_globally_reachable(&network("192.0.0.0/24")));
+        assert!(!is_network_globally_reachable(&network("192.0.0.0/16")));
+        assert!(!is_network_globally_reachable(&network("0.0.0.0/0")));
+        assert!(is_network_globally_reachable(&network("2001:4860::/32")));
+    }
+
+    #[test]
+    fn test_explicit_registry() {
+        let mut registry = IANARegistry::new();
+        registry.add_ipv4_block(100, IANARegistryInfo {
+            designation: "100.0.0.0/8".to_string(),
+            date: "2023-01".to_string(),
+            whois: "".to_string(),
+            rdap: "".to_string(),
+            status: vec!["RESERVED".to_string()],
+            notes: "".to_string(),
+        });
+
+        let addr = IPAddress::from_str("100.1.2.3").unwrap();
+        assert_eq!(classify_address_with(&addr, &registry), AddressClass::Reserved);
+        assert_eq!(classify_address_with(&addr, &IANARegistry::new()), AddressClass::Global);
+        assert_eq!(
+            lookup_iana_info_with(&addr, &registry).map(|info| info.designation),
+            Some("100.0.0.0/8".to_string())
+        );
+    }
 }
\ No newline at end of file
@@ -1,8 +1,12 @@
 //! IP Address implementation
 
 use crate::error::{AddrFormatError, AddrResult};
+use crate::ip::reverse_dns::reverse_labels;
+use crate::strategy::ipv4::IPv4Strategy;
+use crate::ParseFlags;
 use std::fmt;
-use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddrV6};
+use std::ops::{Add, BitAnd, BitOr, Not, Shl, Shr, Sub};
 use std::str::FromStr;
 
 /// IP address types
@@ -13,31 +17,89 @@ pub enum IPAddressType {
 }
 
 /// Represents either an IPv4 or IPv6 address
+///
+/// IPv6 addresses may carry an RFC 4007 zone (`fe80::1%eth0` or `fe80::1%3`).
+/// The zone takes part in equality, hashing and ordering, so the same
+/// link-local address on two interfaces gives two distinct values.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct IPAddress {
     addr: IpAddr,
+    zone: Option<String>,
 }
 
 impl IPAddress {
     /// Create a new IP address from an IpAddr
     pub fn new(addr: IpAddr) -> Self {
-        Self { addr }
+        Self { addr, zone: None }
     }
 
     /// Create a new IPv4 address
     pub fn new_v4(addr: Ipv4Addr) -> Self {
-        Self {
-            addr: IpAddr::V4(addr),
-        }
+        Self::new(IpAddr::V4(addr))
     }
 
     /// Create a new IPv6 address
     pub fn new_v6(addr: Ipv6Addr) -> Self {
-        Self {
-            addr: IpAddr::V6(addr),
+        Self::new(IpAddr::V6(addr))
+    }
+
+    /// Get the IPv6 zone (interface name or numeric scope id), if any
+    pub fn zone(&self) -> Option<&str> {
+        self.zone.as_deref()
+    }
+
+    /// Get a copy of this address with the given IPv6 zone
+    pub fn with_zone(&self, zone: &str) -> AddrResult<IPAddress> {
+        if self.is_ipv4() {
+            return Err(AddrFormatError::new(format!("IPv4 address {} cannot have a zone", self)));
+        }
+        if zone.is_empty() || zone.contains(|c: char| c.is_whitespace() || matches!(c, '%' | '/' | '[' | ']')) {
+            return Err(AddrFormatError::new(format!("Invalid IPv6 zone '{}'", zone)));
+        }
+        Ok(Self {
+            addr: self.addr,
+            zone: Some(zone.to_string()),
+        })
+    }
+
+    /// Get a copy of this address without its zone
+    pub fn without_zone(&self) -> IPAddress {
+        Self::new(self.addr)
+    }
+
+    /// Get the zone as a numeric scope id, if it is one
+    ///
+    /// Interface names such as `eth0` are not resolved to indexes.
+    pub fn scope_id(&self) -> Option<u32> {
+        self.zone.as_deref()?.parse().ok()
+    }
+
+    /// Create an address from a socket address, using a non-zero scope id as the zone
+    pub fn from_socket_addr_v6(socket: &SocketAddrV6) -> Self {
+        let addr = Self::new_v6(*socket.ip());
+        match socket.scope_id() {
+            0 => addr,
+            scope_id => Self {
+                zone: Some(scope_id.to_string()),
+                ..addr
+            },
         }
     }
 
+    /// Create a socket address for this IPv6 address, carrying a numeric zone as the scope id
+    pub fn to_socket_addr_v6(&self, port: u16) -> AddrResult<SocketAddrV6> {
+        let addr = self
+            .as_ipv6()
+            .ok_or_else(|| AddrFormatError::new(format!("{} is not an IPv6 address", self)))?;
+        let scope_id = match self.zone() {
+            None => 0,
+            Some(zone) => self.scope_id().ok_or_else(|| {
+                AddrFormatError::new(format!("Zone '{}' is not a numeric scope id", zone))
+            })?,
+        };
+        Ok(SocketAddrV6::new(*addr, port, 0, scope_id))
+    }
+
     /// Get the IP address type
     pub fn ip_type(&self) -> IPAddressType {
         match self.addr {
@@ -137,6 +199,26 @@ impl IPAddress {
         }
     }
 
+    /// Create from a binary representation (4 bytes for IPv4, 16 bytes for IPv6)
+    pub fn from_bytes(bytes: &[u8]) -> AddrResult<Self> {
+        match bytes.len() {
+            4 => {
+                let mut octets = [0u8; 4];
+                octets.copy_from_slice(bytes);
+                Ok(IPAddress::new_v4(Ipv4Addr::from(octets)))
+            }
+            16 => {
+                let mut octets = [0u8; 16];
+                octets.copy_from_slice(bytes);
+                Ok(IPAddress::new_v6(Ipv6Addr::from(octets)))
+            }
+            len => Err(AddrFormatError::new(format!(
+                "Invalid IP address length: {} (expected 4 or 16 bytes)",
+                len
+            ))),
+        }
+    }
+
     /// Convert to hexadecimal representation
     pub fn to_hex(&self) -> String {
         match self.addr {
@@ -152,67 +234,133 @@ impl IPAddress {
     }
 
     /// Get the reverse DNS pointer name
+    ///
+    /// IPv4 names list the octets in reverse under `in-addr.arpa`; IPv6 names
+    /// list all 32 nibbles in reverse under `ip6.arpa`.
     pub fn reverse_dns(&self) -> String {
         match self.addr {
-            IpAddr::V4(addr) => {
-                let octets = addr.octets();
-                format!("{}.{}.{}.{}.in-addr.arpa", octets[3], octets[2], octets[1], octets[0])
-            }
-            IpAddr::V6(_addr) => {
-                let hex_str = self.to_hex();
-                let reversed: String = hex_str
-                    .chars()
-                    .rev()
-                    .enumerate()
-                    .map(|(i, c)| if i > 0 && i % 1 == 0 { format!(".{}", c) } else { c.to_string() })
-                    .collect();
-                format!("{}.ip6.arpa", reversed)
-            }
+            IpAddr::V4(_) => format!("{}.in-addr.arpa", reverse_labels(self, 32)),
+            IpAddr::V6(_) => format!("{}.ip6.arpa", reverse_labels(self, 128)),
         }
     }
 
-    /// Get the next IP address in sequence
-    pub fn next(&self) -> Option<IPAddress> {
+    /// Parse a reverse DNS pointer name, such as `1.2.0.192.in-addr.arpa`
+    ///
+    /// The name must hold every octet (IPv4) or nibble (IPv6); use
+    /// `IPNetwork::from_reverse_dns` for partial names.
+    pub fn from_reverse_dns(name: &str) -> AddrResult<IPAddress> {
+        let network = crate::ip::reverse_dns::parse_reverse_name(name)?;
+        let (labels, label_bits) = if network.is_ipv4() { (4, 8) } else { (32, 4) };
+        let found = network.prefix_length() / label_bits;
+        if found != labels {
+            return Err(AddrFormatError::new(format!(
+                "Invalid reverse DNS name '{}': expected {} labels for an address, found {}",
+                name, labels, found
+            )));
+        }
+        Ok(network.network_address().clone())
+    }
+
+    /// Get the address as an integer (IPv4 addresses are zero-extended)
+    pub fn to_u128(&self) -> u128 {
         match self.addr {
-            IpAddr::V4(addr) => {
-                let int_addr = u32::from(addr);
-                if int_addr == u32::MAX {
-                    None
-                } else {
-                    Some(IPAddress::new_v4(Ipv4Addr::from(int_addr + 1)))
-                }
-            }
-            IpAddr::V6(addr) => {
-                let int_addr = u128::from(addr);
-                if int_addr == u128::MAX {
-                    None
-                } else {
-                    Some(IPAddress::new_v6(Ipv6Addr::from(int_addr + 1)))
-                }
-            }
+            IpAddr::V4(addr) => u32::from(addr) as u128,
+            IpAddr::V6(addr) => u128::from(addr),
+        }
+    }
+
+    /// Create an address of the same family and zone from an integer (truncated to fit)
+    pub(crate) fn with_value(&self, value: u128) -> IPAddress {
+        let addr = match self.addr {
+            IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::from(value as u32)),
+            IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::from(value)),
+        };
+        Self {
+            addr,
+            zone: self.zone.clone(),
+        }
+    }
+
+    /// Get the largest integer value of this address family
+    pub(crate) fn max_value(&self) -> u128 {
+        match self.addr {
+            IpAddr::V4(_) => u32::MAX as u128,
+            IpAddr::V6(_) => u128::MAX,
+        }
+    }
+
+    /// Add an offset, returning `None` if the result is past the end of the address space
+    pub fn checked_add(&self, offset: u128) -> Option<IPAddress> {
+        self.to_u128()
+            .checked_add(offset)
+            .filter(|value| *value <= self.max_value())
+            .map(|value| self.with_value(value))
+    }
+
+    /// Subtract an offset, returning `None` if the result is before the start of the address space
+    pub fn checked_sub(&self, offset: u128) -> Option<IPAddress> {
+        self.to_u128().checked_sub(offset).map(|value| self.with_value(value))
+    }
+
+    /// Add an offset, wrapping around at the end of the address space
+    pub fn wrapping_add(&self, offset: u128) -> IPAddress {
+        // 2^32 divides 2^128, so truncating the u128 sum wraps IPv4 correctly
+        self.with_value(self.to_u128().wrapping_add(offset))
+    }
+
+    /// Subtract an offset, wrapping around at the start of the address space
+    pub fn wrapping_sub(&self, offset: u128) -> IPAddress {
+        self.with_value(self.to_u128().wrapping_sub(offset))
+    }
+
+    /// Get the number of addresses between this address and another of the same family
+    ///
+    /// The distance is unsigned, as the IPv6 address space does not fit in
+    /// an `i128`, and is the same whichever address comes first; compare the
+    /// addresses for the direction. Returns `None` for different families.
+    pub fn distance(&self, other: &IPAddress) -> Option<u128> {
+        if self.ip_type() != other.ip_type() {
+            return None;
         }
+        Some(self.to_u128().abs_diff(other.to_u128()))
+    }
+
+    /// Get the next IP address in sequence
+    pub fn next(&self) -> Option<IPAddress> {
+        self.checked_add(1)
     }
 
     /// Get the previous IP address in sequence
     pub fn prev(&self) -> Option<IPAddress> {
-        match self.addr {
-            IpAddr::V4(addr) => {
-                let int_addr = u32::from(addr);
-                if int_addr == 0 {
-                    None
-                } else {
-                    Some(IPAddress::new_v4(Ipv4Addr::from(int_addr - 1)))
-                }
-            }
-            IpAddr::V6(addr) => {
-                let int_addr = u128::from(addr);
-                if int_addr == 0 {
-                    None
-                } else {
-                    Some(IPAddress::new_v6(Ipv6Addr::from(int_addr - 1)))
-                }
-            }
+        self.checked_sub(1)
+    }
+}
+
+impl IPAddress {
+    /// Parse an address, honoring the given parse flags
+    ///
+    /// IPv4 uses strict inet_pton() rules unless `INET_ATON` is set, in which
+    /// case legacy forms such as `10.1` or `0x7f.1` are accepted. `ZEROFILL`
+    /// strips leading zeros from each octet first, so `010.001.002.003` is
+    /// 10.1.2.3 rather than an octal address. IPv6 ignores the IPv4 flags.
+    pub fn parse_with(s: &str, flags: ParseFlags) -> AddrResult<Self> {
+        if s.contains(':') {
+            return IPAddress::from_str(s);
         }
+
+        let inet_aton = flags.contains(ParseFlags::INET_ATON);
+        if inet_aton && flags.contains(ParseFlags::INET_PTON) {
+            return Err(AddrFormatError::new("INET_ATON and INET_PTON flags are mutually exclusive"));
+        }
+
+        let ipv4 = match (inet_aton, flags.contains(ParseFlags::ZEROFILL)) {
+            (true, true) => IPv4Strategy::parse_inet_aton(&IPv4Strategy::strip_zerofill(s)),
+            (true, false) => IPv4Strategy::parse_inet_aton(s),
+            (false, true) => IPv4Strategy::parse_with_zerofill(s),
+            (false, false) => IPv4Strategy::parse_inet_pton(s),
+        }
+        .map_err(|e| AddrFormatError::new(format!("Invalid IP address '{}': {}", s, e.message)))?;
+        Ok(IPAddress::new_v4(Ipv4Addr::from(ipv4)))
     }
 }
 
@@ -220,15 +368,26 @@ impl FromStr for IPAddress {
     type Err = AddrFormatError;
 
     fn from_str(s: &str) -> AddrResult<Self> {
-        IpAddr::from_str(s)
+        let (addr, zone) = match s.split_once('%') {
+            Some((addr, zone)) => (addr, Some(zone)),
+            None => (s, None),
+        };
+        let addr = IpAddr::from_str(addr)
             .map(IPAddress::new)
-            .map_err(|e| AddrFormatError::new(format!("Invalid IP address '{}': {}", s, e)))
+            .map_err(|e| AddrFormatError::new(format!("Invalid IP address '{}': {}", s, e)))?;
+        match zone {
+            Some(zone) => addr.with_zone(zone),
+            None => Ok(addr),
+        }
     }
 }
 
 impl fmt::Display for IPAddress {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.addr)
+        match &self.zone {
+            Some(zone) => write!(f, "{}%{}", self.addr, zone),
+            None => write!(f, "{}", self.addr),
+        }
     }
 }
 
@@ -250,12 +409,86 @@ impl From<Ipv6Addr> for IPAddress {
     }
 }
 
+impl From<SocketAddrV6> for IPAddress {
+    fn from(socket: SocketAddrV6) -> Self {
+        IPAddress::from_socket_addr_v6(&socket)
+    }
+}
+
+/// The zone is dropped, as `IpAddr` cannot carry one
 impl From<IPAddress> for IpAddr {
     fn from(addr: IPAddress) -> Self {
         addr.addr
     }
 }
 
+impl Add<u128> for IPAddress {
+    type Output = IPAddress;
+
+    fn add(self, offset: u128) -> IPAddress {
+        self.checked_add(offset).expect("IP address overflow")
+    }
+}
+
+impl Sub<u128> for IPAddress {
+    type Output = IPAddress;
+
+    fn sub(self, offset: u128) -> IPAddress {
+        self.checked_sub(offset).expect("IP address underflow")
+    }
+}
+
+impl BitAnd for IPAddress {
+    type Output = IPAddress;
+
+    fn bitand(self, mask: IPAddress) -> IPAddress {
+        assert_eq!(self.ip_type(), mask.ip_type(), "cannot mask addresses of different families");
+        self.with_value(self.to_u128() & mask.to_u128())
+    }
+}
+
+impl BitOr for IPAddress {
+    type Output = IPAddress;
+
+    fn bitor(self, mask: IPAddress) -> IPAddress {
+        assert_eq!(self.ip_type(), mask.ip_type(), "cannot mask addresses of different families");
+        self.with_value(self.to_u128() | mask.to_u128())
+    }
+}
+
+impl Not for IPAddress {
+    type Output = IPAddress;
+
+    fn not(self) -> IPAddress {
+        self.with_value(!self.to_u128())
+    }
+}
+
+impl Shl<u32> for IPAddress {
+    type Output = IPAddress;
+
+    fn shl(self, bits: u32) -> IPAddress {
+        // Bits shifted past the IPv4 width are dropped when truncating back
+        self.with_value(self.to_u128().checked_shl(bits).unwrap_or(0))
+    }
+}
+
+impl Shr<u32> for IPAddress {
+    type Output = IPAddress;
+
+    fn shr(self, bits: u32) -> IPAddress {
+        self.with_value(self.to_u128().checked_shr(bits).unwrap_or(0))
+    }
+}
+
+forward_ref_op!(impl Add<u128>, add for IPAddress);
+forward_ref_op!(impl Sub<u128>, sub for IPAddress);
+forward_ref_op!(impl BitAnd<IPAddress>, bitand for IPAddress);
+forward_ref_op!(impl BitOr<IPAddress>, bitor for IPAddress);
+forward_ref_op!(impl Not, not for IPAddress);
+forward_ref_op!(impl Shl<u32>, shl for IPAddress);
+forward_ref_op!(impl Shr<u32>, shr for IPAddress);
+
 impl PartialOrd for IPAddress {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         Some(self.cmp(other))
@@ -269,12 +502,13 @@ impl Ord for IPAddress {
             (4, 6) => std::cmp::Ordering::Less,
             (6, 4) => std::cmp::Ordering::Greater,
             _ => {
-                // Same version, compare addresses
-                match (self.addr, other.addr) {
+                // Same version, compare addresses, then zones (unzoned first)
+                let by_value = match (self.addr, other.addr) {
                     (IpAddr::V4(a), IpAddr::V4(b)) => u32::from(a).cmp(&u32::from(b)),
                     (IpAddr::V6(a), IpAddr::V6(b)) => u128::from(a).cmp(&u128::from(b)),
                     _ => unreachable!(),
-                }
+                };
+                by_value.then_with(|| self.zone.cmp(&other.zone))
             }
         }
     }
@@ -321,10 +555,88 @@ mod tests {
         assert_eq!(prev.to_string(), "192.168.1.1");
     }
 
+    #[test]
+    fn test_operators() {
+        let net = IPAddress::from_str("192.168.1.0").unwrap();
+        assert_eq!((&net + 42).to_string(), "192.168.1.42");
+        assert_eq!((net.clone() - 1).to_string(), "192.168.0.255");
+        assert_eq!(net.checked_add(1 << 32), None);
+        assert_eq!(IPAddress::from_str("255.255.255.255").unwrap().wrapping_add(1).to_string(), "0.0.0.0");
+        assert_eq!(IPAddress::from_str("::").unwrap().wrapping_sub(1).to_string(), "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff");
+
+        let other = IPAddress::from_str("192.168.2.1").unwrap();
+        assert_eq!(net.distance(&other), Some(257));
+        assert_eq!(other.distance(&net), Some(257));
+        assert_eq!(net.distance(&IPAddress::from_str("::1").unwrap()), None);
+
+        let mask = IPAddress::from_str("255.255.255.0").unwrap();
+        let host = IPAddress::from_str("10.1.2.3").unwrap();
+        assert_eq!((&host & mask.clone()).to_string(), "10.1.2.0");
+        assert_eq!((host | !mask).to_string(), "10.1.2.255");
+        assert_eq!((IPAddress::from_str("0.0.0.1").unwrap() << 24).to_string(), "1.0.0.0");
+        assert_eq!((IPAddress::from_str("1::").unwrap() >> 112).to_string(), "::1");
+        assert_eq!((IPAddress::from_str("1.2.3.4").unwrap() << 32).to_string(), "0.0.0.0");
+    }
+
+    #[test]
+    fn test_parse_with_flags() {
+        let parse = |s, flags| IPAddress::parse_with(s, flags).map(|addr| addr.to_string());
+
+        assert_eq!(parse("192.0.2.1", ParseFlags::empty()).unwrap(), "192.0.2.1");
+        assert!(parse("10.1", ParseFlags::empty()).is_err());
+        assert!(parse("010.001.002.003", ParseFlags::INET_PTON).is_err());
+        assert_eq!(parse("10.1", ParseFlags::INET_ATON).unwrap(), "10.0.0.1");
+        assert_eq!(parse("010.001.002.003", ParseFlags::INET_ATON).unwrap(), "8.1.2.3");
+        assert_eq!(parse("010.001.002.003", ParseFlags::ZEROFILL).unwrap(), "10.1.2.3");
+        assert_eq!(parse("010.01", ParseFlags::ZEROFILL | ParseFlags::INET_ATON).unwrap(), "10.0.0.1");
+        assert!(parse("10.1", ParseFlags::INET_ATON | ParseFlags::INET_PTON).is_err());
+        assert_eq!(parse("2001:db8::1", ParseFlags::INET_ATON).unwrap(), "2001:db8::1");
+
+        assert_eq!(ParseFlags::from_bits(crate::core::ZEROFILL), ParseFlags::ZEROFILL);
+        assert_eq!(ParseFlags::INET_ATON.bits(), crate::core::INET_ATON);
+    }
+
+    #[test]
+    fn test_zones() {
+        let named = IPAddress::from_str("fe80::1%eth0").unwrap();
+        assert_eq!(named.zone(), Some("eth0"));
+        assert_eq!(named.scope_id(), None);
+        assert_eq!(named.to_string(), "fe80::1%eth0");
+
+        let plain = IPAddress::from_str("fe80::1").unwrap();
+        assert_ne!(named, plain);
+        assert_eq!(named.without_zone(), plain);
+        assert!(plain < named);
+        assert!(named < IPAddress::from_str("fe80::1%eth1").unwrap());
+        assert!(named < IPAddress::from_str("fe80::2").unwrap());
+        assert_eq!(named.next().unwrap().to_string(), "fe80::2%eth0");
+
+        let numeric = IPAddress::from_str("fe80::1%3").unwrap();
+        let socket = numeric.to_socket_addr_v6(22).unwrap();
+        assert_eq!(socket.scope_id(), 3);
+        assert_eq!(IPAddress::from(socket), numeric);
+        assert!(named.to_socket_addr_v6(22).is_err());
+        assert_eq!(IPAddress::from(SocketAddrV6::new(Ipv6Addr::LOCALHOST, 80, 0, 0)).zone(), None);
+
+        assert!(IPAddress::from_str("192.0.2.1%eth0").is_err());
+        assert!(IPAddress::from_str("fe80::1%").is_err());
+        assert!(IPAddress::from_str("fe80::1%eth 0").is_err());
+    }
+
     #[test]
     fn test_reverse_dns() {
         let addr = IPAddress::from_str("192.168.1.1").unwrap();
         assert_eq!(addr.reverse_dns(), "1.1.168.192.in-addr.arpa");
+
+        let addr = IPAddress::from_str("2001:db8::567:89ab").unwrap();
+        assert_eq!(
+            addr.reverse_dns(),
+            "b.a.9.8.7.6.5.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa"
+        );
+        assert_eq!(IPAddress::from_reverse_dns(&addr.reverse_dns()).unwrap(), addr);
+        assert_eq!(IPAddress::from_reverse_dns("1.1.168.192.in-addr.arpa.").unwrap().to_string(), "192.168.1.1");
+        let err = IPAddress::from_reverse_dns("168.192.in-addr.arpa").unwrap_err();
+        assert!(err.to_string().contains("expected 4 labels for an address, found 2"));
     }
 
     #[test]
@@ -342,5 +654,10 @@ mod tests {
         let addr = IPAddress::from_str("192.168.1.1").unwrap();
         assert_eq!(addr.to_binary(), vec![192, 168, 1, 1]);
         assert_eq!(addr.to_hex(), "c0a80101");
+
+        assert_eq!(IPAddress::from_bytes(&addr.to_binary()).unwrap(), addr);
+        let addr6 = IPAddress::from_str("2001:db8::1").unwrap();
+        assert_eq!(IPAddress::from_bytes(&addr6.to_binary()).unwrap(), addr6);
+        assert!(IPAddress::from_bytes(&[1, 2, 3]).is_err());
     }
 }
\ No newline at end of file
@@ -0,0 +1,193 @@
+//! Address counts that can exceed the range of `u128`
+//!
+//! The IPv6 address space holds 2^128 addresses, one more than `u128::MAX`,
+//! and a set spanning both families holds 2^128 + 2^32. `AddressCount` keeps
+//! an extra 64 bits above the `u128` so that sizes of ranges, networks and
+//! sets are always exact.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign};
+
+/// An exact count of IP addresses
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AddressCount {
+    high: u64,
+    low: u128,
+}
+
+impl AddressCount {
+    /// A count of zero addresses
+    pub const ZERO: AddressCount = AddressCount { high: 0, low: 0 };
+
+    /// Create a count from a `u128`
+    pub const fn new(count: u128) -> Self {
+        Self { high: 0, low: count }
+    }
+
+    /// The number of addresses in a block with `bits` host bits (2^bits)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is greater than 128.
+    pub const fn power_of_two(bits: u32) -> Self {
+        assert!(bits <= 128, "address blocks have at most 128 host bits");
+        if bits == 128 {
+            Self { high: 1, low: 0 }
+        } else {
+            Self { high: 0, low: 1u128 << bits }
+        }
+    }
+
+    /// Get the count as a `u128`, if it fits
+    pub fn to_u128(&self) -> Option<u128> {
+        if self.high == 0 {
+            Some(self.low)
+        } else {
+            None
+        }
+    }
+
+    /// Get the count as an `f64`, rounded to the nearest representable value
+    pub fn to_f64(&self) -> f64 {
+        self.high as f64 * 2f64.powi(128) + self.low as f64
+    }
+
+    /// Check if the count is zero
+    pub fn is_zero(&self) -> bool {
+        self.high == 0 && self.low == 0
+    }
+
+    /// Add two counts, returning `None` on overflow
+    pub fn checked_add(self, other: AddressCount) -> Option<AddressCount> {
+        let (low, carry) = self.low.overflowing_add(other.low);
+        let high = self.high.checked_add(other.high)?.checked_add(carry as u64)?;
+        Some(AddressCount { high, low })
+    }
+}
+
+impl From<u128> for AddressCount {
+    fn from(count: u128) -> Self {
+        AddressCount::new(count)
+    }
+}
+
+impl From<u64> for AddressCount {
+    fn from(count: u64) -> Self {
+        AddressCount::new(count as u128)
+    }
+}
+
+impl From<u32> for AddressCount {
+    fn from(count: u32) -> Self {
+        AddressCount::new(count as u128)
+    }
+}
+
+impl TryFrom<AddressCount> for u128 {
+    type Error = crate::error::AddrConversionError;
+
+    fn try_from(count: AddressCount) -> Result<Self, Self::Error> {
+        count.to_u128().ok_or_else(|| {
+            crate::error::AddrConversionError::new(format!(
+                "Address count {} does not fit in a u128",
+                count
+            ))
+        })
+    }
+}
+
+impl PartialEq<u128> for AddressCount {
+    fn eq(&self, other: &u128) -> bool {
+        self.high == 0 && self.low == *other
+    }
+}
+
+impl PartialOrd<u128> for AddressCount {
+    fn partial_cmp(&self, other: &u128) -> Option<Ordering> {
+        Some(self.cmp(&AddressCount::new(*other)))
+    }
+}
+
+impl Add for AddressCount {
+    type Output = AddressCount;
+
+    fn add(self, other: AddressCount) -> AddressCount {
+        self.checked_add(other).expect("address count overflow")
+    }
+}
+
+impl AddAssign for AddressCount {
+    fn add_assign(&mut self, other: AddressCount) {
+        *self = *self + other;
+    }
+}
+
+impl Sum for AddressCount {
+    fn sum<I: Iterator<Item = AddressCount>>(iter: I) -> Self {
+        iter.fold(AddressCount::ZERO, Add::add)
+    }
+}
+
+impl<'a> Sum<&'a AddressCount> for AddressCount {
+    fn sum<I: Iterator<Item = &'a AddressCount>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
+impl fmt::Display for AddressCount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.high == 0 {
+            return write!(f, "{}", self.low);
+        }
+
+        // Long division of the 192-bit value by 10^19, one 64-bit limb at a time
+        const CHUNK: u128 = 10_000_000_000_000_000_000;
+        let mut limbs = [self.high, (self.low >> 64) as u64, self.low as u64];
+        let mut chunks = Vec::new();
+        while limbs.iter().any(|&limb| limb != 0) {
+            let mut rem = 0u128;
+            for limb in limbs.iter_mut() {
+                let cur = (rem << 64) | *limb as u128;
+                *limb = (cur / CHUNK) as u64;
+                rem = cur % CHUNK;
+            }
+            chunks.push(rem as u64);
+        }
+
+        let mut chunks = chunks.into_iter().rev();
+        write!(f, "{}", chunks.next().unwrap_or(0))?;
+        for chunk in chunks {
+            write!(f, "{:019}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_full_ipv6_space() {
+        let count = AddressCount::power_of_two(128);
+        assert_eq!(count.to_u128(), None);
+        assert!(count > u128::MAX);
+        assert_eq!(count, AddressCount::new(u128::MAX) + AddressCount::new(1));
+        assert_eq!(count.to_string(), "340282366920938463463374607431768211456");
+        assert_eq!(count.to_f64(), 2f64.powi(128));
+
+        let both = count + AddressCount::power_of_two(32);
+        assert_eq!(both.to_string(), "340282366920938463463374607436063178752");
+    }
+
+    #[test]
+    fn test_small_counts() {
+        let count: AddressCount = [256u128, 1, 10].iter().map(|&n| AddressCount::from(n)).sum();
+        assert_eq!(count, 267);
+        assert_eq!(count.to_string(), "267");
+        assert_eq!(u128::try_from(count).unwrap(), 267);
+        assert!(AddressCount::ZERO.is_zero());
+    }
+}
@@ -0,0 +1,302 @@
+//! Address and port endpoints
+//!
+//! Endpoints use the URL convention of bracketing IPv6 hosts, so
+//! `1.2.3.4:443`, `[2001:db8::1]:8443` and `[fe80::1%eth0]:22` are all valid,
+//! while an unbracketed IPv6 address is rejected as ambiguous.
+
+use crate::error::{AddrFormatError, AddrResult};
+use crate::ip::{IPAddress, IPNetwork};
+use std::fmt;
+use std::net::{SocketAddr, SocketAddrV4};
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+/// Split `host:port` or `[host]:port` into its host and port parts
+fn split_host_port(s: &str) -> AddrResult<(&str, &str)> {
+    let invalid = |reason: &str| AddrFormatError::new(format!("Invalid endpoint '{}': {}", s, reason));
+
+    if let Some(rest) = s.strip_prefix('[') {
+        let (host, port) = rest.split_once("]:").ok_or_else(|| invalid("expected '[host]:port'"))?;
+        if !host.contains(':') {
+            return Err(invalid("only IPv6 hosts are bracketed"));
+        }
+        return Ok((host, port));
+    }
+
+    let (host, port) = s.rsplit_once(':').ok_or_else(|| invalid("missing port"))?;
+    if host.contains(':') {
+        return Err(invalid("IPv6 hosts must be bracketed, as in '[2001:db8::1]:80'"));
+    }
+    Ok((host, port))
+}
+
+fn parse_port(s: &str) -> AddrResult<u16> {
+    // u16::from_str accepts a leading '+', which is not a valid port
+    if !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()) {
+        if let Ok(port) = s.parse() {
+            return Ok(port);
+        }
+    }
+    Err(AddrFormatError::new(format!("Invalid port '{}'", s)))
+}
+
+/// An IP address and port, such as `192.0.2.1:443` or `[2001:db8::1]:8443`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct IPEndpoint {
+    address: IPAddress,
+    port: u16,
+}
+
+impl IPEndpoint {
+    /// Create a new endpoint
+    pub fn new(address: IPAddress, port: u16) -> Self {
+        Self { address, port }
+    }
+
+    /// Get the address
+    pub fn address(&self) -> &IPAddress {
+        &self.address
+    }
+
+    /// Get the port
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Convert to a socket address
+    ///
+    /// IPv6 zones must be numeric scope ids, as interface names are not resolved.
+    pub fn to_socket_addr(&self) -> AddrResult<SocketAddr> {
+        match self.address.as_ipv4() {
+            Some(addr) => Ok(SocketAddr::V4(SocketAddrV4::new(*addr, self.port))),
+            None => Ok(SocketAddr::V6(self.address.to_socket_addr_v6(self.port)?)),
+        }
+    }
+}
+
+impl FromStr for IPEndpoint {
+    type Err = AddrFormatError;
+
+    fn from_str(s: &str) -> AddrResult<Self> {
+        let (host, port) = split_host_port(s)?;
+        Ok(Self::new(IPAddress::from_str(host)?, parse_port(port)?))
+    }
+}
+
+impl fmt::Display for IPEndpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.address.is_ipv6() {
+            write!(f, "[{}]:{}", self.address, self.port)
+        } else {
+            write!(f, "{}:{}", self.address, self.port)
+        }
+    }
+}
+
+impl From<SocketAddr> for IPEndpoint {
+    fn from(socket: SocketAddr) -> Self {
+        match socket {
+            SocketAddr::V4(socket) => Self::new(IPAddress::new_v4(*socket.ip()), socket.port()),
+            SocketAddr::V6(socket) => Self::new(IPAddress::from_socket_addr_v6(&socket), socket.port()),
+        }
+    }
+}
+
+/// An inclusive range of ports, such as `1000-2000` or a single `443`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PortRange {
+    start: u16,
+    end: u16,
+}
+
+impl PortRange {
+    /// Create a new port range
+    pub fn new(start: u16, end: u16) -> AddrResult<Self> {
+        if start > end {
+            return Err(AddrFormatError::new(format!(
+                "Invalid port range {}-{}: start is after end",
+                start, end
+            )));
+        }
+        Ok(Self { start, end })
+    }
+
+    /// A range holding a single port
+    pub fn single(port: u16) -> Self {
+        Self { start: port, end: port }
+    }
+
+    /// A range holding every port
+    pub fn all() -> Self {
+        Self { start: 0, end: u16::MAX }
+    }
+
+    /// Get the first port
+    pub fn start(&self) -> u16 {
+        self.start
+    }
+
+    /// Get the last port
+    pub fn end(&self) -> u16 {
+        self.end
+    }
+
+    /// Get the number of ports in the range
+    pub fn len(&self) -> u32 {
+        (self.end - self.start) as u32 + 1
+    }
+
+    /// Port ranges are never empty
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Check if a port is in the range
+    pub fn contains(&self, port: u16) -> bool {
+        self.start <= port && port <= self.end
+    }
+
+    /// Iterate over the ports in the range
+    pub fn iter(&self) -> RangeInclusive<u16> {
+        self.start..=self.end
+    }
+}
+
+impl FromStr for PortRange {
+    type Err = AddrFormatError;
+
+    fn from_str(s: &str) -> AddrResult<Self> {
+        match s.split_once('-') {
+            Some((start, end)) => Self::new(parse_port(start)?, parse_port(end)?),
+            None => Ok(Self::single(parse_port(s)?)),
+        }
+    }
+}
+
+impl fmt::Display for PortRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.start == self.end {
+            write!(f, "{}", self.start)
+        } else {
+            write!(f, "{}-{}", self.start, self.end)
+        }
+    }
+}
+
+impl From<u16> for PortRange {
+    fn from(port: u16) -> Self {
+        Self::single(port)
+    }
+}
+
+/// A network and port range, such as `10.0.0.0/24:1000-2000`
+///
+/// A bare address is read as a single-host network, so `10.0.0.1:80` and
+/// `[2001:db8::/32]:443` are also valid. The addresses can be turned into an
+/// `IPSet` with `IPSet::from_network(range.network().clone())`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct IPEndpointRange {
+    network: IPNetwork,
+    ports: PortRange,
+}
+
+impl IPEndpointRange {
+    /// Create a new endpoint range
+    pub fn new(network: IPNetwork, ports: PortRange) -> Self {
+        Self { network, ports }
+    }
+
+    /// Get the network
+    pub fn network(&self) -> &IPNetwork {
+        &self.network
+    }
+
+    /// Get the port range
+    pub fn ports(&self) -> PortRange {
+        self.ports
+    }
+
+    /// Check if an endpoint falls within the network and port range
+    pub fn contains(&self, endpoint: &IPEndpoint) -> bool {
+        self.ports.contains(endpoint.port) && self.network.contains(&endpoint.address)
+    }
+}
+
+impl FromStr for IPEndpointRange {
+    type Err = AddrFormatError;
+
+    fn from_str(s: &str) -> AddrResult<Self> {
+        let (host, ports) = split_host_port(s)?;
+        let network = if host.contains('/') {
+            IPNetwork::from_str(host)?
+        } else {
+            let address = IPAddress::from_str(host)?;
+            let prefix_length = if address.is_ipv4() { 32 } else { 128 };
+            IPNetwork::new(address, prefix_length)?
+        };
+        Ok(Self::new(network, PortRange::from_str(ports)?))
+    }
+}
+
+impl fmt::Display for IPEndpointRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.network.is_ipv6() {
+            write!(f, "[{}]:{}", self.network, self.ports)
+        } else {
+            write!(f, "{}:{}", self.network, self.ports)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_parsing() {
+        for s in ["1.2.3.4:443", "[2001:db8::1]:8443", "[fe80::1%eth0]:22"] {
+            assert_eq!(IPEndpoint::from_str(s).unwrap().to_string(), s);
+        }
+
+        let endpoint = IPEndpoint::from_str("[fe80::1%eth0]:22").unwrap();
+        assert_eq!(endpoint.address().zone(), Some("eth0"));
+        assert_eq!(endpoint.port(), 22);
+
+        for s in ["1.2.3.4", "2001:db8::1:80", "[1.2.3.4]:80", "1.2.3.4:65536", "1.2.3.4:+80", "[::1]80", "1.2.3.4:"] {
+            assert!(IPEndpoint::from_str(s).is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_socket_addr_conversion() {
+        let endpoint = IPEndpoint::from_str("[fe80::1%3]:22").unwrap();
+        let socket = endpoint.to_socket_addr().unwrap();
+        assert_eq!(socket.to_string(), "[fe80::1%3]:22");
+        assert_eq!(IPEndpoint::from(socket), endpoint);
+
+        let socket: SocketAddr = "192.0.2.1:443".parse().unwrap();
+        assert_eq!(IPEndpoint::from(socket).to_socket_addr().unwrap(), socket);
+
+        assert!(IPEndpoint::from_str("[fe80::1%eth0]:22").unwrap().to_socket_addr().is_err());
+    }
+
+    #[test]
+    fn test_endpoint_range() {
+        let range = IPEndpointRange::from_str("10.0.0.0/24:1000-2000").unwrap();
+        assert_eq!(range.to_string(), "10.0.0.0/24:1000-2000");
+        assert_eq!(range.ports().len(), 1001);
+        assert!(range.contains(&IPEndpoint::from_str("10.0.0.7:1500").unwrap()));
+        assert!(!range.contains(&IPEndpoint::from_str("10.0.0.7:2001").unwrap()));
+        assert!(!range.contains(&IPEndpoint::from_str("10.0.1.7:1500").unwrap()));
+
+        let range = IPEndpointRange::from_str("[2001:db8::/32]:443").unwrap();
+        assert_eq!(range.to_string(), "[2001:db8::/32]:443");
+        assert!(range.contains(&IPEndpoint::from_str("[2001:db8::1]:443").unwrap()));
+
+        let host = IPEndpointRange::from_str("10.0.0.1:80").unwrap();
+        assert_eq!(host.to_string(), "10.0.0.1/32:80");
+
+        assert!(IPEndpointRange::from_str("10.0.0.0/24:2000-1000").is_err());
+        assert!(IPEndpointRange::from_str("2001:db8::/32:443").is_err());
+    }
+}
@@ -0,0 +1,161 @@
+//! IP interface - a host address together with the network it is configured on
+
+use crate::error::{AddrFormatError, AddrResult};
+use crate::ip::network::{parse_mask, split_network_str};
+use crate::ip::{IPAddress, IPNetwork};
+use std::fmt;
+use std::str::FromStr;
+
+/// A host address with a prefix length, such as `192.168.1.10/24`
+///
+/// Unlike `IPNetwork`, which clears host bits, an interface keeps the
+/// configured address, like the `ip` attribute of Python netaddr's
+/// `IPNetwork`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct IPInterface {
+    ip: IPAddress,
+    prefix_length: u8,
+}
+
+impl IPInterface {
+    /// Create a new interface
+    pub fn new(ip: IPAddress, prefix_length: u8) -> AddrResult<Self> {
+        let max_prefix = if ip.is_ipv4() { 32 } else { 128 };
+        if prefix_length > max_prefix {
+            return Err(AddrFormatError::new(format!(
+                "Invalid prefix length {} for {}",
+                prefix_length, ip
+            )));
+        }
+        Ok(Self { ip, prefix_length })
+    }
+
+    /// Get the configured host address
+    pub fn ip(&self) -> &IPAddress {
+        &self.ip
+    }
+
+    /// Get the prefix length
+    pub fn prefix_length(&self) -> u8 {
+        self.prefix_length
+    }
+
+    /// Get the IP version (4 or 6)
+    pub fn version(&self) -> u8 {
+        self.ip.version()
+    }
+
+    /// Get the network address (the host address with host bits cleared)
+    pub fn network(&self) -> IPAddress {
+        self.cidr().network_address().clone()
+    }
+
+    /// Get the network the interface is on
+    pub fn cidr(&self) -> IPNetwork {
+        IPNetwork::new(self.ip.clone(), self.prefix_length)
+            .expect("prefix length was validated on construction")
+    }
+
+    /// Get the subnet mask
+    pub fn netmask(&self) -> IPAddress {
+        self.cidr().netmask().expect("netmask is always defined")
+    }
+
+    /// Get the host mask
+    pub fn hostmask(&self) -> IPAddress {
+        !self.netmask()
+    }
+
+    /// Check if an address is on the same network as this interface
+    pub fn contains(&self, addr: &IPAddress) -> bool {
+        self.cidr().contains(addr)
+    }
+
+    /// Format as `address/netmask`, e.g. `192.168.1.10/255.255.255.0`
+    pub fn with_netmask(&self) -> String {
+        format!("{}/{}", self.ip, self.netmask())
+    }
+
+    /// Format as `address/hostmask`, e.g. `192.168.1.10/0.0.0.255`
+    pub fn with_hostmask(&self) -> String {
+        format!("{}/{}", self.ip, self.hostmask())
+    }
+
+    /// Format as `address/prefix`, e.g. `192.168.1.10/24`
+    pub fn with_prefixlen(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl FromStr for IPInterface {
+    type Err = AddrFormatError;
+
+    /// Parse `address/prefix` or any other mask notation accepted by
+    /// `IPNetwork`; a bare address is a single-host interface
+    fn from_str(s: &str) -> AddrResult<Self> {
+        let (addr, mask) = split_network_str(s)?;
+
+        let ip = IPAddress::from_str(addr)?;
+        let prefix_length = match mask {
+            Some((mask, notation)) => parse_mask(&ip, mask, notation)?,
+            None if ip.is_ipv4() => 32,
+            None => 128,
+        };
+        IPInterface::new(ip, prefix_length)
+    }
+}
+
+impl fmt::Display for IPInterface {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.ip, self.prefix_length)
+    }
+}
+
+impl From<IPNetwork> for IPInterface {
+    fn from(network: IPNetwork) -> Self {
+        Self {
+            prefix_length: network.prefix_length(),
+            ip: network.network_address().clone(),
+        }
+    }
+}
+
+impl From<IPInterface> for IPNetwork {
+    fn from(interface: IPInterface) -> Self {
+        interface.cidr()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interface_keeps_host() {
+        let iface = IPInterface::from_str("192.168.1.10/24").unwrap();
+        assert_eq!(iface.ip().to_string(), "192.168.1.10");
+        assert_eq!(iface.network().to_string(), "192.168.1.0");
+        assert_eq!(iface.cidr().to_string(), "192.168.1.0/24");
+        assert_eq!(iface.to_string(), "192.168.1.10/24");
+        assert_eq!(iface.with_netmask(), "192.168.1.10/255.255.255.0");
+        assert_eq!(IPInterface::from_str("192.168.1.10 255.255.255.0").unwrap(), iface);
+        assert_eq!(iface.with_hostmask(), "192.168.1.10/0.0.0.255");
+        assert!(iface.contains(&IPAddress::from_str("192.168.1.200").unwrap()));
+        assert_ne!(iface, IPInterface::from_str("192.168.1.11/24").unwrap());
+    }
+
+    #[test]
+    fn test_ipv6_interface() {
+        let iface = IPInterface::from_str("2001:db8::1/64").unwrap();
+        assert_eq!(iface.cidr().to_string(), "2001:db8::/64");
+        assert_eq!(iface.with_netmask(), "2001:db8::1/ffff:ffff:ffff:ffff::");
+        assert_eq!(iface.with_hostmask(), "2001:db8::1/::ffff:ffff:ffff:ffff");
+
+        let zoned = IPInterface::from_str("fe80::1%eth0/64").unwrap();
+        assert_eq!(zoned.to_string(), "fe80::1%eth0/64");
+        assert_eq!(zoned.cidr().to_string(), "fe80::%eth0/64");
+
+        assert_eq!(IPInterface::from_str("10.0.0.1").unwrap().prefix_length(), 32);
+        assert!(IPInterface::from_str("10.0.0.1/33").is_err());
+    }
+}
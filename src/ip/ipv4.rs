@@ -3,6 +3,7 @@
 use crate::error::{AddrFormatError, AddrResult};
 use std::fmt;
 use std::net::Ipv4Addr;
+use std::ops::{Add, BitAnd, BitOr, Not, Shl, Shr, Sub};
 use std::str::FromStr;
 
 /// IPv4 address with extended functionality
@@ -135,6 +136,36 @@ impl IPv4 {
         IPv4::from_u32(self.to_u32() | inverted_mask)
     }
 
+    /// Add an offset, returning `None` if the result is past 255.255.255.255
+    pub fn checked_add(&self, offset: u128) -> Option<IPv4> {
+        let offset = u32::try_from(offset).ok()?;
+        self.to_u32().checked_add(offset).map(IPv4::from_u32)
+    }
+
+    /// Subtract an offset, returning `None` if the result is before 0.0.0.0
+    pub fn checked_sub(&self, offset: u128) -> Option<IPv4> {
+        let offset = u32::try_from(offset).ok()?;
+        self.to_u32().checked_sub(offset).map(IPv4::from_u32)
+    }
+
+    /// Add an offset, wrapping around at the end of the address space
+    pub fn wrapping_add(&self, offset: u128) -> IPv4 {
+        IPv4::from_u32(self.to_u32().wrapping_add(offset as u32))
+    }
+
+    /// Subtract an offset, wrapping around at the start of the address space
+    pub fn wrapping_sub(&self, offset: u128) -> IPv4 {
+        IPv4::from_u32(self.to_u32().wrapping_sub(offset as u32))
+    }
+
+    /// Get the number of addresses between this address and another
+    ///
+    /// Like [`IPAddress::distance`](crate::ip::IPAddress::distance), the
+    /// distance is unsigned and the same whichever address comes first.
+    pub fn distance(&self, other: &IPv4) -> u128 {
+        self.to_u32().abs_diff(other.to_u32()) as u128
+    }
+
     /// Parse with zero-fill handling for inet_aton compatibility
     pub fn parse_with_zerofill(s: &str) -> AddrResult<Self> {
         // Handle zero-filled octets like "010.020.030.040"
@@ -219,6 +250,88 @@ impl From<u32> for IPv4 {
     }
 }
 
+impl Add<u128> for IPv4 {
+    type Output = IPv4;
+
+    fn add(self, offset: u128) -> IPv4 {
+        self.checked_add(offset).expect("IPv4 address overflow")
+    }
+}
+
+impl Sub<u128> for IPv4 {
+    type Output = IPv4;
+
+    fn sub(self, offset: u128) -> IPv4 {
+        self.checked_sub(offset).expect("IPv4 address underflow")
+    }
+}
+
+impl BitAnd for IPv4 {
+    type Output = IPv4;
+
+    fn bitand(self, mask: IPv4) -> IPv4 {
+        self & mask.to_u32()
+    }
+}
+
+impl BitAnd<u32> for IPv4 {
+    type Output = IPv4;
+
+    fn bitand(self, mask: u32) -> IPv4 {
+        IPv4::from_u32(self.to_u32() & mask)
+    }
+}
+
+impl BitOr for IPv4 {
+    type Output = IPv4;
+
+    fn bitor(self, mask: IPv4) -> IPv4 {
+        self | mask.to_u32()
+    }
+}
+
+impl BitOr<u32> for IPv4 {
+    type Output = IPv4;
+
+    fn bitor(self, mask: u32) -> IPv4 {
+        IPv4::from_u32(self.to_u32() | mask)
+    }
+}
+
+impl Not for IPv4 {
+    type Output = IPv4;
+
+    fn not(self) -> IPv4 {
+        IPv4::from_u32(!self.to_u32())
+    }
+}
+
+impl Shl<u32> for IPv4 {
+    type Output = IPv4;
+
+    fn shl(self, bits: u32) -> IPv4 {
+        IPv4::from_u32(self.to_u32().checked_shl(bits).unwrap_or(0))
+    }
+}
+
+impl Shr<u32> for IPv4 {
+    type Output = IPv4;
+
+    fn shr(self, bits: u32) -> IPv4 {
+        IPv4::from_u32(self.to_u32().checked_shr(bits).unwrap_or(0))
+    }
+}
+
+forward_ref_op!(impl Add<u128>, add for IPv4);
+forward_ref_op!(impl Sub<u128>, sub for IPv4);
+forward_ref_op!(impl BitAnd<IPv4>, bitand for IPv4);
+forward_ref_op!(impl BitAnd<u32>, bitand for IPv4);
+forward_ref_op!(impl BitOr<IPv4>, bitor for IPv4);
+forward_ref_op!(impl BitOr<u32>, bitor for IPv4);
+forward_ref_op!(impl Not, not for IPv4);
+forward_ref_op!(impl Shl<u32>, shl for IPv4);
+forward_ref_op!(impl Shr<u32>, shr for IPv4);
+
 /// Subnet mask utilities
 pub struct SubnetMask;
 
@@ -248,8 +361,8 @@ impl SubnetMask {
         let mask_u32 = mask.to_u32();
         let inverted = !mask_u32;
 
-        // Check if inverted mask + 1 is a power of 2 (or 0)
-        inverted == 0 || (inverted & (inverted + 1)) == 0
+        // Check if inverted mask + 1 is a power of 2 (or overflows, for 0.0.0.0)
+        inverted.checked_add(1).is_none_or(|next| inverted & next == 0)
     }
 }
 
@@ -323,6 +436,27 @@ mod tests {
         assert_eq!(addr, IPv4::new(10, 0, 0, 0));
     }
 
+    #[test]
+    fn test_operators() {
+        let addr = IPv4::new(192, 168, 1, 0);
+        assert_eq!(&addr + 42, IPv4::new(192, 168, 1, 42));
+        assert_eq!(addr.clone() - 1, IPv4::new(192, 168, 0, 255));
+        assert_eq!(addr.distance(&IPv4::new(192, 168, 2, 1)), 257);
+
+        let last = IPv4::new(255, 255, 255, 255);
+        assert_eq!(last.checked_add(1), None);
+        assert_eq!(last.wrapping_add(2), IPv4::new(0, 0, 0, 1));
+        assert_eq!(IPv4::new(0, 0, 0, 0).checked_sub(1), None);
+        assert_eq!(addr.checked_add(1 << 32), None);
+
+        let mask = IPv4::new(255, 255, 255, 0);
+        assert_eq!(IPv4::new(10, 1, 2, 3) & mask.clone(), IPv4::new(10, 1, 2, 0));
+        assert_eq!(IPv4::new(10, 1, 2, 3) | !mask, IPv4::new(10, 1, 2, 255));
+        assert_eq!(IPv4::new(0, 0, 1, 1) << 8, IPv4::new(0, 1, 1, 0));
+        assert_eq!(IPv4::new(1, 0, 0, 0) >> 24, IPv4::new(0, 0, 0, 1));
+        assert_eq!(IPv4::new(1, 2, 3, 4) << 32, IPv4::new(0, 0, 0, 0));
+    }
+
     #[test]
     fn test_zerofill_parsing() {
         let addr = IPv4::parse_with_zerofill("010.020.030.040").unwrap();
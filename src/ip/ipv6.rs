@@ -3,6 +3,7 @@
 use crate::error::{AddrFormatError, AddrResult};
 use std::fmt;
 use std::net::Ipv6Addr;
+use std::ops::{Add, BitAnd, BitOr, Not, Shl, Shr, Sub};
 use std::str::FromStr;
 
 /// IPv6 address with extended functionality
@@ -98,6 +99,8 @@ impl IPv6 {
     }
 
     /// Convert to IPv4 if this is an IPv4-mapped address
+    ///
+    /// For addresses synthesized by NAT64/DNS64, see [`crate::ip::Nat64Prefix`].
     pub fn to_ipv4(&self) -> Option<crate::ip::ipv4::IPv4> {
         self.addr.to_ipv4().map(|ipv4| crate::ip::ipv4::IPv4::from(ipv4))
     }
@@ -126,6 +129,47 @@ impl IPv6 {
         segments[0] == 0x2001 && segments[1] == 0x0000
     }
 
+    /// Get the IPv4 address of the site if this is a 6to4 address
+    pub fn six_to_four_ipv4(&self) -> Option<crate::ip::ipv4::IPv4> {
+        if !self.is_6to4() {
+            return None;
+        }
+        Some(crate::ip::ipv4::IPv4::from_u32((self.network_prefix() >> 16) as u32))
+    }
+
+    /// Create a 6to4 address from the site's IPv4 address, subnet ID and interface ID
+    pub fn from_six_to_four(ipv4: &crate::ip::ipv4::IPv4, subnet_id: u16, interface_id: u64) -> Self {
+        let network_prefix = (0x2002u64 << 48) | ((ipv4.to_u32() as u64) << 16) | subnet_id as u64;
+        Self::from_parts(network_prefix, interface_id)
+    }
+
+    /// Decode the server, client and flags if this is a Teredo address
+    pub fn teredo(&self) -> Option<crate::ip::transition::TeredoAddress> {
+        crate::ip::transition::TeredoAddress::decode(self)
+    }
+
+    /// Check if the interface ID is an ISATAP one (`0:5efe::/32` or `200:5efe::/32`)
+    pub fn is_isatap(&self) -> bool {
+        let segments = self.segments();
+        segments[4] & !0x0200 == 0 && segments[5] == 0x5efe
+    }
+
+    /// Get the IPv4 address in the interface ID if this is an ISATAP address
+    pub fn isatap_ipv4(&self) -> Option<crate::ip::ipv4::IPv4> {
+        if !self.is_isatap() {
+            return None;
+        }
+        Some(crate::ip::ipv4::IPv4::from_u32(self.interface_id() as u32))
+    }
+
+    /// Create an ISATAP address from a network prefix and an IPv4 address
+    ///
+    /// `universal` sets the universal/local bit, for globally unique IPv4 addresses.
+    pub fn from_isatap(network_prefix: u64, ipv4: &crate::ip::ipv4::IPv4, universal: bool) -> Self {
+        let marker: u64 = if universal { 0x0200_5efe } else { 0x5efe };
+        Self::from_parts(network_prefix, (marker << 32) | ipv4.to_u32() as u64)
+    }
+
     /// Get the interface identifier (last 64 bits)
     pub fn interface_id(&self) -> u64 {
         let segments = self.segments();
@@ -191,6 +235,34 @@ impl IPv6 {
         Self::from_u128(network_u128)
     }
 
+    /// Add an offset, returning `None` if the result is past the end of the address space
+    pub fn checked_add(&self, offset: u128) -> Option<IPv6> {
+        self.to_u128().checked_add(offset).map(IPv6::from_u128)
+    }
+
+    /// Subtract an offset, returning `None` if the result is before `::`
+    pub fn checked_sub(&self, offset: u128) -> Option<IPv6> {
+        self.to_u128().checked_sub(offset).map(IPv6::from_u128)
+    }
+
+    /// Add an offset, wrapping around at the end of the address space
+    pub fn wrapping_add(&self, offset: u128) -> IPv6 {
+        IPv6::from_u128(self.to_u128().wrapping_add(offset))
+    }
+
+    /// Subtract an offset, wrapping around at the start of the address space
+    pub fn wrapping_sub(&self, offset: u128) -> IPv6 {
+        IPv6::from_u128(self.to_u128().wrapping_sub(offset))
+    }
+
+    /// Get the number of addresses between this address and another
+    ///
+    /// Like [`IPAddress::distance`](crate::ip::IPAddress::distance), the
+    /// distance is unsigned and the same whichever address comes first.
+    pub fn distance(&self, other: &IPv6) -> u128 {
+        self.to_u128().abs_diff(other.to_u128())
+    }
+
     /// Check if this is a solicited-node multicast address
     pub fn is_solicited_node_multicast(&self) -> bool {
         let segments = self.segments();
@@ -249,6 +321,88 @@ impl From<u128> for IPv6 {
     }
 }
 
+impl Add<u128> for IPv6 {
+    type Output = IPv6;
+
+    fn add(self, offset: u128) -> IPv6 {
+        self.checked_add(offset).expect("IPv6 address overflow")
+    }
+}
+
+impl Sub<u128> for IPv6 {
+    type Output = IPv6;
+
+    fn sub(self, offset: u128) -> IPv6 {
+        self.checked_sub(offset).expect("IPv6 address underflow")
+    }
+}
+
+impl BitAnd for IPv6 {
+    type Output = IPv6;
+
+    fn bitand(self, mask: IPv6) -> IPv6 {
+        self & mask.to_u128()
+    }
+}
+
+impl BitAnd<u128> for IPv6 {
+    type Output = IPv6;
+
+    fn bitand(self, mask: u128) -> IPv6 {
+        IPv6::from_u128(self.to_u128() & mask)
+    }
+}
+
+impl BitOr for IPv6 {
+    type Output = IPv6;
+
+    fn bitor(self, mask: IPv6) -> IPv6 {
+        self | mask.to_u128()
+    }
+}
+
+impl BitOr<u128> for IPv6 {
+    type Output = IPv6;
+
+    fn bitor(self, mask: u128) -> IPv6 {
+        IPv6::from_u128(self.to_u128() | mask)
+    }
+}
+
+impl Not for IPv6 {
+    type Output = IPv6;
+
+    fn not(self) -> IPv6 {
+        IPv6::from_u128(!self.to_u128())
+    }
+}
+
+impl Shl<u32> for IPv6 {
+    type Output = IPv6;
+
+    fn shl(self, bits: u32) -> IPv6 {
+        IPv6::from_u128(self.to_u128().checked_shl(bits).unwrap_or(0))
+    }
+}
+
+impl Shr<u32> for IPv6 {
+    type Output = IPv6;
+
+    fn shr(self, bits: u32) -> IPv6 {
+        IPv6::from_u128(self.to_u128().checked_shr(bits).unwrap_or(0))
+    }
+}
+
+forward_ref_op!(impl Add<u128>, add for IPv6);
+forward_ref_op!(impl Sub<u128>, sub for IPv6);
+forward_ref_op!(impl BitAnd<IPv6>, bitand for IPv6);
+forward_ref_op!(impl BitAnd<u128>, bitand for IPv6);
+forward_ref_op!(impl BitOr<IPv6>, bitor for IPv6);
+forward_ref_op!(impl BitOr<u128>, bitor for IPv6);
+forward_ref_op!(impl Not, not for IPv6);
+forward_ref_op!(impl Shl<u32>, shl for IPv6);
+forward_ref_op!(impl Shr<u32>, shr for IPv6);
+
 /// RFC 1924 Base85 encoding for IPv6 addresses
 pub struct Base85;
 
@@ -320,6 +474,12 @@ impl IPv6SubnetMask {
         let mask_u128 = mask.to_u128();
         mask_u128.count_ones() as u8
     }
+
+    /// Check if a mask is valid (contiguous 1s followed by contiguous 0s)
+    pub fn is_valid_mask(mask: &IPv6) -> bool {
+        let inverted = !mask.to_u128();
+        inverted.checked_add(1).is_none_or(|next| inverted & next == 0)
+    }
 }
 
 #[cfg(test)]
@@ -395,6 +555,10 @@ mod tests {
         let mask = IPv6SubnetMask::from_prefix_length(64).unwrap();
         let prefix_len = IPv6SubnetMask::to_prefix_length(&mask);
         assert_eq!(prefix_len, 64);
+
+        assert!(IPv6SubnetMask::is_valid_mask(&mask));
+        assert!(IPv6SubnetMask::is_valid_mask(&IPv6::from_u128(0)));
+        assert!(!IPv6SubnetMask::is_valid_mask(&IPv6::from_str("ffff::ffff").unwrap()));
     }
 
     #[test]
@@ -405,4 +569,25 @@ mod tests {
         let ipv4 = mapped.to_ipv4().unwrap();
         assert_eq!(ipv4.to_string(), "192.168.1.1");
     }
+
+    #[test]
+    fn test_operators() {
+        let net = IPv6::from_str("2001:db8::").unwrap();
+        assert_eq!((&net + 0x2a).to_string(), "2001:db8::2a");
+        assert_eq!((net.clone() - 1).to_string(), "2001:db7:ffff:ffff:ffff:ffff:ffff:ffff");
+        assert_eq!(net.distance(&IPv6::from_str("2001:db8::1:0").unwrap()), 0x10000);
+
+        let last = IPv6::from_u128(u128::MAX);
+        assert_eq!(last.checked_add(1), None);
+        assert_eq!(last.wrapping_add(1), IPv6::from_u128(0));
+        assert_eq!(IPv6::from_u128(0).checked_sub(1), None);
+
+        let mask = IPv6SubnetMask::from_prefix_length(32).unwrap();
+        let addr = IPv6::from_str("2001:db8:1:2::3").unwrap();
+        assert_eq!(&addr & mask.clone(), net);
+        assert_eq!((addr | !mask).to_string(), "2001:db8:ffff:ffff:ffff:ffff:ffff:ffff");
+        assert_eq!((IPv6::from_u128(1) << 112).to_string(), "1::");
+        assert_eq!(IPv6::from_u128(1) << 128, IPv6::from_u128(0));
+        assert_eq!(IPv6::from_str("1::").unwrap() >> 112, IPv6::from_u128(1));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,325 @@
+//! Addresses matched by an arbitrary bitmask
+//!
+//! A CIDR prefix fixes the leading bits of an address. Cisco ACL wildcards and
+//! SDN flow rules may fix any bits, so `10.0.0.1 0.255.0.0` matches every
+//! address `10.x.0.1`. A `MaskedAddress` holds such a value/mask pair and
+//! expands it exactly into CIDR blocks, ranges or an `IPSet`.
+
+use crate::error::{AddrFormatError, AddrResult};
+use crate::ip::{AddressCount, IPAddress, IPNetwork, IPRange};
+use crate::sets::{IPSet, IntoIPSet};
+use std::fmt;
+use std::str::FromStr;
+
+/// The most CIDR blocks collected into a set by `MaskedAddress::to_ip_set`
+const MAX_SET_CIDRS: u128 = 1 << 16;
+
+/// An address pattern with an arbitrary (possibly non-contiguous) mask
+///
+/// Bits set in the mask must match the address; bits clear in the mask may
+/// take any value. A Cisco wildcard is the inverse of this mask.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct MaskedAddress {
+    address: IPAddress,
+    mask: IPAddress,
+}
+
+impl MaskedAddress {
+    /// Create a masked address from an address and the mask of bits that must match
+    ///
+    /// Bits of the address outside the mask are cleared and any zone is dropped.
+    pub fn new(address: IPAddress, mask: IPAddress) -> AddrResult<Self> {
+        if address.ip_type() != mask.ip_type() {
+            return Err(AddrFormatError::new(format!(
+                "Mask {} does not match the address family of {}",
+                mask, address
+            )));
+        }
+        let address = address.without_zone();
+        let mask = mask.without_zone();
+        Ok(Self {
+            address: address.with_value(address.to_u128() & mask.to_u128()),
+            mask,
+        })
+    }
+
+    /// Create a masked address from an address and a Cisco wildcard (bits that may vary)
+    pub fn from_wildcard(address: IPAddress, wildcard: IPAddress) -> AddrResult<Self> {
+        let mask = !&wildcard;
+        Self::new(address, mask)
+    }
+
+    /// Get the address, with the bits outside the mask cleared
+    pub fn address(&self) -> &IPAddress {
+        &self.address
+    }
+
+    /// Get the mask of bits that must match
+    pub fn mask(&self) -> &IPAddress {
+        &self.mask
+    }
+
+    /// Get the Cisco wildcard (the inverse of the mask)
+    pub fn wildcard(&self) -> IPAddress {
+        !&self.mask
+    }
+
+    /// Get the IP version (4 or 6)
+    pub fn version(&self) -> u8 {
+        self.address.version()
+    }
+
+    /// Check if the mask is contiguous, so that the pattern is a single CIDR block
+    pub fn is_contiguous(&self) -> bool {
+        self.to_network().is_some()
+    }
+
+    /// Get the pattern as a CIDR block, if the mask is contiguous
+    pub fn to_network(&self) -> Option<IPNetwork> {
+        let (width, free) = self.free_bits();
+        let host_bits = free.trailing_ones();
+        if free.checked_shr(host_bits).unwrap_or(0) != 0 {
+            return None;
+        }
+        Some(IPNetwork::new_unchecked(self.address.clone(), (width - host_bits) as u8))
+    }
+
+    /// Check if an address matches the pattern
+    pub fn contains(&self, addr: &IPAddress) -> bool {
+        addr.ip_type() == self.address.ip_type()
+            && addr.to_u128() & self.mask.to_u128() == self.address.to_u128()
+    }
+
+    /// Check if any address matches both patterns
+    pub fn overlaps(&self, other: &MaskedAddress) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// Get the pattern matching the addresses that match both patterns
+    pub fn intersection(&self, other: &MaskedAddress) -> Option<MaskedAddress> {
+        if self.address.ip_type() != other.address.ip_type() {
+            return None;
+        }
+        let common = self.mask.to_u128() & other.mask.to_u128();
+        if (self.address.to_u128() ^ other.address.to_u128()) & common != 0 {
+            return None;
+        }
+        Some(Self {
+            address: self.address.with_value(self.address.to_u128() | other.address.to_u128()),
+            mask: self.mask.with_value(self.mask.to_u128() | other.mask.to_u128()),
+        })
+    }
+
+    /// Get the number of matching addresses
+    pub fn size(&self) -> AddressCount {
+        AddressCount::power_of_two(self.free_bits().1.count_ones())
+    }
+
+    /// Get the number of CIDR blocks in the minimal covering of the pattern
+    pub fn cidr_count(&self) -> AddressCount {
+        let (_, free) = self.free_bits();
+        AddressCount::power_of_two(free.count_ones() - free.trailing_ones())
+    }
+
+    /// Iterate over the minimal list of CIDR blocks matching the pattern, in address order
+    ///
+    /// The trailing wildcard bits become the host bits of each block and every
+    /// combination of the remaining wildcard bits gives one block. No covering
+    /// can use fewer blocks, as no block can be larger than the trailing run.
+    pub fn iter_cidrs(&self) -> MaskedCidrIterator {
+        let (width, free) = self.free_bits();
+        let host_bits = free.trailing_ones();
+        let host_mask = u128::MAX.checked_shr(128 - host_bits).unwrap_or(0);
+        MaskedCidrIterator {
+            base: self.address.clone(),
+            varying: free & !host_mask,
+            current: Some(0),
+            prefix_length: (width - host_bits) as u8,
+        }
+    }
+
+    /// Get the minimal list of CIDR blocks matching the pattern
+    ///
+    /// The list has `cidr_count()` entries, which for sparse IPv6 masks can be
+    /// far too many to collect; use `iter_cidrs()` to walk them lazily.
+    pub fn to_cidrs(&self) -> Vec<IPNetwork> {
+        self.iter_cidrs().collect()
+    }
+
+    /// Get the minimal list of address ranges matching the pattern
+    ///
+    /// Consecutive blocks are never adjacent, since the bit above the host
+    /// bits of each block is fixed by the mask, so each block is its own range.
+    pub fn to_ranges(&self) -> Vec<IPRange> {
+        self.iter_cidrs().map(IPRange::from).collect()
+    }
+
+    /// Get the matching addresses as a set
+    ///
+    /// Masks matching more than 2^16 separate blocks are rejected, as the set
+    /// holds every block; use `iter_cidrs` to walk those lazily.
+    pub fn to_ip_set(&self) -> AddrResult<IPSet> {
+        if self.cidr_count() > MAX_SET_CIDRS {
+            return Err(AddrFormatError::new(format!(
+                "{} matches {} separate blocks, too many for a set; iterate them with iter_cidrs",
+                self,
+                self.cidr_count()
+            )));
+        }
+        Ok(self.iter_cidrs().collect())
+    }
+
+    /// Get the address width and the bits that may vary
+    fn free_bits(&self) -> (u32, u128) {
+        let width = if self.address.is_ipv4() { 32 } else { 128 };
+        (width, !self.mask.to_u128() & self.mask.max_value())
+    }
+}
+
+impl FromStr for MaskedAddress {
+    type Err = AddrFormatError;
+
+    /// Parse `addr/mask` (bits that must match) or `addr wildcard` (Cisco
+    /// wildcard, bits that may vary); a prefix length is also accepted after `/`
+    fn from_str(s: &str) -> AddrResult<Self> {
+        if let Some((addr, mask)) = s.split_once('/') {
+            let address = IPAddress::from_str(addr.trim())?;
+            let mask = mask.trim();
+            if mask.bytes().all(|b| b.is_ascii_digit()) {
+                let network = IPNetwork::from_str(s.trim())?;
+                return Self::new(address, network.netmask()?);
+            }
+            return Self::new(address, IPAddress::from_str(mask)?);
+        }
+
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        match parts.as_slice() {
+            [addr, wildcard] => Self::from_wildcard(IPAddress::from_str(addr)?, IPAddress::from_str(wildcard)?),
+            _ => Err(AddrFormatError::new(format!(
+                "Invalid masked address '{}': expected 'addr/mask' or 'addr wildcard'",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for MaskedAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.address, self.mask)
+    }
+}
+
+impl From<IPNetwork> for MaskedAddress {
+    fn from(network: IPNetwork) -> Self {
+        let mask = network.netmask().expect("netmask is always defined");
+        Self::new(network.network_address().clone(), mask).expect("netmask has the network's family")
+    }
+}
+
+impl IntoIPSet for MaskedAddress {
+    fn into_ip_set(self) -> AddrResult<IPSet> {
+        self.to_ip_set()
+    }
+}
+
+/// Iterator over the CIDR blocks matching a masked address, in address order
+pub struct MaskedCidrIterator {
+    base: IPAddress,
+    varying: u128,
+    current: Option<u128>,
+    prefix_length: u8,
+}
+
+impl Iterator for MaskedCidrIterator {
+    type Item = IPNetwork;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.current?;
+
+        // Step to the next subset of the varying bits in ascending order by
+        // filling the other bits with ones so the carry skips over them
+        let next = (current | !self.varying).wrapping_add(1) & self.varying;
+        self.current = if next == 0 { None } else { Some(next) };
+
+        let address = self.base.with_value(self.base.to_u128() | current);
+        Some(IPNetwork::new_unchecked(address, self.prefix_length))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wildcard_expansion() {
+        let masked = MaskedAddress::from_str("10.0.0.1 0.255.0.0").unwrap();
+        assert_eq!(masked.to_string(), "10.0.0.1/255.0.255.255");
+        assert_eq!(masked.wildcard().to_string(), "0.255.0.0");
+        assert!(!masked.is_contiguous());
+        assert_eq!(masked.size(), 256);
+        assert!(masked.contains(&IPAddress::from_str("10.42.0.1").unwrap()));
+        assert!(!masked.contains(&IPAddress::from_str("10.42.0.2").unwrap()));
+
+        let cidrs = masked.to_cidrs();
+        assert_eq!(masked.cidr_count(), 256);
+        assert_eq!(cidrs.len(), 256);
+        assert_eq!(cidrs[0].to_string(), "10.0.0.1/32");
+        assert_eq!(cidrs[255].to_string(), "10.255.0.1/32");
+
+        let set = masked.to_ip_set().unwrap();
+        assert_eq!(set.size(), 256);
+        assert_eq!(set.range_count(), masked.to_ranges().len());
+    }
+
+    #[test]
+    fn test_trailing_wildcard_bits() {
+        // Every other /24 in 10.0.0.0/16
+        let masked = MaskedAddress::from_str("10.0.0.0/255.255.1.0").unwrap();
+        let cidrs = masked.to_cidrs();
+        assert_eq!(cidrs.len(), 128);
+        assert_eq!(cidrs[1].to_string(), "10.0.2.0/24");
+        assert_eq!(masked.size(), 128 * 256);
+
+        let contiguous = MaskedAddress::from_str("10.1.2.3/16").unwrap();
+        assert_eq!(contiguous.to_network().unwrap().to_string(), "10.1.0.0/16");
+        assert_eq!(contiguous.to_cidrs(), vec![contiguous.to_network().unwrap()]);
+        assert_eq!(MaskedAddress::from(IPNetwork::from_str("10.1.0.0/16").unwrap()), contiguous);
+
+        let any = MaskedAddress::from_str("0.0.0.0 255.255.255.255").unwrap();
+        assert_eq!(any.to_cidrs()[0].to_string(), "0.0.0.0/0");
+    }
+
+    #[test]
+    fn test_ipv6_masks() {
+        // The interface identifier ::1 in every /64 of 2001:db8::/48
+        let masked = MaskedAddress::from_str("2001:db8::1/ffff:ffff:ffff:0:ffff:ffff:ffff:ffff").unwrap();
+        assert_eq!(masked.cidr_count(), 65536);
+        let mut cidrs = masked.iter_cidrs();
+        assert_eq!(cidrs.next().unwrap().to_string(), "2001:db8::1/128");
+        assert_eq!(cidrs.next().unwrap().to_string(), "2001:db8:0:1::1/128");
+
+        // Only the lowest bit is fixed: half of the address space in 2^127 blocks
+        let odd = MaskedAddress::from_str("::1/::1").unwrap();
+        assert_eq!(odd.cidr_count(), AddressCount::power_of_two(127));
+        assert_eq!(odd.iter_cidrs().nth(3).unwrap().to_string(), "::7/128");
+        assert!(odd.to_ip_set().is_err());
+        assert!(odd.into_ip_set().is_err());
+        assert_eq!(masked.to_ip_set().unwrap().size(), 65536);
+
+        let wildcard = MaskedAddress::from_str(":: ::fffe").unwrap();
+        assert_eq!(wildcard.cidr_count(), 2u128.pow(15));
+        assert!(MaskedAddress::from_str("10.0.0.0/ffff::").is_err());
+    }
+
+    #[test]
+    fn test_intersection() {
+        let odd_octet = MaskedAddress::from_str("10.0.0.1 0.255.255.254").unwrap();
+        let subnet = MaskedAddress::from_str("10.1.2.0/24").unwrap();
+        let both = odd_octet.intersection(&subnet).unwrap();
+        assert_eq!(both.size(), 128);
+        assert!(both.contains(&IPAddress::from_str("10.1.2.3").unwrap()));
+
+        let even = MaskedAddress::from_str("10.0.0.0 0.255.255.254").unwrap();
+        assert!(!even.overlaps(&odd_octet));
+    }
+}
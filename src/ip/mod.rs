@@ -1,13 +1,58 @@
 //! IP address functionality module
+//!
+//! `IPAddress`, [`ipv4::IPv4`] and [`ipv6::IPv6`] implement the arithmetic,
+//! bitwise and shift operators. Arithmetic operators panic on overflow, like
+//! the integer operators; use `checked_add`/`wrapping_add` and friends to
+//! handle it explicitly. Masking an `IPAddress` with one of the other family
+//! panics as well. Shifts discard the bits shifted out, so shifting by the
+//! address width or more gives all zeros.
+
+/// Implement an operator on references by cloning into the owned implementation
+macro_rules! forward_ref_op {
+    (impl $imp:ident, $method:ident for $t:ty) => {
+        impl std::ops::$imp for &$t {
+            type Output = $t;
+
+            fn $method(self) -> $t {
+                std::ops::$imp::$method(self.clone())
+            }
+        }
+    };
+    (impl $imp:ident<$rhs:ty>, $method:ident for $t:ty) => {
+        impl std::ops::$imp<$rhs> for &$t {
+            type Output = $t;
+
+            fn $method(self, rhs: $rhs) -> $t {
+                std::ops::$imp::$method(self.clone(), rhs)
+            }
+        }
+    };
+}
 
 pub mod ipv4;
 pub mod ipv6;
 pub mod network;
 pub mod range;
 pub mod address;
+pub mod count;
+pub mod endpoint;
+pub mod interface;
+pub mod masked;
+pub mod nat64;
 pub mod operations;
+pub mod reverse_dns;
+pub mod summarize;
+pub mod transition;
+pub mod zone_file;
 
 pub use address::{IPAddress, IPAddressType};
+pub use count::AddressCount;
+pub use endpoint::{IPEndpoint, IPEndpointRange, PortRange};
+pub use interface::IPInterface;
+pub use masked::MaskedAddress;
+pub use nat64::Nat64Prefix;
 pub use network::IPNetwork;
 pub use range::IPRange;
+pub use reverse_dns::{ClasslessDelegation, ClasslessLabel, ReverseZone};
+pub use transition::{SixRdDomain, TeredoAddress};
 pub use operations::*;
\ No newline at end of file
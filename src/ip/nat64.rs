@@ -0,0 +1,242 @@
+//! IPv4-embedded IPv6 addresses for NAT64 and DNS64 (RFC 6052)
+//!
+//! An IPv4 address is embedded after a prefix of 32, 40, 48, 56, 64 or 96
+//! bits. Bits 64 to 71 (the "u-octet") are reserved and always zero, so the
+//! IPv4 octets skip over byte 8 of the address:
+//!
+//! ```text
+//! /32: | prefix  |  v4 (4)     | u | suffix          |
+//! /40: | prefix     | v4 (3)   | u |v4| suffix       |
+//! /48: | prefix        | v4 (2)| u |v4 (2)| suffix   |
+//! /56: | prefix           |v4| u | v4 (3)  | suffix  |
+//! /64: | prefix              | u |  v4 (4)  | suffix |
+//! /96: | prefix                               | v4   |
+//! ```
+
+use crate::error::{AddrFormatError, AddrResult};
+use crate::ip::{IPAddress, IPNetwork};
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+
+/// The byte index of the reserved u-octet
+const U_OCTET: usize = 8;
+
+/// An RFC 6052 prefix for translating between IPv4 and IPv6 addresses
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Nat64Prefix {
+    network: IPNetwork,
+}
+
+impl Nat64Prefix {
+    /// Create a prefix from an IPv6 network of length 32, 40, 48, 56, 64 or 96
+    pub fn new(network: IPNetwork) -> AddrResult<Self> {
+        if !network.is_ipv6() || ![32, 40, 48, 56, 64, 96].contains(&network.prefix_length()) {
+            return Err(AddrFormatError::new(format!(
+                "Invalid NAT64 prefix {}: expected an IPv6 /32, /40, /48, /56, /64 or /96",
+                network
+            )));
+        }
+        if network.network_address().to_u128().to_be_bytes()[U_OCTET] != 0 {
+            return Err(AddrFormatError::new(format!(
+                "Invalid NAT64 prefix {}: bits 64 to 71 must be zero",
+                network
+            )));
+        }
+        Ok(Self {
+            network: IPNetwork::new(network.network_address().without_zone(), network.prefix_length())?,
+        })
+    }
+
+    /// The well-known prefix `64:ff9b::/96`
+    pub fn well_known() -> Self {
+        Self::from_str("64:ff9b::/96").expect("the well-known prefix is valid")
+    }
+
+    /// The local-use prefix `64:ff9b:1::/48` (RFC 8215)
+    pub fn local_use() -> Self {
+        Self::from_str("64:ff9b:1::/48").expect("the local-use prefix is valid")
+    }
+
+    /// Get the prefix as a network
+    pub fn network(&self) -> &IPNetwork {
+        &self.network
+    }
+
+    /// Get the prefix length
+    pub fn prefix_length(&self) -> u8 {
+        self.network.prefix_length()
+    }
+
+    /// Check if an IPv6 address falls within the prefix
+    pub fn contains(&self, addr: &IPAddress) -> bool {
+        self.network.contains(addr)
+    }
+
+    /// Embed an IPv4 address, giving the IPv6 address that represents it
+    pub fn embed(&self, ipv4: &IPAddress) -> AddrResult<IPAddress> {
+        let ipv4 = ipv4.as_ipv4().ok_or_else(|| {
+            AddrFormatError::new(format!("Only IPv4 addresses can be embedded, not {}", ipv4))
+        })?;
+
+        let mut octets = self.network.network_address().to_u128().to_be_bytes();
+        for (index, octet) in self.octet_indexes().zip(ipv4.octets()) {
+            octets[index] = octet;
+        }
+        Ok(IPAddress::new_v6(Ipv6Addr::from(octets)))
+    }
+
+    /// Extract the IPv4 address embedded in an IPv6 address
+    ///
+    /// The address must fall within the prefix and have a zero u-octet; the
+    /// suffix after the IPv4 address is ignored.
+    pub fn extract(&self, ipv6: &IPAddress) -> AddrResult<IPAddress> {
+        if !self.contains(ipv6) {
+            return Err(AddrFormatError::new(format!("{} is not within the NAT64 prefix {}", ipv6, self)));
+        }
+
+        let octets = ipv6.to_u128().to_be_bytes();
+        if octets[U_OCTET] != 0 {
+            return Err(AddrFormatError::new(format!(
+                "{} is not an RFC 6052 address: bits 64 to 71 must be zero",
+                ipv6
+            )));
+        }
+
+        let mut ipv4 = [0u8; 4];
+        for (octet, index) in ipv4.iter_mut().zip(self.octet_indexes()) {
+            *octet = octets[index];
+        }
+        Ok(IPAddress::new_v4(Ipv4Addr::from(ipv4)))
+    }
+
+    /// Embed an IPv4 network, giving the IPv6 network whose addresses all
+    /// carry an IPv4 address from it
+    ///
+    /// Only the IPv4 network bits are fixed, so for prefixes shorter than /96
+    /// the result also holds addresses with other u-octet and suffix bits.
+    pub fn embed_network(&self, network: &IPNetwork) -> AddrResult<IPNetwork> {
+        let address = self.embed(network.network_address())?;
+        let prefix_length = match network.prefix_length() {
+            0 => self.prefix_length(),
+            bits => self.bit_position(bits - 1) + 1,
+        };
+        IPNetwork::new(address, prefix_length)
+    }
+
+    /// Extract the IPv4 network embedded in an IPv6 network
+    ///
+    /// The IPv4 prefix length is the number of IPv4 bits the IPv6 network fixes.
+    pub fn extract_network(&self, network: &IPNetwork) -> AddrResult<IPNetwork> {
+        if !self.network.contains_network(network) {
+            return Err(AddrFormatError::new(format!(
+                "{} is not within the NAT64 prefix {}",
+                network, self
+            )));
+        }
+
+        let address = self.extract(network.network_address())?;
+        let prefix_length = (0..32)
+            .take_while(|&bit| self.bit_position(bit) < network.prefix_length())
+            .count();
+        IPNetwork::new(address, prefix_length as u8)
+    }
+
+    /// Get the byte indexes holding the four IPv4 octets
+    fn octet_indexes(&self) -> impl Iterator<Item = usize> {
+        (self.prefix_length() as usize / 8..).filter(|&index| index != U_OCTET).take(4)
+    }
+
+    /// Get the IPv6 bit position of an IPv4 bit (0 is the most significant)
+    fn bit_position(&self, bit: u8) -> u8 {
+        let position = self.prefix_length() + bit;
+        if self.prefix_length() < 96 && position >= 64 {
+            position + 8
+        } else {
+            position
+        }
+    }
+}
+
+impl FromStr for Nat64Prefix {
+    type Err = AddrFormatError;
+
+    fn from_str(s: &str) -> AddrResult<Self> {
+        Self::new(IPNetwork::from_str(s)?)
+    }
+}
+
+impl fmt::Display for Nat64Prefix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.network)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(s: &str) -> IPAddress {
+        IPAddress::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_rfc6052_examples() {
+        // The table in RFC 6052 section 2.4, embedding 192.0.2.33
+        let ipv4 = addr("192.0.2.33");
+        for (prefix, expected) in [
+            ("2001:db8::/32", "2001:db8:c000:221::"),
+            ("2001:db8:100::/40", "2001:db8:1c0:2:21::"),
+            ("2001:db8:122::/48", "2001:db8:122:c000:2:2100::"),
+            ("2001:db8:122:300::/56", "2001:db8:122:3c0:0:221::"),
+            ("2001:db8:122:344::/64", "2001:db8:122:344:c0:2:2100:0"),
+            ("2001:db8:122:344::/96", "2001:db8:122:344::c000:221"),
+        ] {
+            let prefix = Nat64Prefix::from_str(prefix).unwrap();
+            let ipv6 = prefix.embed(&ipv4).unwrap();
+            assert_eq!(ipv6.to_string(), expected, "{}", prefix);
+            assert_eq!(prefix.extract(&ipv6).unwrap(), ipv4, "{}", prefix);
+        }
+    }
+
+    #[test]
+    fn test_well_known_prefixes() {
+        let ipv6 = Nat64Prefix::well_known().embed(&addr("192.0.2.1")).unwrap();
+        assert_eq!(ipv6.to_string(), "64:ff9b::c000:201");
+        assert_eq!(Nat64Prefix::well_known().extract(&addr("64:ff9b::808:808")).unwrap().to_string(), "8.8.8.8");
+
+        let local = Nat64Prefix::local_use();
+        assert_eq!(local.embed(&addr("10.1.2.3")).unwrap().to_string(), "64:ff9b:1:a01:2:300::");
+        assert!(local.extract(&addr("64:ff9b:1:a01:ff02:300::")).is_err());
+        assert!(local.extract(&addr("2001:db8::1")).is_err());
+        assert!(local.embed(&addr("2001:db8::1")).is_err());
+    }
+
+    #[test]
+    fn test_invalid_prefixes() {
+        for prefix in ["2001:db8::/33", "2001:db8::/128", "10.0.0.0/8", "2001:db8:0:0:ff00::/96"] {
+            assert!(Nat64Prefix::from_str(prefix).is_err(), "{}", prefix);
+        }
+    }
+
+    #[test]
+    fn test_networks() {
+        let network = IPNetwork::from_str("10.0.0.0/8").unwrap();
+        let well_known = Nat64Prefix::well_known();
+        let embedded = well_known.embed_network(&network).unwrap();
+        assert_eq!(embedded.to_string(), "64:ff9b::a00:0/104");
+        assert_eq!(well_known.extract_network(&embedded).unwrap(), network);
+
+        // A /24 in a /56 prefix fixes the octet before the u-octet and two after it
+        let prefix = Nat64Prefix::from_str("2001:db8:122:300::/56").unwrap();
+        let network = IPNetwork::from_str("192.0.2.0/24").unwrap();
+        let embedded = prefix.embed_network(&network).unwrap();
+        assert_eq!(embedded.to_string(), "2001:db8:122:3c0:0:200::/88");
+        assert_eq!(prefix.extract_network(&embedded).unwrap(), network);
+
+        let host = IPNetwork::from_str("192.0.2.33/32").unwrap();
+        assert_eq!(prefix.embed_network(&host).unwrap().prefix_length(), 96);
+        assert_eq!(prefix.extract_network(prefix.network()).unwrap().to_string(), "0.0.0.0/0");
+        assert!(prefix.extract_network(&IPNetwork::from_str("2001:db8::/32").unwrap()).is_err());
+    }
+}
@@ -1,7 +1,11 @@
 //! IP Network (CIDR) implementation
 
 use crate::error::{AddrFormatError, AddrResult};
-use crate::ip::{IPAddress, IPAddressType};
+use crate::ip::ipv4::{IPv4, SubnetMask};
+use crate::ip::ipv6::{IPv6, IPv6SubnetMask};
+use crate::ip::reverse_dns::{self, ClasslessDelegation, ClasslessLabel, ReverseZone};
+use crate::ip::{AddressCount, IPAddress, IPAddressType};
+use crate::ParseFlags;
 use std::fmt;
 use std::str::FromStr;
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
@@ -32,8 +36,12 @@ impl IPNetwork {
             )));
         }
 
-        // Normalize network address (clear host bits)
+        // Normalize network address (clear host bits), keeping any IPv6 zone
         let normalized_network = Self::normalize_network_address(&network_address, prefix_length)?;
+        let normalized_network = match network_address.zone() {
+            Some(zone) => normalized_network.with_zone(zone)?,
+            None => normalized_network,
+        };
 
         Ok(Self {
             network_address: normalized_network,
@@ -102,14 +110,16 @@ impl IPNetwork {
         }
     }
 
+    /// Get the host mask (the inverse of the netmask)
+    pub fn hostmask(&self) -> AddrResult<IPAddress> {
+        Ok(!self.netmask()?)
+    }
+
     /// Get the broadcast address (IPv4 only)
     pub fn broadcast_address(&self) -> AddrResult<IPAddress> {
         match self.network_address.ip_type() {
             IPAddressType::IPv4 => {
-                let network_u32 = u32::from(*self.network_address.as_ipv4().unwrap());
-                let host_bits = 32 - self.prefix_length;
-                let broadcast_u32 = network_u32 | ((1u32 << host_bits) - 1);
-                Ok(IPAddress::new_v4(Ipv4Addr::from(broadcast_u32)))
+                Ok(self.last_address())
             }
             IPAddressType::IPv6 => {
                 Err(AddrFormatError::new("IPv6 networks don't have broadcast addresses"))
@@ -117,31 +127,45 @@ impl IPNetwork {
         }
     }
 
-    /// Get the number of host addresses in this network
-    pub fn num_addresses(&self) -> u128 {
+    /// Get the number of addresses in this network
+    ///
+    /// The count is exact even for `::/0`, which holds 2^128 addresses.
+    pub fn num_addresses(&self) -> AddressCount {
         let host_bits = match self.network_address.ip_type() {
             IPAddressType::IPv4 => 32 - self.prefix_length,
             IPAddressType::IPv6 => 128 - self.prefix_length,
         };
 
-        if host_bits >= 128 {
-            return u128::MAX;
-        }
+        AddressCount::power_of_two(host_bits as u32)
+    }
 
-        1u128 << host_bits
+    /// Get the last address of the network (the broadcast address for IPv4)
+    pub fn last_address(&self) -> IPAddress {
+        let host_mask = match self.network_address.ip_type() {
+            IPAddressType::IPv4 => u32::MAX.checked_shr(self.prefix_length as u32).unwrap_or(0) as u128,
+            IPAddressType::IPv6 => u128::MAX.checked_shr(self.prefix_length as u32).unwrap_or(0),
+        };
+        // Host bits of the network address are clear, so adding sets them
+        // (and keeps any zone)
+        self.network_address.wrapping_add(host_mask)
     }
 
     /// Check if an IP address is contained in this network
+    ///
+    /// A network with a zone only contains addresses in the same zone.
     pub fn contains(&self, addr: &IPAddress) -> bool {
         if self.network_address.ip_type() != addr.ip_type() {
             return false;
         }
+        if self.network_address.zone().is_some() && self.network_address.zone() != addr.zone() {
+            return false;
+        }
 
         match (self.network_address.as_ip_addr(), addr.as_ip_addr()) {
             (IpAddr::V4(net), IpAddr::V4(addr)) => {
                 let net_u32 = u32::from(*net);
                 let addr_u32 = u32::from(*addr);
-                let mask = (!0u32) << (32 - self.prefix_length);
+                let mask = !u32::MAX.checked_shr(self.prefix_length as u32).unwrap_or(0);
                 (net_u32 & mask) == (addr_u32 & mask)
             }
             (IpAddr::V6(net), IpAddr::V6(addr)) => {
@@ -226,17 +250,16 @@ impl IPNetwork {
                     broadcast.prev()
                 }
             }
-            IPAddressType::IPv6 => {
-                let host_bits = 128 - self.prefix_length;
-                let network_u128 = u128::from(*self.network_address.as_ipv6().unwrap());
-                let last_u128 = network_u128 | ((1u128 << host_bits) - 1);
-                Some(IPAddress::new_v6(Ipv6Addr::from(last_u128)))
-            }
+            IPAddressType::IPv6 => Some(self.last_address()),
         }
     }
 
-    /// Create subnets by dividing this network
-    pub fn subnets(&self, new_prefix_length: u8) -> AddrResult<Vec<IPNetwork>> {
+    /// Iterate over the subnets of a longer prefix length that divide this network
+    ///
+    /// The iterator is lazy and double-ended, and `nth`, `skip` and indexed
+    /// access are O(1), so even the 2^32 /64s of a /32 can be walked or
+    /// sampled without allocating them.
+    pub fn subnets(&self, new_prefix_length: u8) -> AddrResult<SubnetIterator> {
         let max_prefix = match self.network_address.ip_type() {
             IPAddressType::IPv4 => 32,
             IPAddressType::IPv6 => 128,
@@ -248,35 +271,25 @@ impl IPNetwork {
             ));
         }
 
-        let subnet_size = 1u128 << (new_prefix_length - self.prefix_length);
-        let mut subnets = Vec::new();
-
-        match self.network_address.ip_type() {
-            IPAddressType::IPv4 => {
-                let network_u32 = u32::from(*self.network_address.as_ipv4().unwrap());
-                let step = 1u32 << (32 - new_prefix_length);
-
-                for i in 0..subnet_size {
-                    if let Ok(i_u32) = u32::try_from(i) {
-                        let subnet_addr = network_u32 + (i_u32 * step);
-                        let subnet_ip = IPAddress::new_v4(Ipv4Addr::from(subnet_addr));
-                        subnets.push(IPNetwork::new(subnet_ip, new_prefix_length)?);
-                    }
-                }
-            }
-            IPAddressType::IPv6 => {
-                let network_u128 = u128::from(*self.network_address.as_ipv6().unwrap());
-                let step = 1u128 << (128 - new_prefix_length);
-
-                for i in 0..subnet_size {
-                    let subnet_addr = network_u128 + (i * step);
-                    let subnet_ip = IPAddress::new_v6(Ipv6Addr::from(subnet_addr));
-                    subnets.push(IPNetwork::new(subnet_ip, new_prefix_length)?);
-                }
-            }
-        }
+        Ok(SubnetIterator {
+            base: self.network_address.clone(),
+            prefix_length: new_prefix_length,
+            shift: (max_prefix - new_prefix_length) as u32,
+            front: 0,
+            back: u128::MAX >> (128 - (new_prefix_length - self.prefix_length) as u32),
+            finished: false,
+        })
+    }
 
-        Ok(subnets)
+    /// Get the subnet at `index` among the subnets of a longer prefix length,
+    /// such as the Nth /64 of a /48
+    pub fn subnet(&self, new_prefix_length: u8, index: u128) -> AddrResult<IPNetwork> {
+        self.subnets(new_prefix_length)?.get(index).ok_or_else(|| {
+            AddrFormatError::new(format!(
+                "Subnet index {} is out of range for /{} subnets of {}",
+                index, new_prefix_length, self
+            ))
+        })
     }
 
     /// Get the parent network (supernet)
@@ -332,20 +345,163 @@ impl IPNetwork {
     }
 }
 
+impl IPNetwork {
+    /// Parse a network, honoring the given parse flags
+    ///
+    /// The address is parsed with [`IPAddress::parse_with`] and the mask may
+    /// use any notation accepted by `from_str`; without a mask the network is
+    /// a single host. Host bits set to the right of the
+    /// prefix are an error unless `NOHOST` is given, in which case they are
+    /// cleared.
+    pub fn parse_with(s: &str, flags: ParseFlags) -> AddrResult<Self> {
+        let (addr, mask) = split_network_str(s)?;
+
+        let address = IPAddress::parse_with(addr, flags)?;
+        let prefix_length = match mask {
+            Some((mask, notation)) => parse_mask(&address, mask, notation)?,
+            None if address.is_ipv4() => 32,
+            None => 128,
+        };
+
+        let network = IPNetwork::new(address.clone(), prefix_length)?;
+        if !flags.contains(ParseFlags::NOHOST) && network.network_address != address {
+            return Err(AddrFormatError::new(format!(
+                "'{}' has host bits set (expected {})",
+                s, network
+            )));
+        }
+        Ok(network)
+    }
+}
+
+/// How the mask part of a network string is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MaskNotation {
+    /// `addr/mask`, where the mask is a prefix length, netmask or hostmask
+    Slash,
+    /// `addr mask`, where the mask is a netmask or Cisco wildcard
+    Space,
+}
+
+/// Split a network string into its address and optional mask parts
+pub(crate) fn split_network_str(s: &str) -> AddrResult<(&str, Option<(&str, MaskNotation)>)> {
+    if let Some((addr, mask)) = s.split_once('/') {
+        return Ok((addr.trim(), Some((mask.trim(), MaskNotation::Slash))));
+    }
+
+    let parts: Vec<&str> = s.split_whitespace().collect();
+    match parts.as_slice() {
+        [addr] => Ok((addr, None)),
+        [addr, mask] => Ok((addr, Some((mask, MaskNotation::Space)))),
+        _ => Err(AddrFormatError::new(format!(
+            "Invalid network '{}': expected 'addr/prefix', 'addr/mask' or 'addr mask'",
+            s
+        ))),
+    }
+}
+
+/// Get the prefix length for the mask part of a network string
+///
+/// A dotted mask is read as a netmask if it is one and as a hostmask (Cisco
+/// wildcard) otherwise, so the ambiguous all-ones mask is a netmask. The
+/// all-zeros mask is a netmask after a `/` but the host wildcard in space
+/// notation, so an ACL entry like `10.0.0.1 0.0.0.0` matches one host rather
+/// than everything. Masks that are neither are rejected as non-contiguous.
+pub(crate) fn parse_mask(address: &IPAddress, mask: &str, notation: MaskNotation) -> AddrResult<u8> {
+    if notation == MaskNotation::Slash && mask.bytes().all(|b| b.is_ascii_digit()) {
+        return mask
+            .parse::<u8>()
+            .map_err(|_| AddrFormatError::new(format!("Invalid prefix length '{}'", mask)));
+    }
+
+    let mask_address = IPAddress::from_str(mask)
+        .map_err(|_| AddrFormatError::new(format!("Invalid mask '{}'", mask)))?;
+    if mask_address.ip_type() != address.ip_type() {
+        return Err(AddrFormatError::new(format!(
+            "Mask '{}' does not match the address family of {}",
+            mask, address
+        )));
+    }
+
+    let (is_netmask, is_hostmask, ones) = match mask_address.as_ip_addr() {
+        IpAddr::V4(mask) => {
+            let mask = IPv4::from(*mask);
+            let inverse = !mask.clone();
+            (SubnetMask::is_valid_mask(&mask), SubnetMask::is_valid_mask(&inverse), SubnetMask::to_prefix_length(&mask))
+        }
+        IpAddr::V6(mask) => {
+            let mask = IPv6::from(*mask);
+            let inverse = !mask.clone();
+            (IPv6SubnetMask::is_valid_mask(&mask), IPv6SubnetMask::is_valid_mask(&inverse), IPv6SubnetMask::to_prefix_length(&mask))
+        }
+    };
+    let width = if address.is_ipv4() { 32 } else { 128 };
+
+    if notation == MaskNotation::Space && ones == 0 {
+        Ok(width)
+    } else if is_netmask {
+        Ok(ones)
+    } else if is_hostmask {
+        Ok(width - ones)
+    } else {
+        Err(AddrFormatError::new(format!("Non-contiguous mask '{}'", mask)))
+    }
+}
+
+impl IPNetwork {
+    /// Format as `addr/netmask`, e.g. `10.0.0.0/255.255.255.0`
+    pub fn with_netmask(&self) -> String {
+        format!("{}/{}", self.network_address, self.netmask().expect("netmask is always defined"))
+    }
+
+    /// Format as `addr/hostmask`, e.g. `10.0.0.0/0.0.0.255`
+    pub fn with_hostmask(&self) -> String {
+        format!("{}/{}", self.network_address, self.hostmask().expect("hostmask is always defined"))
+    }
+
+    /// Format as `addr netmask`, as in router configs, e.g. `10.0.0.0 255.255.255.0`
+    ///
+    /// A /0 network gives an all-zeros mask, which parses back as a host
+    /// wildcard; use the `/` forms where that matters.
+    pub fn to_netmask_notation(&self) -> String {
+        format!("{} {}", self.network_address, self.netmask().expect("netmask is always defined"))
+    }
+
+    /// Format as `addr wildcard`, as in Cisco ACLs, e.g. `10.0.0.0 0.0.0.255`
+    pub fn to_wildcard_notation(&self) -> String {
+        format!("{} {}", self.network_address, self.hostmask().expect("hostmask is always defined"))
+    }
+
+    /// Get the minimal reverse DNS zones covering this network
+    /// (see [`crate::ip::reverse_dns`])
+    pub fn reverse_zones(&self) -> Vec<ReverseZone> {
+        reverse_dns::reverse_zones(self)
+    }
+
+    /// Parse a full or partial reverse DNS name, such as `10.in-addr.arpa`
+    pub fn from_reverse_dns(name: &str) -> AddrResult<IPNetwork> {
+        reverse_dns::parse_reverse_name(name)
+    }
+
+    /// Get the RFC 2317 classless delegation of an IPv4 block longer than /24
+    pub fn rfc2317_delegation(&self, label: ClasslessLabel) -> AddrResult<ClasslessDelegation> {
+        reverse_dns::rfc2317_delegation(self, label)
+    }
+}
+
 impl FromStr for IPNetwork {
     type Err = AddrFormatError;
 
+    /// Parse `addr/prefix`, `addr/netmask`, `addr/hostmask`, `addr netmask`
+    /// or `addr wildcard`
     fn from_str(s: &str) -> AddrResult<Self> {
-        let parts: Vec<&str> = s.split('/').collect();
-        if parts.len() != 2 {
-            return Err(AddrFormatError::new(
-                "Network must be in CIDR notation (address/prefix)"
-            ));
-        }
+        let (addr, mask) = split_network_str(s)?;
+        let (mask, notation) = mask.ok_or_else(|| {
+            AddrFormatError::new("Network must be in CIDR notation (address/prefix)")
+        })?;
 
-        let address = IPAddress::from_str(parts[0])?;
-        let prefix_length = parts[1].parse::<u8>()
-            .map_err(|_| AddrFormatError::new("Invalid prefix length"))?;
+        let address = IPAddress::from_str(addr)?;
+        let prefix_length = parse_mask(&address, mask, notation)?;
 
         IPNetwork::new(address, prefix_length)
     }
@@ -372,6 +528,107 @@ impl Ord for IPNetwork {
     }
 }
 
+/// Iterator over the subnets of a network, in address order
+///
+/// Subnets are computed from their index, so the iterator never allocates and
+/// can be advanced from either end in constant time.
+#[derive(Debug, Clone)]
+pub struct SubnetIterator {
+    base: IPAddress,
+    prefix_length: u8,
+    shift: u32,
+    front: u128,
+    back: u128,
+    finished: bool,
+}
+
+impl SubnetIterator {
+    /// Get the number of subnets left, which can exceed `usize` (and `u128` for `::/0` into /128s)
+    pub fn remaining(&self) -> AddressCount {
+        if self.finished {
+            AddressCount::ZERO
+        } else {
+            AddressCount::new(self.back - self.front) + AddressCount::new(1)
+        }
+    }
+
+    /// Get the subnet `index` places after the next one, without advancing
+    pub fn get(&self, index: u128) -> Option<IPNetwork> {
+        if self.finished || index > self.back - self.front {
+            return None;
+        }
+        Some(self.subnet_at(self.front + index))
+    }
+
+    fn subnet_at(&self, index: u128) -> IPNetwork {
+        let address = self.base.with_value(self.base.to_u128() | (index << self.shift));
+        IPNetwork::new_unchecked(address, self.prefix_length)
+    }
+}
+
+impl Iterator for SubnetIterator {
+    type Item = IPNetwork;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let subnet = self.subnet_at(self.front);
+        if self.front == self.back {
+            self.finished = true;
+        } else {
+            self.front += 1;
+        }
+        Some(subnet)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        if self.finished || n as u128 > self.back - self.front {
+            self.finished = true;
+            return None;
+        }
+        self.front += n as u128;
+        self.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.remaining().to_u128().and_then(|n| usize::try_from(n).ok()) {
+            Some(n) => (n, Some(n)),
+            None => (usize::MAX, None),
+        }
+    }
+
+    fn last(mut self) -> Option<Self::Item> {
+        self.next_back()
+    }
+}
+
+impl DoubleEndedIterator for SubnetIterator {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let subnet = self.subnet_at(self.back);
+        if self.front == self.back {
+            self.finished = true;
+        } else {
+            self.back -= 1;
+        }
+        Some(subnet)
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        if self.finished || n as u128 > self.back - self.front {
+            self.finished = true;
+            return None;
+        }
+        self.back -= n as u128;
+        self.next_back()
+    }
+}
+
+impl std::iter::FusedIterator for SubnetIterator {}
+
 /// Iterator over host addresses in a network
 pub struct NetworkHostIterator {
     current: Option<IPAddress>,
@@ -442,11 +699,76 @@ mod tests {
         assert_eq!(network.network_address().to_string(), "192.168.1.0");
     }
 
+    #[test]
+    fn test_parse_with_flags() {
+        let net = IPNetwork::parse_with("192.168.1.0/24", ParseFlags::empty()).unwrap();
+        assert_eq!(net.to_string(), "192.168.1.0/24");
+
+        assert!(IPNetwork::parse_with("192.168.1.100/24", ParseFlags::empty()).is_err());
+        let net = IPNetwork::parse_with("192.168.1.100/24", ParseFlags::NOHOST).unwrap();
+        assert_eq!(net.to_string(), "192.168.1.0/24");
+
+        let net = IPNetwork::parse_with("10.1/8", ParseFlags::INET_ATON | ParseFlags::NOHOST).unwrap();
+        assert_eq!(net.to_string(), "10.0.0.0/8");
+        let net = IPNetwork::parse_with("010.001.000.000/16", ParseFlags::ZEROFILL).unwrap();
+        assert_eq!(net.to_string(), "10.1.0.0/16");
+        assert_eq!(IPNetwork::parse_with("10.0.0.1", ParseFlags::empty()).unwrap().to_string(), "10.0.0.1/32");
+        assert_eq!(IPNetwork::parse_with("2001:db8::1", ParseFlags::empty()).unwrap().to_string(), "2001:db8::1/128");
+        assert!(IPNetwork::parse_with("2001:db8::1/64", ParseFlags::empty()).is_err());
+    }
+
+    #[test]
+    fn test_zoned_network() {
+        let network = IPNetwork::from_str("fe80::1%eth0/64").unwrap();
+        assert_eq!(network.to_string(), "fe80::%eth0/64");
+        assert_eq!(network.network_address().zone(), Some("eth0"));
+        assert_eq!(network.last_address().to_string(), "fe80::ffff:ffff:ffff:ffff%eth0");
+
+        assert!(network.contains(&IPAddress::from_str("fe80::2%eth0").unwrap()));
+        assert!(!network.contains(&IPAddress::from_str("fe80::2%eth1").unwrap()));
+        assert!(!network.contains(&IPAddress::from_str("fe80::2").unwrap()));
+
+        let unzoned = IPNetwork::from_str("fe80::/64").unwrap();
+        assert!(unzoned.contains(&IPAddress::from_str("fe80::2%eth1").unwrap()));
+        assert_ne!(unzoned, network);
+    }
+
+    #[test]
+    fn test_mask_notations() {
+        for s in ["10.0.0.0/24", "10.0.0.0/255.255.255.0", "10.0.0.0/0.0.0.255", "10.0.0.0 255.255.255.0", "10.0.0.0 0.0.0.255", " 10.0.0.0   0.0.0.255 "] {
+            assert_eq!(IPNetwork::from_str(s).unwrap().to_string(), "10.0.0.0/24", "{}", s);
+        }
+        assert_eq!(IPNetwork::from_str("2001:db8::/ffff:ffff::").unwrap().to_string(), "2001:db8::/32");
+        assert_eq!(IPNetwork::from_str("10.0.0.0/0.0.0.0").unwrap().prefix_length(), 0);
+
+        let err = IPNetwork::from_str("10.0.0.0 255.0.255.0").unwrap_err();
+        assert!(err.to_string().contains("Non-contiguous mask '255.0.255.0'"));
+        assert!(IPNetwork::from_str("10.0.0.0 24").is_err());
+        assert!(IPNetwork::from_str("10.0.0.0/ffff::").is_err());
+        assert!(IPNetwork::from_str("10.0.0.0").is_err());
+
+        let network = IPNetwork::from_str("10.0.0.0/24").unwrap();
+        assert_eq!(network.with_netmask(), "10.0.0.0/255.255.255.0");
+        assert_eq!(network.with_hostmask(), "10.0.0.0/0.0.0.255");
+        assert_eq!(network.to_netmask_notation(), "10.0.0.0 255.255.255.0");
+        assert_eq!(network.to_wildcard_notation(), "10.0.0.0 0.0.0.255");
+
+        let network = IPNetwork::parse_with("10.0.0.1 0.0.0.255", ParseFlags::NOHOST).unwrap();
+        assert_eq!(network.to_string(), "10.0.0.0/24");
+
+        // A zero wildcard matches a single host, not everything
+        assert_eq!(IPNetwork::from_str("10.0.0.1 0.0.0.0").unwrap().to_string(), "10.0.0.1/32");
+        assert_eq!(IPNetwork::from_str("2001:db8::1 ::").unwrap().to_string(), "2001:db8::1/128");
+        let host = IPNetwork::from_str("10.0.0.1/32").unwrap();
+        assert_eq!(IPNetwork::from_str(&host.to_wildcard_notation()).unwrap(), host);
+    }
+
     #[test]
     fn test_netmask() {
         let network = IPNetwork::from_str("192.168.1.0/24").unwrap();
         let netmask = network.netmask().unwrap();
         assert_eq!(netmask.to_string(), "255.255.255.0");
+        assert_eq!(network.hostmask().unwrap().to_string(), "0.0.0.255");
     }
 
     #[test]
@@ -495,7 +817,7 @@ mod tests {
     #[test]
     fn test_subnetting() {
         let network = IPNetwork::from_str("192.168.1.0/24").unwrap();
-        let subnets = network.subnets(26).unwrap();
+        let subnets: Vec<IPNetwork> = network.subnets(26).unwrap().collect();
         assert_eq!(subnets.len(), 4);
         assert_eq!(subnets[0].to_string(), "192.168.1.0/26");
         assert_eq!(subnets[1].to_string(), "192.168.1.64/26");
@@ -503,6 +825,37 @@ mod tests {
         assert_eq!(subnets[3].to_string(), "192.168.1.192/26");
     }
 
+    #[test]
+    fn test_lazy_subnets() {
+        let network = IPNetwork::from_str("2001:db8::/32").unwrap();
+        let mut subnets = network.subnets(64).unwrap();
+        assert_eq!(subnets.remaining(), 1u128 << 32);
+        assert_eq!(subnets.size_hint(), (1 << 32, Some(1 << 32)));
+        assert_eq!(subnets.nth(1).unwrap().to_string(), "2001:db8:0:1::/64");
+        assert_eq!(subnets.next_back().unwrap().to_string(), "2001:db8:ffff:ffff::/64");
+        assert_eq!(subnets.get(0).unwrap().to_string(), "2001:db8:0:2::/64");
+        assert_eq!(subnets.remaining(), (1u128 << 32) - 3);
+
+        let mut skipped = network.subnets(64).unwrap().skip((1 << 32) - 2);
+        assert_eq!(skipped.next().unwrap().to_string(), "2001:db8:ffff:fffe::/64");
+        assert_eq!(skipped.next().unwrap().to_string(), "2001:db8:ffff:ffff::/64");
+        assert!(skipped.next().is_none());
+
+        let site = IPNetwork::from_str("2001:db8:1::/48").unwrap();
+        assert_eq!(site.subnet(64, 0x2a).unwrap().to_string(), "2001:db8:1:2a::/64");
+        assert!(site.subnet(64, 1 << 16).is_err());
+
+        let everything = IPNetwork::from_str("::/0").unwrap().subnets(128).unwrap();
+        assert_eq!(everything.remaining(), AddressCount::power_of_two(128));
+        assert_eq!(everything.size_hint(), (usize::MAX, None));
+        assert_eq!(everything.last().unwrap().to_string(), "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff/128");
+
+        let mut quarters = IPNetwork::from_str("10.0.0.0/8").unwrap().subnets(10).unwrap();
+        assert_eq!(quarters.nth_back(1).unwrap().to_string(), "10.128.0.0/10");
+        assert_eq!(quarters.nth(1).unwrap().to_string(), "10.64.0.0/10");
+        assert!(quarters.next().is_none());
+    }
+
     #[test]
     fn test_supernetting() {
         let network = IPNetwork::from_str("192.168.1.0/24").unwrap();
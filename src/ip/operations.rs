@@ -104,6 +104,9 @@ pub fn cidr_abbrev_to_verbose(cidr_str: &str) -> AddrResult<String> {
 }
 
 /// Exclude one CIDR block from another, returning the remaining blocks
+///
+/// CIDR blocks either nest or are disjoint, so what remains is the space
+/// before and after the excluded block, decomposed arithmetically.
 pub fn cidr_exclude(
     base: &IPNetwork,
     exclude: &IPNetwork,
@@ -118,74 +121,21 @@ pub fn cidr_exclude(
         return Ok(Vec::new());
     }
 
-    // If base completely contains exclude, we need to split
-    if base.contains_network(exclude) {
-        return split_network_exclude(base, exclude);
-    }
-
-    // Partial overlap case
+    let base_range = IPRange::from(base);
+    let exclude_range = IPRange::from(exclude);
     let mut result = Vec::new();
 
-    // Find the overlapping part and the non-overlapping parts
-    let base_range = IPRange::new(
-        base.network_address().clone(),
-        base.last_host().unwrap_or_else(|| base.network_address().clone()),
-    )?;
-
-    let exclude_range = IPRange::new(
-        exclude.network_address().clone(),
-        exclude.last_host().unwrap_or_else(|| exclude.network_address().clone()),
-    )?;
-
-    if let Some(intersection) = base_range.intersection(&exclude_range) {
-        // Add the parts before and after the intersection
-        if base_range.start() < intersection.start() {
-            let before_end = intersection.start().prev().unwrap();
-            let before_range = IPRange::new(base_range.start().clone(), before_end)?;
-            result.extend(before_range.to_cidrs()?);
-        }
-
-        if intersection.end() < base_range.end() {
-            let after_start = intersection.end().next().unwrap();
-            let after_range = IPRange::new(after_start, base_range.end().clone())?;
-            result.extend(after_range.to_cidrs()?);
+    if let Some(before_end) = exclude_range.start().prev() {
+        if base_range.start() <= &before_end {
+            let before = IPRange::new_unchecked(base_range.start().clone(), before_end);
+            result.extend(before.iter_cidrs());
         }
     }
 
-    Ok(result)
-}
-
-/// Split a network to exclude a contained subnet
-fn split_network_exclude(
-    base: &IPNetwork,
-    exclude: &IPNetwork,
-) -> AddrResult<Vec<IPNetwork>> {
-    let mut result = Vec::new();
-    let mut queue = vec![base.clone()];
-
-    while let Some(current) = queue.pop() {
-        if !current.overlaps(exclude) {
-            result.push(current);
-            continue;
-        }
-
-        if exclude.contains_network(&current) {
-            // Skip this network entirely
-            continue;
-        }
-
-        if current.prefix_length() >= exclude.prefix_length() {
-            // Can't split further in a meaningful way
-            if current != *exclude {
-                result.push(current);
-            }
-            continue;
-        }
-
-        // Split current network into two halves
-        let subnets = current.subnets(current.prefix_length() + 1)?;
-        if subnets.len() == 2 {
-            queue.extend(subnets);
+    if let Some(after_start) = exclude_range.end().next() {
+        if &after_start <= base_range.end() {
+            let after = IPRange::new_unchecked(after_start, base_range.end().clone());
+            result.extend(after.iter_cidrs());
         }
     }
 
@@ -199,19 +149,7 @@ pub fn cidr_merge(cidrs: &[IPNetwork]) -> AddrResult<Vec<IPNetwork>> {
     }
 
     // Convert to ranges, merge them, then back to CIDRs
-    let mut ranges = Vec::new();
-    for cidr in cidrs {
-        let start = cidr.network_address().clone();
-        let end = match cidr.ip_type() {
-            crate::ip::IPAddressType::IPv4 => cidr.broadcast_address()?,
-            crate::ip::IPAddressType::IPv6 => {
-                cidr.last_host().ok_or_else(|| {
-                    AddrFormatError::new("Cannot determine last address")
-                })?
-            }
-        };
-        ranges.push(IPRange::new(start, end)?);
-    }
+    let ranges: Vec<IPRange> = cidrs.iter().map(IPRange::from).collect();
 
     let merged_ranges = crate::ip::range::merge_ranges(&ranges)?;
 
@@ -387,6 +325,15 @@ mod tests {
         let remaining = cidr_exclude(&base, &exclude).unwrap();
         assert_eq!(remaining.len(), 1);
         assert_eq!(remaining[0].to_string(), "192.168.1.0/25");
+
+        let base = IPNetwork::from_str("::/0").unwrap();
+        let exclude = IPNetwork::from_str("2001:db8::1/128").unwrap();
+        let remaining = cidr_exclude(&base, &exclude).unwrap();
+        assert_eq!(remaining.len(), 128);
+        assert_eq!(remaining[0].to_string(), "::/3");
+        assert_eq!(remaining[127].to_string(), "8000::/1");
+        let total: crate::ip::AddressCount = remaining.iter().map(|c| c.num_addresses()).sum();
+        assert_eq!(total, u128::MAX);
     }
 
     #[test]
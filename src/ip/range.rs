@@ -1,11 +1,14 @@
 //! IP Range implementation for arbitrary ranges
 
 use crate::error::{AddrFormatError, AddrResult};
-use crate::ip::{IPAddress, IPAddressType, IPNetwork};
+use crate::ip::{AddressCount, IPAddress, IPAddressType, IPNetwork};
 use std::fmt;
 use std::str::FromStr;
 
 /// Represents an arbitrary range of IP addresses
+///
+/// Ranges span address values, so they never hold IPv6 zones: [`IPRange::new`]
+/// rejects zoned addresses and zoned addresses are never contained.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct IPRange {
     start: IPAddress,
@@ -15,6 +18,9 @@ pub struct IPRange {
 impl IPRange {
     /// Create a new IP range
     pub fn new(start: IPAddress, end: IPAddress) -> AddrResult<Self> {
+        check_unzoned(&start)?;
+        check_unzoned(&end)?;
+
         if start.ip_type() != end.ip_type() {
             return Err(AddrFormatError::new(
                 "Start and end addresses must be the same IP version"
@@ -30,6 +36,11 @@ impl IPRange {
         Ok(Self { start, end })
     }
 
+    /// Create without validating the address versions and order
+    pub fn new_unchecked(start: IPAddress, end: IPAddress) -> Self {
+        Self { start, end }
+    }
+
     /// Get the start address
     pub fn start(&self) -> &IPAddress {
         &self.start
@@ -57,7 +68,7 @@ impl IPRange {
 
     /// Check if an IP address is contained in this range
     pub fn contains(&self, addr: &IPAddress) -> bool {
-        if self.start.ip_type() != addr.ip_type() {
+        if self.start.ip_type() != addr.ip_type() || addr.zone().is_some() {
             return false;
         }
 
@@ -65,17 +76,21 @@ impl IPRange {
     }
 
     /// Get the number of addresses in this range
-    pub fn size(&self) -> u128 {
+    ///
+    /// The count is exact even for the full IPv6 space (2^128 addresses).
+    pub fn size(&self) -> AddressCount {
+        let (start, end, _) = self.bounds();
+        AddressCount::new(end - start) + AddressCount::new(1)
+    }
+
+    /// Get the start and end as integers, with the address width in bits
+    fn bounds(&self) -> (u128, u128, u32) {
         match (self.start.as_ip_addr(), self.end.as_ip_addr()) {
             (std::net::IpAddr::V4(start), std::net::IpAddr::V4(end)) => {
-                let start_u32 = u32::from(*start);
-                let end_u32 = u32::from(*end);
-                (end_u32 - start_u32 + 1) as u128
+                (u32::from(*start) as u128, u32::from(*end) as u128, 32)
             }
             (std::net::IpAddr::V6(start), std::net::IpAddr::V6(end)) => {
-                let start_u128 = u128::from(*start);
-                let end_u128 = u128::from(*end);
-                end_u128 - start_u128 + 1
+                (u128::from(*start), u128::from(*end), 128)
             }
             _ => unreachable!("Different IP versions should be caught in constructor"),
         }
@@ -104,65 +119,22 @@ impl IPRange {
 
     /// Convert this range to a list of CIDR blocks
     pub fn to_cidrs(&self) -> AddrResult<Vec<IPNetwork>> {
-        let mut cidrs = Vec::new();
-
-        match (self.start.as_ip_addr(), self.end.as_ip_addr()) {
-            (std::net::IpAddr::V4(start), std::net::IpAddr::V4(end)) => {
-                let mut current = u32::from(*start);
-                let end_u32 = u32::from(*end);
-
-                while current <= end_u32 {
-                    let maxsize = Self::maxblock_ipv4(current, end_u32 - current + 1);
-                    let maxblock = Self::largest_power_of_2_le(maxsize);
-                    let prefix_len = 32 - (maxblock.trailing_zeros() as u8);
-
-                    let network_addr = IPAddress::new_v4(std::net::Ipv4Addr::from(current));
-                    let network = IPNetwork::new(network_addr, prefix_len)?;
-                    cidrs.push(network);
-
-                    current += maxblock;
-                    if current == 0 {
-                        break; // Overflow protection
-                    }
-                }
-            }
-            (std::net::IpAddr::V6(start), std::net::IpAddr::V6(end)) => {
-                let mut current = u128::from(*start);
-                let end_u128 = u128::from(*end);
-
-                while current <= end_u128 {
-                    let remaining = end_u128 - current + 1;
-                    let maxblock = Self::largest_power_of_2_le_u128(remaining);
-                    let prefix_len = 128 - (maxblock.trailing_zeros() as u8);
-
-                    // Align to block boundary if needed
-                    let aligned_current = current & !(maxblock - 1);
-                    if aligned_current != current {
-                        let smaller_block = Self::largest_power_of_2_le_u128(current - aligned_current);
-                        let smaller_prefix = 128 - (smaller_block.trailing_zeros() as u8);
-
-                        let network_addr = IPAddress::new_v6(std::net::Ipv6Addr::from(current));
-                        let network = IPNetwork::new(network_addr, smaller_prefix)?;
-                        cidrs.push(network);
-
-                        current += smaller_block;
-                        continue;
-                    }
-
-                    let network_addr = IPAddress::new_v6(std::net::Ipv6Addr::from(current));
-                    let network = IPNetwork::new(network_addr, prefix_len)?;
-                    cidrs.push(network);
-
-                    current += maxblock;
-                    if current == 0 {
-                        break; // Overflow protection
-                    }
-                }
-            }
-            _ => unreachable!(),
+        Ok(self.iter_cidrs().collect())
+    }
+
+    /// Lazily decompose this range into the minimal list of CIDR blocks
+    ///
+    /// Each block is computed arithmetically from the alignment of the current
+    /// address and the number of addresses left, so even `::/0` takes a
+    /// single step. The blocks are built from address values alone, so they
+    /// are never zoned.
+    pub fn iter_cidrs(&self) -> CidrIterator {
+        let (start, end, width) = self.bounds();
+        CidrIterator {
+            current: Some(start),
+            end,
+            width,
         }
-
-        Ok(cidrs)
     }
 
     /// Get an iterator over all IP addresses in this range
@@ -170,46 +142,6 @@ impl IPRange {
         RangeHostIterator::new(self)
     }
 
-    /// Calculate maximum block size for IPv4
-    fn maxblock_ipv4(address: u32, max_size: u32) -> u32 {
-        let mut maxsize = 1;
-
-        // Find the largest power of 2 that:
-        // 1. Is <= max_size
-        // 2. Address is aligned to it
-        while maxsize <= max_size && (address & (maxsize - 1)) == 0 {
-            maxsize <<= 1;
-        }
-
-        maxsize >> 1
-    }
-
-    /// Find the largest power of 2 that is <= n
-    fn largest_power_of_2_le(n: u32) -> u32 {
-        if n == 0 {
-            return 0;
-        }
-
-        let mut power = 1;
-        while power <= n {
-            power <<= 1;
-        }
-        power >> 1
-    }
-
-    /// Find the largest power of 2 that is <= n (u128 version)
-    fn largest_power_of_2_le_u128(n: u128) -> u128 {
-        if n == 0 {
-            return 0;
-        }
-
-        let mut power = 1;
-        while power <= n {
-            power <<= 1;
-        }
-        power >> 1
-    }
-
     /// Create a range from a hyphen-separated string (e.g., "192.168.1.1-192.168.1.10")
     pub fn from_hyphen_string(s: &str) -> AddrResult<Self> {
         let parts: Vec<&str> = s.split('-').map(|s| s.trim()).collect();
@@ -293,6 +225,69 @@ impl Ord for IPRange {
     }
 }
 
+impl From<IPNetwork> for IPRange {
+    fn from(network: IPNetwork) -> Self {
+        IPRange::from(&network)
+    }
+}
+
+/// The zone of a zoned network is dropped, since ranges cannot hold one
+impl From<&IPNetwork> for IPRange {
+    fn from(network: &IPNetwork) -> Self {
+        IPRange::new_unchecked(network.network_address().without_zone(), network.last_address().without_zone())
+    }
+}
+
+/// Reject a zoned address, which ranges and sets cannot hold
+pub(crate) fn check_unzoned(addr: &IPAddress) -> AddrResult<()> {
+    match addr.zone() {
+        Some(_) => Err(AddrFormatError::new(format!(
+            "Zoned address {} cannot be part of an IP range or set",
+            addr
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Iterator over the CIDR blocks covering a range, in address order
+pub struct CidrIterator {
+    current: Option<u128>,
+    end: u128,
+    width: u32,
+}
+
+impl Iterator for CidrIterator {
+    type Item = IPNetwork;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.current?;
+
+        // The block may not be larger than the alignment of its first address...
+        let align_bits = if current == 0 {
+            self.width
+        } else {
+            current.trailing_zeros().min(self.width)
+        };
+        // ...nor larger than the number of addresses left (span + 1)
+        let span = self.end - current;
+        let fit_bits = match span.checked_add(1) {
+            Some(remaining) => 127 - remaining.leading_zeros(),
+            None => 128,
+        };
+        let host_bits = align_bits.min(fit_bits);
+
+        let last = current | u128::MAX.checked_shr(128 - host_bits).unwrap_or(0);
+        self.current = if last == self.end { None } else { Some(last + 1) };
+
+        let address = if self.width == 32 {
+            IPAddress::new_v4(std::net::Ipv4Addr::from(current as u32))
+        } else {
+            IPAddress::new_v6(std::net::Ipv6Addr::from(current))
+        };
+        Some(IPNetwork::new_unchecked(address, (self.width - host_bits) as u8))
+    }
+}
+
 /// Iterator over host addresses in a range
 pub struct RangeHostIterator {
     current: Option<IPAddress>,
@@ -426,6 +421,25 @@ pub fn merge_ranges(ranges: &[IPRange]) -> AddrResult<Vec<IPRange>> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_zoned_ranges() {
+        let zoned = IPAddress::from_str("fe80::1%eth0").unwrap();
+        let end = IPAddress::from_str("fe80::5").unwrap();
+        assert!(IPRange::new(zoned.clone(), end.clone()).is_err());
+        assert!(IPRange::from_str("fe80::1%eth0-fe80::5%eth0").is_err());
+
+        let range = IPRange::new(zoned.without_zone(), end).unwrap();
+        assert!(range.contains(&IPAddress::from_str("fe80::3").unwrap()));
+        assert!(!range.contains(&IPAddress::from_str("fe80::3%eth0").unwrap()));
+
+        // A zoned network's range and blocks drop the zone
+        let network = IPNetwork::from_str("fe80::%eth0/126").unwrap();
+        let range = IPRange::from(&network);
+        assert_eq!(range.to_string(), "fe80::-fe80::3");
+        let cidrs: Vec<String> = range.iter_cidrs().map(|cidr| cidr.to_string()).collect();
+        assert_eq!(cidrs, ["fe80::/126"]);
+    }
+
     #[test]
     fn test_range_creation() {
         let start = IPAddress::from_str("192.168.1.1").unwrap();
@@ -490,6 +504,34 @@ mod tests {
         assert!(cidrs.len() > 1); // Should be broken into multiple CIDRs
     }
 
+    #[test]
+    fn test_full_space_cidrs() {
+        let range = IPRange::from_str("::-ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff").unwrap();
+        assert_eq!(range.size(), AddressCount::power_of_two(128));
+        let cidrs = range.to_cidrs().unwrap();
+        assert_eq!(cidrs.len(), 1);
+        assert_eq!(cidrs[0].to_string(), "::/0");
+
+        let range = IPRange::from_str("0.0.0.0-255.255.255.255").unwrap();
+        assert_eq!(range.size(), 1u128 << 32);
+        assert_eq!(range.to_cidrs().unwrap()[0].to_string(), "0.0.0.0/0");
+
+        // Everything but the first and last address
+        let range = IPRange::from_str("::1-ffff:ffff:ffff:ffff:ffff:ffff:ffff:fffe").unwrap();
+        assert_eq!(range.size(), u128::MAX - 1);
+        let cidrs = range.to_cidrs().unwrap();
+        assert_eq!(cidrs.len(), 254);
+        assert_eq!(cidrs[0].to_string(), "::1/128");
+        assert_eq!(cidrs[127].to_string(), "8000::/2");
+        assert_eq!(cidrs[253].to_string(), "ffff:ffff:ffff:ffff:ffff:ffff:ffff:fffe/128");
+        let total: AddressCount = cidrs.iter().map(|c| c.num_addresses()).sum();
+        assert_eq!(total, range.size());
+
+        let range = IPRange::from_str("192.168.1.1-192.168.1.10").unwrap();
+        let cidrs: Vec<String> = range.iter_cidrs().map(|c| c.to_string()).collect();
+        assert_eq!(cidrs, vec!["192.168.1.1/32", "192.168.1.2/31", "192.168.1.4/30", "192.168.1.8/31", "192.168.1.10/32"]);
+    }
+
     #[test]
     fn test_range_iterator() {
         let range = IPRange::from_str("192.168.1.1-192.168.1.3").unwrap();
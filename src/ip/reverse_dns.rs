@@ -0,0 +1,408 @@
+//! Reverse DNS zones for networks
+//!
+//! Reverse zones are delegated on label boundaries: octets under
+//! `in-addr.arpa` and nibbles under `ip6.arpa`. A network that does not end
+//! on a boundary is split into the zones one boundary further down, so a /22
+//! becomes four /24 zones and a /50 becomes four /52 zones.
+//!
+//! IPv4 blocks longer than /24 cannot have a zone of their own. RFC 2317
+//! works around this: the /24 zone holds a CNAME for each address, pointing
+//! into a child zone named after the block, which is delegated to the block's
+//! holder.
+
+use crate::error::{AddrFormatError, AddrResult};
+use crate::ip::{IPAddress, IPNetwork};
+use crate::sets::IPSet;
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+
+/// Get the reversed octet (IPv4) or nibble (IPv6) labels for the first `bits`
+/// bits of an address, joined by dots
+///
+/// `bits` is rounded down to a whole label.
+pub(crate) fn reverse_labels(addr: &IPAddress, bits: u8) -> String {
+    let mut labels: Vec<String> = if addr.is_ipv4() {
+        addr.to_u128().to_be_bytes()[12..12 + bits as usize / 8]
+            .iter()
+            .map(|octet| octet.to_string())
+            .collect()
+    } else {
+        format!("{:032x}", addr.to_u128())
+            .chars()
+            .take(bits as usize / 4)
+            .map(String::from)
+            .collect()
+    };
+    labels.reverse();
+    labels.join(".")
+}
+
+/// Parse a full or partial reverse DNS name into the network it names
+///
+/// `10.in-addr.arpa` gives `10.0.0.0/8` and `8.b.d.0.1.0.0.2.ip6.arpa` gives
+/// `2001:db8::/32`. A trailing dot is allowed and the suffix and hex digits
+/// are case-insensitive, but every label must be a decimal octet without
+/// leading zeros (IPv4) or a single hex digit (IPv6).
+pub fn parse_reverse_name(name: &str) -> AddrResult<IPNetwork> {
+    let invalid = |reason: String| AddrFormatError::new(format!("Invalid reverse DNS name '{}': {}", name, reason));
+
+    let trimmed = name.strip_suffix('.').unwrap_or(name);
+    let lower = trimmed.to_ascii_lowercase();
+    let (is_ipv4, labels) = if let Some(labels) = strip_zone_suffix(&lower, "in-addr.arpa") {
+        (true, labels)
+    } else if let Some(labels) = strip_zone_suffix(&lower, "ip6.arpa") {
+        (false, labels)
+    } else {
+        return Err(invalid("expected a name ending in in-addr.arpa or ip6.arpa".to_string()));
+    };
+
+    let (max_labels, label_bits) = if is_ipv4 { (4, 8) } else { (32, 4) };
+    if labels.len() > max_labels {
+        return Err(invalid(format!("{} labels, at most {} are allowed", labels.len(), max_labels)));
+    }
+
+    // Labels are least significant first; fill the value from the most significant end
+    let mut value = 0u128;
+    for (position, label) in labels.iter().rev().enumerate() {
+        let digit = if is_ipv4 { parse_octet_label(label) } else { parse_nibble_label(label) }.ok_or_else(|| {
+            let expected = if is_ipv4 { "a decimal octet 0-255" } else { "a single hex digit" };
+            invalid(format!("label '{}' is not {}", label, expected))
+        })?;
+        let width = if is_ipv4 { 32 } else { 128 };
+        value |= (digit as u128) << (width - label_bits * (position as u32 + 1));
+    }
+
+    let address = if is_ipv4 {
+        IPAddress::new_v4(Ipv4Addr::from(value as u32))
+    } else {
+        IPAddress::new_v6(Ipv6Addr::from(value))
+    };
+    IPNetwork::new(address, (labels.len() as u32 * label_bits) as u8)
+}
+
+/// Strip a zone suffix at a label boundary, returning the labels before it
+///
+/// A name like `.in-addr.arpa` gives a single empty label.
+fn strip_zone_suffix<'a>(name: &'a str, suffix: &str) -> Option<Vec<&'a str>> {
+    if name == suffix {
+        return Some(Vec::new());
+    }
+    Some(name.strip_suffix(suffix)?.strip_suffix('.')?.split('.').collect())
+}
+
+fn parse_octet_label(label: &str) -> Option<u8> {
+    let is_canonical = !label.is_empty()
+        && label.bytes().all(|b| b.is_ascii_digit())
+        && (label == "0" || !label.starts_with('0'));
+    if is_canonical {
+        label.parse().ok()
+    } else {
+        None
+    }
+}
+
+fn parse_nibble_label(label: &str) -> Option<u8> {
+    match label.as_bytes() {
+        [digit] => (*digit as char).to_digit(16).map(|d| d as u8),
+        _ => None,
+    }
+}
+
+/// A reverse DNS zone, such as `2.0.192.in-addr.arpa` for `192.0.2.0/24`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ReverseZone {
+    network: IPNetwork,
+}
+
+impl ReverseZone {
+    /// Create the zone for a network that ends on an octet (IPv4) or nibble (IPv6) boundary
+    pub fn new(network: IPNetwork) -> AddrResult<Self> {
+        let label_bits = if network.is_ipv4() { 8 } else { 4 };
+        if !network.prefix_length().is_multiple_of(label_bits) {
+            return Err(AddrFormatError::new(format!(
+                "{} does not end on a {}-bit reverse DNS label boundary",
+                network, label_bits
+            )));
+        }
+        Ok(Self { network })
+    }
+
+    /// Get the network the zone covers
+    pub fn network(&self) -> &IPNetwork {
+        &self.network
+    }
+
+    /// Get the zone name, without a trailing dot
+    pub fn name(&self) -> String {
+        let suffix = if self.network.is_ipv4() { "in-addr.arpa" } else { "ip6.arpa" };
+        let labels = reverse_labels(self.network.network_address(), self.network.prefix_length());
+        if labels.is_empty() {
+            suffix.to_string()
+        } else {
+            format!("{}.{}", labels, suffix)
+        }
+    }
+
+    /// Check if an address falls within the zone
+    pub fn contains(&self, addr: &IPAddress) -> bool {
+        self.network.contains(addr)
+    }
+}
+
+impl FromStr for ReverseZone {
+    type Err = AddrFormatError;
+
+    /// Parse a zone name, such as `2.0.192.in-addr.arpa`
+    fn from_str(s: &str) -> AddrResult<Self> {
+        Ok(Self {
+            network: parse_reverse_name(s)?,
+        })
+    }
+}
+
+impl fmt::Display for ReverseZone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Get the minimal reverse zones covering a network
+///
+/// IPv4 networks longer than /24 give the /24 zone that holds them; see
+/// [`rfc2317_delegation`] for delegating just the block.
+pub fn reverse_zones(network: &IPNetwork) -> Vec<ReverseZone> {
+    let (label_bits, max_zone_prefix) = if network.is_ipv4() { (8, 24) } else { (4, 128) };
+    let prefix = network.prefix_length();
+
+    let zone_prefix = prefix.div_ceil(label_bits) * label_bits;
+    if zone_prefix > max_zone_prefix {
+        let parent = IPNetwork::new(network.network_address().clone(), max_zone_prefix)
+            .expect("a shorter prefix is valid");
+        return vec![ReverseZone { network: parent }];
+    }
+    if zone_prefix == prefix {
+        return vec![ReverseZone { network: network.clone() }];
+    }
+
+    network
+        .subnets(zone_prefix)
+        .expect("the zone prefix is longer than the network prefix")
+        .map(|network| ReverseZone { network })
+        .collect()
+}
+
+/// Get the minimal reverse zones covering a set, in address order
+pub fn set_reverse_zones(set: &IPSet) -> Vec<ReverseZone> {
+    let mut zones: Vec<ReverseZone> = set
+        .iter_ranges()
+        .flat_map(|range| range.iter_cidrs())
+        .flat_map(|network| reverse_zones(&network))
+        .collect();
+    zones.sort();
+    zones.dedup();
+    zones
+}
+
+/// How the child zone of an RFC 2317 delegation is labelled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ClasslessLabel {
+    /// First address and prefix length, as in RFC 2317: `0/26`
+    #[default]
+    Slash,
+    /// First and last address: `0-63`, for tools that reject `/` in names
+    Range,
+}
+
+/// A CNAME record in a reverse zone
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CnameRecord {
+    /// The owner name, without a trailing dot
+    pub name: String,
+    /// The canonical name, without a trailing dot
+    pub target: String,
+}
+
+impl fmt::Display for CnameRecord {
+    /// Format as a zone file line with absolute names
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}. IN CNAME {}.", self.name, self.target)
+    }
+}
+
+/// An RFC 2317 classless delegation of an IPv4 block longer than /24
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClasslessDelegation {
+    network: IPNetwork,
+    parent_zone: ReverseZone,
+    child_zone: String,
+}
+
+impl ClasslessDelegation {
+    /// Get the delegated block
+    pub fn network(&self) -> &IPNetwork {
+        &self.network
+    }
+
+    /// Get the /24 zone that holds the CNAME records
+    pub fn parent_zone(&self) -> &ReverseZone {
+        &self.parent_zone
+    }
+
+    /// Get the name of the delegated child zone, such as `0/26.2.0.192.in-addr.arpa`
+    pub fn child_zone(&self) -> &str {
+        &self.child_zone
+    }
+
+    /// Get the CNAME record for an address in the block
+    pub fn cname_record(&self, addr: &IPAddress) -> Option<CnameRecord> {
+        if self.network.contains(addr) {
+            Some(self.record_for_octet(addr.to_u128() as u8))
+        } else {
+            None
+        }
+    }
+
+    /// Get the CNAME records for every address in the block, for the parent zone
+    pub fn cname_records(&self) -> Vec<CnameRecord> {
+        let first = self.network.network_address().to_u128() as u8;
+        let last = self.network.last_address().to_u128() as u8;
+        (first..=last).map(|octet| self.record_for_octet(octet)).collect()
+    }
+
+    fn record_for_octet(&self, octet: u8) -> CnameRecord {
+        CnameRecord {
+            name: format!("{}.{}", octet, self.parent_zone),
+            target: format!("{}.{}", octet, self.child_zone),
+        }
+    }
+}
+
+/// Get the RFC 2317 delegation of an IPv4 block longer than /24
+pub fn rfc2317_delegation(network: &IPNetwork, label: ClasslessLabel) -> AddrResult<ClasslessDelegation> {
+    if !network.is_ipv4() || network.prefix_length() <= 24 {
+        return Err(AddrFormatError::new(format!(
+            "RFC 2317 delegation needs an IPv4 block longer than /24, not {}",
+            network
+        )));
+    }
+
+    let parent_zone = reverse_zones(network).remove(0);
+    let first = network.network_address().to_u128() as u8;
+    let last = network.last_address().to_u128() as u8;
+    let child_label = match label {
+        ClasslessLabel::Slash => format!("{}/{}", first, network.prefix_length()),
+        ClasslessLabel::Range => format!("{}-{}", first, last),
+    };
+
+    Ok(ClasslessDelegation {
+        network: network.clone(),
+        child_zone: format!("{}.{}", child_label, parent_zone),
+        parent_zone,
+    })
+}
+
+/// Get the RFC 2317 delegations for the IPv4 blocks longer than /24 in a set
+pub fn set_rfc2317_delegations(set: &IPSet, label: ClasslessLabel) -> Vec<ClasslessDelegation> {
+    set.iter_ranges()
+        .flat_map(|range| range.iter_cidrs())
+        .filter(|network| network.is_ipv4() && network.prefix_length() > 24)
+        .map(|network| rfc2317_delegation(&network, label).expect("the block was checked"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn zone_names(network: &str) -> Vec<String> {
+        reverse_zones(&IPNetwork::from_str(network).unwrap())
+            .iter()
+            .map(ReverseZone::name)
+            .collect()
+    }
+
+    #[test]
+    fn test_ipv4_zones() {
+        assert_eq!(zone_names("192.0.2.0/24"), ["2.0.192.in-addr.arpa"]);
+        assert_eq!(zone_names("10.0.0.0/8"), ["10.in-addr.arpa"]);
+        assert_eq!(zone_names("0.0.0.0/0"), ["in-addr.arpa"]);
+        assert_eq!(
+            zone_names("198.51.100.0/22"),
+            ["100.51.198.in-addr.arpa", "101.51.198.in-addr.arpa", "102.51.198.in-addr.arpa", "103.51.198.in-addr.arpa"]
+        );
+        assert_eq!(zone_names("192.0.2.64/26"), ["2.0.192.in-addr.arpa"]);
+    }
+
+    #[test]
+    fn test_ipv6_zones() {
+        assert_eq!(zone_names("2001:db8::/32"), ["8.b.d.0.1.0.0.2.ip6.arpa"]);
+        assert_eq!(zone_names("2001:db8::/34"), ["0.8.b.d.0.1.0.0.2.ip6.arpa", "1.8.b.d.0.1.0.0.2.ip6.arpa", "2.8.b.d.0.1.0.0.2.ip6.arpa", "3.8.b.d.0.1.0.0.2.ip6.arpa"]);
+        assert_eq!(zone_names("::/0"), ["ip6.arpa"]);
+        assert!(ReverseZone::new(IPNetwork::from_str("2001:db8::/33").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_set_zones() {
+        let set: IPSet = ["192.0.2.0/26", "192.0.2.192/26", "192.0.3.0/24", "2001:db8::/48"]
+            .iter()
+            .map(|s| IPNetwork::from_str(s).unwrap())
+            .collect();
+        let zones: Vec<String> = set_reverse_zones(&set).iter().map(ReverseZone::name).collect();
+        assert_eq!(zones, ["2.0.192.in-addr.arpa", "3.0.192.in-addr.arpa", "0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa"]);
+
+        let delegations = set_rfc2317_delegations(&set, ClasslessLabel::Range);
+        assert_eq!(delegations.len(), 2);
+        assert_eq!(delegations[1].child_zone(), "192-255.2.0.192.in-addr.arpa");
+    }
+
+    #[test]
+    fn test_parse_reverse_name() {
+        for (name, network) in [
+            ("10.in-addr.arpa", "10.0.0.0/8"),
+            ("2.0.192.in-addr.arpa.", "192.0.2.0/24"),
+            ("in-addr.arpa", "0.0.0.0/0"),
+            ("8.b.d.0.1.0.0.2.ip6.arpa", "2001:db8::/32"),
+            ("8.B.D.0.1.0.0.2.IP6.ARPA.", "2001:db8::/32"),
+        ] {
+            assert_eq!(parse_reverse_name(name).unwrap().to_string(), network, "{}", name);
+        }
+        assert_eq!(ReverseZone::from_str("2.0.192.in-addr.arpa").unwrap().name(), "2.0.192.in-addr.arpa");
+
+        for (name, label) in [
+            ("256.2.0.192.in-addr.arpa", "'256'"),
+            ("02.0.192.in-addr.arpa", "'02'"),
+            ("1..0.192.in-addr.arpa", "''"),
+            (".in-addr.arpa", "''"),
+            (".ip6.arpa.", "''"),
+            ("8.bd.0.1.0.0.2.ip6.arpa", "'bd'"),
+            ("g.ip6.arpa", "'g'"),
+        ] {
+            let err = parse_reverse_name(name).unwrap_err().to_string();
+            assert!(err.contains(&format!("label {}", label)), "{}: {}", name, err);
+        }
+        assert!(parse_reverse_name("1.1.1.1.1.in-addr.arpa").unwrap_err().to_string().contains("5 labels"));
+        assert!(parse_reverse_name("2.0.192.xin-addr.arpa").is_err());
+        assert!(parse_reverse_name("example.com").is_err());
+    }
+
+    #[test]
+    fn test_rfc2317_delegation() {
+        let network = IPNetwork::from_str("192.0.2.64/26").unwrap();
+        let delegation = rfc2317_delegation(&network, ClasslessLabel::Slash).unwrap();
+        assert_eq!(delegation.parent_zone().name(), "2.0.192.in-addr.arpa");
+        assert_eq!(delegation.child_zone(), "64/26.2.0.192.in-addr.arpa");
+
+        let records = delegation.cname_records();
+        assert_eq!(records.len(), 64);
+        assert_eq!(records[1].to_string(), "65.2.0.192.in-addr.arpa. IN CNAME 65.64/26.2.0.192.in-addr.arpa.");
+
+        let host = IPNetwork::from_str("192.0.2.7/32").unwrap();
+        let delegation = rfc2317_delegation(&host, ClasslessLabel::Range).unwrap();
+        assert_eq!(delegation.child_zone(), "7-7.2.0.192.in-addr.arpa");
+        assert_eq!(delegation.cname_records().len(), 1);
+
+        assert!(rfc2317_delegation(&IPNetwork::from_str("192.0.2.0/24").unwrap(), ClasslessLabel::Slash).is_err());
+    }
+}
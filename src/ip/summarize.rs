@@ -0,0 +1,292 @@
+//! Route summarization with a tolerance for over-coverage
+//!
+//! `cidr_merge` only aggregates exactly. Route summarization usually trades
+//! precision for table size: covering `10.0.0.0/24` and `10.0.2.0/24` with
+//! `10.0.0.0/22` swallows the unused `10.0.1.0/24` and `10.0.3.0/24`.
+//! `summarize_cidrs` finds the fewest prefixes within a limit on both the
+//! number of prefixes and the number of extra addresses.
+
+use crate::error::{AddrFormatError, AddrResult};
+use crate::ip::{AddressCount, IPAddress, IPNetwork};
+use crate::sets::IPSet;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+/// A summary prefix and the address space it covers beyond its inputs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CidrSummary {
+    network: IPNetwork,
+    extra: Vec<IPNetwork>,
+}
+
+impl CidrSummary {
+    /// Get the summary prefix
+    pub fn network(&self) -> &IPNetwork {
+        &self.network
+    }
+
+    /// Get the address space covered by the summary but by none of the inputs
+    pub fn extra(&self) -> &[IPNetwork] {
+        &self.extra
+    }
+
+    /// Get the number of extra addresses
+    pub fn extra_size(&self) -> AddressCount {
+        self.extra.iter().map(IPNetwork::num_addresses).sum()
+    }
+
+    /// Check if the summary covers only input addresses
+    pub fn is_exact(&self) -> bool {
+        self.extra.is_empty()
+    }
+}
+
+/// Summarize networks into the fewest prefixes covering them all
+///
+/// At most `max_prefixes` prefixes are used, and together they may cover at
+/// most `extra_budget` addresses that are not in any input network. Among the
+/// summaries with the fewest prefixes, the one with the least extra space is
+/// chosen. IPv4 summaries come before IPv6, each in address order. With a
+/// budget of zero the result matches `cidr_merge`, as far as `max_prefixes`
+/// allows.
+///
+/// Returns an error if no summary meets both limits.
+pub fn summarize_cidrs(
+    networks: &[IPNetwork],
+    max_prefixes: usize,
+    extra_budget: u128,
+) -> AddrResult<Vec<CidrSummary>> {
+    let input: IPSet = networks.iter().cloned().collect();
+    let (ipv4, ipv6) = input.split_by_version();
+    let tries: Vec<Node> = [(ipv4, 32), (ipv6, 128)]
+        .into_iter()
+        .filter(|(set, _)| !set.is_empty())
+        .map(|(set, width)| {
+            let blocks: Vec<Block> = set
+                .iter_ranges()
+                .flat_map(|range| range.iter_cidrs())
+                .map(|network| Block::from_network(&network, width))
+                .collect();
+            Node::build(&blocks, width, max_prefixes)
+        })
+        .collect();
+
+    if tries.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Combine the per-family costs: the cheapest way to spend k prefixes overall
+    let cap = tries.iter().map(|node| node.cost.len()).sum::<usize>().min(max_prefixes);
+    let total_cost = |k: usize| -> Option<(u128, Vec<usize>)> {
+        match tries.as_slice() {
+            [only] => only.cost_with(k).map(|cost| (cost, vec![k])),
+            [v4, v6] => (1..k)
+                .filter_map(|i| Some((v4.cost_with(i)?.saturating_add(v6.cost_with(k - i)?), vec![i, k - i])))
+                .min_by_key(|(cost, _)| *cost),
+            _ => unreachable!("one trie per address family"),
+        }
+    };
+
+    let mut best_cost = None;
+    for k in 1..=cap {
+        if let Some((cost, split)) = total_cost(k) {
+            if cost <= extra_budget {
+                let mut prefixes = Vec::new();
+                for (node, k) in tries.iter().zip(split) {
+                    node.collect(k, &mut prefixes);
+                }
+                return Ok(prefixes
+                    .into_iter()
+                    .map(|network| {
+                        let extra = IPSet::from_network(network.clone())
+                            .and_then(|summary| summary.difference(&input))
+                            .and_then(|extra| extra.networks())
+                            .expect("summaries are valid networks");
+                        CidrSummary { network, extra }
+                    })
+                    .collect());
+            }
+            best_cost = Some(best_cost.map_or(cost, |best: u128| best.min(cost)));
+        }
+    }
+
+    Err(AddrFormatError::new(match best_cost {
+        Some(cost) => format!(
+            "Cannot summarize into {} prefixes within {} extra addresses (needs {})",
+            max_prefixes, extra_budget, cost
+        ),
+        None => format!("Cannot summarize into {} prefixes", max_prefixes),
+    }))
+}
+
+/// A prefix as a left-aligned key and length, within an address family of `width` bits
+#[derive(Debug, Clone, Copy)]
+struct Block {
+    key: u128,
+    bits: u8,
+    width: u8,
+}
+
+impl Block {
+    fn from_network(network: &IPNetwork, width: u8) -> Self {
+        Self {
+            key: network.network_address().to_u128() << (128 - width as u32),
+            bits: network.prefix_length(),
+            width,
+        }
+    }
+
+    fn to_network(self) -> IPNetwork {
+        let value = self.key >> (128 - self.width as u32);
+        let address = if self.width == 32 {
+            IPAddress::new_v4(Ipv4Addr::from(value as u32))
+        } else {
+            IPAddress::new_v6(Ipv6Addr::from(value))
+        };
+        IPNetwork::new_unchecked(address, self.bits)
+    }
+
+    /// The last key covered by the block
+    fn last_key(self) -> u128 {
+        self.key | u128::MAX.checked_shr(self.bits as u32).unwrap_or(0)
+    }
+}
+
+/// A node of the path-compressed trie over the merged input blocks
+///
+/// Leaves are input blocks; each inner node is the smallest prefix holding
+/// both of its subtrees. Only these prefixes can be part of a cheapest
+/// summary, as any other prefix swallows more space for the same coverage.
+struct Node {
+    block: Block,
+    children: Option<Box<[Node; 2]>>,
+    /// `cost[k - 1]` is the least extra space needed to cover the subtree
+    /// with at most `k` prefixes
+    cost: Vec<u128>,
+}
+
+impl Node {
+    /// Build the trie over sorted, disjoint blocks, keeping costs for up to `cap` prefixes
+    fn build(blocks: &[Block], width: u8, cap: usize) -> Node {
+        if let [block] = blocks {
+            return Node {
+                block: *block,
+                children: None,
+                cost: vec![0],
+            };
+        }
+
+        let first = blocks[0].key;
+        let last = blocks[blocks.len() - 1].last_key();
+        let bits = (first ^ last).leading_zeros() as u8;
+        let split_bit = 1u128 << (127 - bits as u32);
+        let split = blocks.partition_point(|block| block.key & split_bit == 0);
+        let left = Node::build(&blocks[..split], width, cap);
+        let right = Node::build(&blocks[split..], width, cap);
+
+        // Space in each half of this prefix outside the child subtree, plus
+        // the gaps within the subtrees themselves
+        let half = 1u128 << (width - bits - 1);
+        let gap = |child: &Node| half - (1u128 << (width - child.block.bits)) + child.cost[0];
+        let mut cost = vec![gap(&left).saturating_add(gap(&right))];
+        for k in 2..=(left.cost.len() + right.cost.len()).min(cap) {
+            let split_cost = (1..k)
+                .filter_map(|i| Some(left.cost_with(i)?.saturating_add(right.cost_with(k - i)?)))
+                .min()
+                .expect("both subtrees take at least one prefix");
+            cost.push(split_cost.min(cost[k - 2]));
+        }
+
+        let block = Block {
+            key: first & !u128::MAX.checked_shr(bits as u32).unwrap_or(0),
+            bits,
+            width,
+        };
+        Node {
+            block,
+            children: Some(Box::new([left, right])),
+            cost,
+        }
+    }
+
+    /// Get the least extra space to cover the subtree with at most `k` prefixes
+    fn cost_with(&self, k: usize) -> Option<u128> {
+        match k {
+            0 => None,
+            k => Some(self.cost[k.min(self.cost.len()) - 1]),
+        }
+    }
+
+    /// Collect the prefixes of a cheapest cover with at most `k` prefixes
+    fn collect(&self, k: usize, prefixes: &mut Vec<IPNetwork>) {
+        let target = self.cost_with(k).expect("at least one prefix");
+        if let (Some([left, right]), false) = (self.children.as_deref(), target == self.cost[0]) {
+            let i = (1..k)
+                .find(|&i| {
+                    let cost = left.cost_with(i).zip(right.cost_with(k - i));
+                    cost.is_some_and(|(l, r)| l.saturating_add(r) == target)
+                })
+                .expect("the cost was computed from some split");
+            left.collect(i, prefixes);
+            right.collect(k - i, prefixes);
+        } else {
+            prefixes.push(self.block.to_network());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn networks(list: &[&str]) -> Vec<IPNetwork> {
+        list.iter().map(|s| IPNetwork::from_str(s).unwrap()).collect()
+    }
+
+    fn summary_strings(summaries: &[CidrSummary]) -> Vec<String> {
+        summaries.iter().map(|s| s.network().to_string()).collect()
+    }
+
+    #[test]
+    fn test_exact_summary() {
+        let input = networks(&["10.0.0.0/24", "10.0.1.0/24", "10.0.3.0/24"]);
+        let summaries = summarize_cidrs(&input, 10, 0).unwrap();
+        assert_eq!(summary_strings(&summaries), ["10.0.0.0/23", "10.0.3.0/24"]);
+        assert!(summaries.iter().all(CidrSummary::is_exact));
+
+        assert!(summarize_cidrs(&input, 1, 0).is_err());
+        assert!(summarize_cidrs(&[], 0, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_summary_with_budget() {
+        let input = networks(&["10.0.0.0/24", "10.0.1.0/24", "10.0.3.0/24"]);
+        let summaries = summarize_cidrs(&input, 1, 256).unwrap();
+        assert_eq!(summary_strings(&summaries), ["10.0.0.0/22"]);
+        assert_eq!(summaries[0].extra(), networks(&["10.0.2.0/24"]).as_slice());
+        assert_eq!(summaries[0].extra_size(), 256);
+
+        let err = summarize_cidrs(&input, 1, 255).unwrap_err();
+        assert!(err.to_string().contains("needs 256"));
+
+        // Two prefixes fit in a small budget by swallowing the cheaper gap
+        let input = networks(&["10.0.0.0/26", "10.0.0.128/26", "10.0.1.0/24", "10.0.8.0/24"]);
+        let summaries = summarize_cidrs(&input, 2, 1000).unwrap();
+        assert_eq!(summary_strings(&summaries), ["10.0.0.0/23", "10.0.8.0/24"]);
+        assert_eq!(summaries[0].extra_size(), 128);
+        assert_eq!(summaries[1].extra_size(), 0);
+    }
+
+    #[test]
+    fn test_mixed_families() {
+        let input = networks(&["2001:db8::/48", "2001:db8:2::/48", "192.0.2.0/25", "192.0.2.128/26"]);
+        let summaries = summarize_cidrs(&input, 2, (2 << 80) + 64).unwrap();
+        assert_eq!(summary_strings(&summaries), ["192.0.2.0/24", "2001:db8::/46"]);
+        assert_eq!(summaries[1].extra_size(), 2u128 << 80);
+        assert!(summarize_cidrs(&input, 2, 2 << 80).is_err());
+        assert_eq!(summarize_cidrs(&input, 3, 64).unwrap().len(), 3);
+
+        let full = summarize_cidrs(&networks(&["::/1", "8000::/1"]), 1, 0).unwrap();
+        assert_eq!(summary_strings(&full), ["::/0"]);
+    }
+}
@@ -0,0 +1,287 @@
+//! Addresses of the IPv6 transition mechanisms that carry IPv4 addresses
+//!
+//! 6to4 (RFC 3056) and ISATAP (RFC 5214) addresses are decoded by
+//! [`IPv6::six_to_four_ipv4`] and [`IPv6::isatap_ipv4`]. Teredo (RFC 4380)
+//! packs more than an address, and 6rd (RFC 5969) depends on the operator's
+//! configuration, so both get their own types here.
+
+use crate::error::{AddrFormatError, AddrResult};
+use crate::ip::ipv4::IPv4;
+use crate::ip::ipv6::IPv6;
+use crate::ip::{IPAddress, IPNetwork};
+use std::fmt;
+use std::net::Ipv6Addr;
+
+/// The parts of a Teredo address (2001::/32)
+///
+/// The client's port and IPv4 address are stored in the IPv6 address with
+/// every bit inverted; this type holds them as the client's NAT sees them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TeredoAddress {
+    server: IPv4,
+    flags: u16,
+    client_port: u16,
+    client: IPv4,
+}
+
+impl TeredoAddress {
+    /// The flag set when the client is behind a cone NAT
+    pub const CONE_FLAG: u16 = 0x8000;
+
+    /// Create from the server address, flags and the client's mapped address and port
+    pub fn new(server: IPv4, flags: u16, client: IPv4, client_port: u16) -> Self {
+        Self {
+            server,
+            flags,
+            client_port,
+            client,
+        }
+    }
+
+    /// Decode a Teredo address, or return `None` if it is outside 2001::/32
+    pub fn decode(addr: &IPv6) -> Option<Self> {
+        if !addr.is_teredo() {
+            return None;
+        }
+        let segments = addr.segments();
+        Some(Self {
+            server: IPv4::from_u32(addr.network_prefix() as u32),
+            flags: segments[4],
+            client_port: !segments[5],
+            client: IPv4::from_u32(!(addr.interface_id() as u32)),
+        })
+    }
+
+    /// Encode as an IPv6 address
+    pub fn to_ipv6(&self) -> IPv6 {
+        let network_prefix = (0x2001u64 << 48) | self.server.to_u32() as u64;
+        let interface_id = ((self.flags as u64) << 48)
+            | ((!self.client_port as u64) << 32)
+            | (!self.client.to_u32()) as u64;
+        IPv6::from_parts(network_prefix, interface_id)
+    }
+
+    /// Get the Teredo server address
+    pub fn server(&self) -> &IPv4 {
+        &self.server
+    }
+
+    /// Get the flags
+    pub fn flags(&self) -> u16 {
+        self.flags
+    }
+
+    /// Check if the client is behind a cone NAT
+    pub fn is_cone(&self) -> bool {
+        self.flags & Self::CONE_FLAG != 0
+    }
+
+    /// Get the client's mapped IPv4 address
+    pub fn client(&self) -> &IPv4 {
+        &self.client
+    }
+
+    /// Get the client's mapped UDP port
+    pub fn client_port(&self) -> u16 {
+        self.client_port
+    }
+}
+
+impl From<&TeredoAddress> for IPv6 {
+    fn from(teredo: &TeredoAddress) -> Self {
+        teredo.to_ipv6()
+    }
+}
+
+impl fmt::Display for TeredoAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "server {} client {}:{} flags {:#06x}",
+            self.server, self.client, self.client_port, self.flags
+        )
+    }
+}
+
+/// A 6rd domain, mapping customer IPv4 addresses to delegated IPv6 prefixes
+///
+/// Every IPv4 address in the domain shares its first `ipv4_mask_length` bits
+/// with the border relay. The remaining bits follow the 6rd prefix to form
+/// the prefix delegated to the customer edge router.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SixRdDomain {
+    prefix: IPNetwork,
+    ipv4_mask_length: u8,
+    border_relay: IPv4,
+}
+
+impl SixRdDomain {
+    /// Create a domain from its 6rd prefix, IPv4 mask length and border relay address
+    pub fn new(prefix: IPNetwork, ipv4_mask_length: u8, border_relay: IPv4) -> AddrResult<Self> {
+        if !prefix.is_ipv6() {
+            return Err(AddrFormatError::new(format!("Invalid 6rd prefix {}: expected IPv6", prefix)));
+        }
+        if ipv4_mask_length > 32 {
+            return Err(AddrFormatError::new(format!(
+                "Invalid 6rd IPv4 mask length {}: expected at most 32",
+                ipv4_mask_length
+            )));
+        }
+        let delegated = prefix.prefix_length() as u32 + 32 - ipv4_mask_length as u32;
+        if delegated > 128 {
+            return Err(AddrFormatError::new(format!(
+                "Invalid 6rd domain: {} with IPv4 mask length {} delegates /{}",
+                prefix, ipv4_mask_length, delegated
+            )));
+        }
+        Ok(Self {
+            prefix: IPNetwork::new(prefix.network_address().without_zone(), prefix.prefix_length())?,
+            ipv4_mask_length,
+            border_relay,
+        })
+    }
+
+    /// Get the 6rd prefix
+    pub fn prefix(&self) -> &IPNetwork {
+        &self.prefix
+    }
+
+    /// Get the number of leading IPv4 bits shared across the domain
+    pub fn ipv4_mask_length(&self) -> u8 {
+        self.ipv4_mask_length
+    }
+
+    /// Get the border relay address
+    pub fn border_relay(&self) -> &IPv4 {
+        &self.border_relay
+    }
+
+    /// Get the length of the prefixes delegated to customer edge routers
+    pub fn delegated_prefix_length(&self) -> u8 {
+        self.prefix.prefix_length() + 32 - self.ipv4_mask_length
+    }
+
+    /// Check if an IPv4 address belongs to the domain
+    pub fn contains_ipv4(&self, ipv4: &IPv4) -> bool {
+        (ipv4.to_u32() ^ self.border_relay.to_u32()) & self.common_mask() == 0
+    }
+
+    /// Get the IPv6 prefix delegated to a customer edge IPv4 address
+    pub fn delegated_prefix(&self, ipv4: &IPv4) -> AddrResult<IPNetwork> {
+        if !self.contains_ipv4(ipv4) {
+            return Err(AddrFormatError::new(format!(
+                "{} is outside the 6rd domain of border relay {}/{}",
+                ipv4, self.border_relay, self.ipv4_mask_length
+            )));
+        }
+
+        let suffix = (ipv4.to_u32() & !self.common_mask()) as u128;
+        let value = self.prefix.network_address().to_u128() | suffix.checked_shl(self.suffix_shift()).unwrap_or(0);
+        IPNetwork::new(IPAddress::new_v6(Ipv6Addr::from(value)), self.delegated_prefix_length())
+    }
+
+    /// Get the customer edge IPv4 address whose delegated prefix holds an IPv6 address
+    pub fn ipv4_address(&self, addr: &IPv6) -> AddrResult<IPv4> {
+        if !self.prefix.contains(&IPAddress::new_v6(*addr.as_ipv6_addr())) {
+            return Err(AddrFormatError::new(format!("{} is outside the 6rd prefix {}", addr, self.prefix)));
+        }
+
+        let suffix = addr.to_u128().checked_shr(self.suffix_shift()).unwrap_or(0) as u32 & !self.common_mask();
+        Ok(IPv4::from_u32((self.border_relay.to_u32() & self.common_mask()) | suffix))
+    }
+
+    /// The mask of the IPv4 bits shared across the domain
+    fn common_mask(&self) -> u32 {
+        u32::MAX.checked_shl(32 - self.ipv4_mask_length as u32).unwrap_or(0)
+    }
+
+    /// The position of the lowest IPv4 bit within the IPv6 address
+    fn suffix_shift(&self) -> u32 {
+        128 - self.delegated_prefix_length() as u32
+    }
+}
+
+impl fmt::Display for SixRdDomain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} via {}/{}", self.prefix, self.border_relay, self.ipv4_mask_length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn ipv4(s: &str) -> IPv4 {
+        IPv4::from_str(s).unwrap()
+    }
+
+    fn ipv6(s: &str) -> IPv6 {
+        IPv6::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_teredo() {
+        // The example in RFC 4380 section 4
+        let addr = ipv6("2001:0:4136:e378:8000:63bf:3fff:fdd2");
+        let teredo = addr.teredo().unwrap();
+        assert_eq!(teredo.server(), &ipv4("65.54.227.120"));
+        assert!(teredo.is_cone());
+        assert_eq!(teredo.client_port(), 40000);
+        assert_eq!(teredo.client(), &ipv4("192.0.2.45"));
+        assert_eq!(teredo.to_ipv6(), addr);
+
+        let built = TeredoAddress::new(ipv4("65.54.227.120"), TeredoAddress::CONE_FLAG, ipv4("192.0.2.45"), 40000);
+        assert_eq!(built, teredo);
+        assert_eq!(built.to_string(), "server 65.54.227.120 client 192.0.2.45:40000 flags 0x8000");
+        assert!(ipv6("2001:db8::1").teredo().is_none());
+    }
+
+    #[test]
+    fn test_6to4_and_isatap() {
+        let addr = ipv6("2002:c000:22a:1::1");
+        assert_eq!(addr.six_to_four_ipv4(), Some(ipv4("192.0.2.42")));
+        assert_eq!(IPv6::from_six_to_four(&ipv4("192.0.2.42"), 1, 1), addr);
+        assert!(ipv6("2001:db8::1").six_to_four_ipv4().is_none());
+
+        let local = ipv6("fe80::5efe:a00:1");
+        assert_eq!(local.isatap_ipv4(), Some(ipv4("10.0.0.1")));
+        assert_eq!(IPv6::from_isatap(0xfe80u64 << 48, &ipv4("10.0.0.1"), false), local);
+
+        let universal = ipv6("2001:db8::200:5efe:c000:201");
+        assert_eq!(universal.isatap_ipv4(), Some(ipv4("192.0.2.1")));
+        assert_eq!(IPv6::from_isatap(0x2001_0db8u64 << 32, &ipv4("192.0.2.1"), true), universal);
+        assert!(ipv6("2001:db8::100:5efe:c000:201").isatap_ipv4().is_none());
+    }
+
+    #[test]
+    fn test_6rd() {
+        let prefix = IPNetwork::from_str("2001:db8::/32").unwrap();
+        let domain = SixRdDomain::new(prefix, 8, ipv4("10.0.0.1")).unwrap();
+        assert_eq!(domain.delegated_prefix_length(), 56);
+
+        let delegated = domain.delegated_prefix(&ipv4("10.1.2.3")).unwrap();
+        assert_eq!(delegated.to_string(), "2001:db8:102:300::/56");
+        assert_eq!(domain.ipv4_address(&ipv6("2001:db8:102:3ff::1")).unwrap(), ipv4("10.1.2.3"));
+        assert!(domain.delegated_prefix(&ipv4("192.0.2.1")).is_err());
+        assert!(domain.ipv4_address(&ipv6("2001:db9::1")).is_err());
+
+        // With no shared bits, the whole IPv4 address follows the prefix
+        let prefix = IPNetwork::from_str("2001:db8::/32").unwrap();
+        let domain = SixRdDomain::new(prefix, 0, ipv4("192.0.2.1")).unwrap();
+        let delegated = domain.delegated_prefix(&ipv4("198.51.100.7")).unwrap();
+        assert_eq!(delegated.to_string(), "2001:db8:c633:6407::/64");
+        let address = IPv6::from(*delegated.network_address().as_ipv6().unwrap());
+        assert_eq!(domain.ipv4_address(&address).unwrap(), ipv4("198.51.100.7"));
+    }
+
+    #[test]
+    fn test_invalid_6rd_domains() {
+        let v6 = IPNetwork::from_str("2001:db8::/100").unwrap();
+        assert!(SixRdDomain::new(v6, 0, ipv4("10.0.0.1")).is_err());
+        let v4 = IPNetwork::from_str("10.0.0.0/8").unwrap();
+        assert!(SixRdDomain::new(v4, 8, ipv4("10.0.0.1")).is_err());
+        let v6 = IPNetwork::from_str("2001:db8::/32").unwrap();
+        assert!(SixRdDomain::new(v6, 33, ipv4("10.0.0.1")).is_err());
+    }
+}
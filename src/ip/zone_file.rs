@@ -0,0 +1,361 @@
+//! Reverse zone file generation
+//!
+//! Builds `$ORIGIN`, `$TTL`, a placeholder SOA and NS, and one PTR record per
+//! host for each reverse zone covering a network or set, with hostnames from
+//! a template. The matching forward A/AAAA records come with each zone.
+//!
+//! Networks are enumerated with `IPNetwork::hosts()` and sets address by
+//! address, up to 2^16 addresses. Larger networks, such as any IPv6 subnet,
+//! are far too large for that, so their PTR records are generated sparsely
+//! from an explicit list of addresses.
+
+use crate::error::{AddrFormatError, AddrResult};
+use crate::ip::{IPAddress, IPNetwork, ReverseZone};
+use crate::sets::IPSet;
+use std::fmt::Write;
+
+/// The largest network or set enumerated in full; larger ones need
+/// [`ZoneFileGenerator::sparse_zone_files`]
+const MAX_HOSTS: u128 = 1 << 16;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TemplatePart {
+    Literal(String),
+    Octet(usize),
+    Nibbles,
+}
+
+/// A hostname template, such as `host-{a}-{b}-{c}-{d}.example.net`
+///
+/// `{a}` to `{d}` are the octets of an IPv4 address, most significant first,
+/// and `{nibbles}` is the 32 hex digits of an IPv6 address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostnameTemplate {
+    parts: Vec<TemplatePart>,
+}
+
+impl HostnameTemplate {
+    /// Parse a template, rejecting unknown placeholders and unbalanced braces
+    pub fn new(template: &str) -> AddrResult<Self> {
+        let invalid = |reason: String| AddrFormatError::new(format!("Invalid hostname template '{}': {}", template, reason));
+
+        let mut parts = Vec::new();
+        let mut rest = template;
+        while let Some(open) = rest.find(['{', '}']) {
+            if rest[open..].starts_with('}') {
+                return Err(invalid("unmatched '}'".to_string()));
+            }
+            if open > 0 {
+                parts.push(TemplatePart::Literal(rest[..open].to_string()));
+            }
+            let close = rest[open..].find('}').ok_or_else(|| invalid("unmatched '{'".to_string()))? + open;
+            parts.push(match &rest[open + 1..close] {
+                "a" => TemplatePart::Octet(0),
+                "b" => TemplatePart::Octet(1),
+                "c" => TemplatePart::Octet(2),
+                "d" => TemplatePart::Octet(3),
+                "nibbles" => TemplatePart::Nibbles,
+                other => return Err(invalid(format!("unknown placeholder '{{{}}}'", other))),
+            });
+            rest = &rest[close + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(TemplatePart::Literal(rest.to_string()));
+        }
+
+        Ok(Self { parts })
+    }
+
+    /// Render the hostname for an address, without a trailing dot
+    pub fn render(&self, addr: &IPAddress) -> AddrResult<String> {
+        let mut hostname = String::new();
+        for part in &self.parts {
+            match part {
+                TemplatePart::Literal(text) => hostname.push_str(text),
+                TemplatePart::Octet(index) => match addr.as_ipv4() {
+                    Some(ipv4) => write!(hostname, "{}", ipv4.octets()[*index]).expect("writing to a String"),
+                    None => {
+                        return Err(AddrFormatError::new(format!(
+                            "Octet placeholders need an IPv4 address, not {}",
+                            addr
+                        )))
+                    }
+                },
+                TemplatePart::Nibbles if addr.is_ipv6() => {
+                    write!(hostname, "{:032x}", addr.to_u128()).expect("writing to a String")
+                }
+                TemplatePart::Nibbles => {
+                    return Err(AddrFormatError::new(format!(
+                        "The {{nibbles}} placeholder needs an IPv6 address, not {}",
+                        addr
+                    )))
+                }
+            }
+        }
+        Ok(hostname.trim_end_matches('.').to_string())
+    }
+}
+
+/// A generated reverse zone with its PTR records
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZoneFile {
+    zone: ReverseZone,
+    records: Vec<(IPAddress, String)>,
+    text: String,
+}
+
+impl ZoneFile {
+    /// Get the zone
+    pub fn zone(&self) -> &ReverseZone {
+        &self.zone
+    }
+
+    /// Get the addresses and hostnames of the PTR records, in address order
+    pub fn records(&self) -> &[(IPAddress, String)] {
+        &self.records
+    }
+
+    /// Get the zone file text
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Get the matching forward A/AAAA records, with absolute names
+    pub fn forward_records(&self) -> String {
+        let mut text = String::new();
+        for (addr, hostname) in &self.records {
+            let kind = if addr.is_ipv4() { "A" } else { "AAAA" };
+            writeln!(text, "{}. IN {} {}", hostname, kind, addr).expect("writing to a String");
+        }
+        text
+    }
+}
+
+/// Generates reverse zone files from a hostname template
+///
+/// The SOA and NS records use placeholder values (`localhost.`, serial 1)
+/// unless they are set with the builder methods.
+#[derive(Debug, Clone)]
+pub struct ZoneFileGenerator {
+    template: HostnameTemplate,
+    ttl: u32,
+    name_server: String,
+    hostmaster: String,
+    serial: u32,
+}
+
+impl ZoneFileGenerator {
+    /// Create a generator for a hostname template
+    pub fn new(template: HostnameTemplate) -> Self {
+        Self {
+            template,
+            ttl: 3600,
+            name_server: "localhost".to_string(),
+            hostmaster: "root.localhost".to_string(),
+            serial: 1,
+        }
+    }
+
+    /// Set the default TTL
+    pub fn with_ttl(mut self, ttl: u32) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Set the primary name server for the SOA and NS records
+    pub fn with_name_server(mut self, name_server: &str) -> Self {
+        self.name_server = name_server.trim_end_matches('.').to_string();
+        self
+    }
+
+    /// Set the hostmaster mailbox for the SOA record, in DNS form (`hostmaster.example.net`)
+    pub fn with_hostmaster(mut self, hostmaster: &str) -> Self {
+        self.hostmaster = hostmaster.trim_end_matches('.').to_string();
+        self
+    }
+
+    /// Set the SOA serial number
+    pub fn with_serial(mut self, serial: u32) -> Self {
+        self.serial = serial;
+        self
+    }
+
+    /// Generate the zone files for every host in a network
+    ///
+    /// Networks larger than 2^16 addresses are rejected; use
+    /// `sparse_zone_files` for those.
+    pub fn network_zone_files(&self, network: &IPNetwork) -> AddrResult<Vec<ZoneFile>> {
+        if network.num_addresses() > MAX_HOSTS {
+            return Err(AddrFormatError::new(format!(
+                "{} is too large to enumerate; list its addresses with sparse_zone_files",
+                network
+            )));
+        }
+        self.zone_files(&network.reverse_zones(), network.hosts())
+    }
+
+    /// Generate the zone files for every address in a set
+    ///
+    /// Every member gets a PTR record, however the set splits into CIDR
+    /// blocks. Sets with more than 2^16 addresses are rejected; use
+    /// `sparse_zone_files` for those.
+    pub fn set_zone_files(&self, set: &IPSet) -> AddrResult<Vec<ZoneFile>> {
+        if set.size() > MAX_HOSTS {
+            return Err(AddrFormatError::new(
+                "The set has too many addresses to enumerate; list them with sparse_zone_files",
+            ));
+        }
+        self.zone_files(&set.reverse_zones(), set.addresses())
+    }
+
+    /// Generate the zone files covering a network, with PTR records for the given addresses only
+    ///
+    /// Any IPv6 zones are dropped from the addresses.
+    pub fn sparse_zone_files<I>(&self, network: &IPNetwork, addresses: I) -> AddrResult<Vec<ZoneFile>>
+    where
+        I: IntoIterator<Item = IPAddress>,
+    {
+        self.zone_files(&network.reverse_zones(), addresses)
+    }
+
+    /// Group addresses into their zones (sorted and disjoint) and render each zone
+    fn zone_files<I>(&self, zones: &[ReverseZone], addresses: I) -> AddrResult<Vec<ZoneFile>>
+    where
+        I: IntoIterator<Item = IPAddress>,
+    {
+        let mut records: Vec<Vec<(IPAddress, String)>> = vec![Vec::new(); zones.len()];
+        for addr in addresses {
+            let addr = addr.without_zone();
+            // Zones are disjoint and sorted, so only the last one starting at or before the address can hold it
+            let index = zones
+                .partition_point(|zone| zone.network().network_address() <= &addr)
+                .checked_sub(1)
+                .filter(|&index| zones[index].contains(&addr))
+                .ok_or_else(|| AddrFormatError::new(format!("{} is outside the reverse zones", addr)))?;
+            let hostname = self.template.render(&addr)?;
+            records[index].push((addr, hostname));
+        }
+
+        Ok(zones
+            .iter()
+            .zip(records)
+            .map(|(zone, mut records)| {
+                records.sort();
+                records.dedup();
+                ZoneFile {
+                    text: self.render_zone(zone, &records),
+                    zone: zone.clone(),
+                    records,
+                }
+            })
+            .collect())
+    }
+
+    fn render_zone(&self, zone: &ReverseZone, records: &[(IPAddress, String)]) -> String {
+        let origin = zone.name();
+        let mut text = String::new();
+        let mut line = |s: String| {
+            text.push_str(&s);
+            text.push('\n');
+        };
+
+        line(format!("$ORIGIN {}.", origin));
+        line(format!("$TTL {}", self.ttl));
+        line(format!(
+            "@ IN SOA {}. {}. ( {} 3600 900 1209600 {} )",
+            self.name_server, self.hostmaster, self.serial, self.ttl
+        ));
+        line(format!("@ IN NS {}.", self.name_server));
+        for (addr, hostname) in records {
+            let name = addr.reverse_dns();
+            let owner = name.strip_suffix(&format!(".{}", origin)).unwrap_or(&name);
+            line(format!("{} IN PTR {}.", owner, hostname));
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_hostname_template() {
+        let template = HostnameTemplate::new("host-{a}-{b}-{c}-{d}.example.net").unwrap();
+        let addr = IPAddress::from_str("192.0.2.10").unwrap();
+        assert_eq!(template.render(&addr).unwrap(), "host-192-0-2-10.example.net");
+        assert!(template.render(&IPAddress::from_str("2001:db8::1").unwrap()).is_err());
+
+        let template = HostnameTemplate::new("{nibbles}.v6.example.net.").unwrap();
+        let addr = IPAddress::from_str("2001:db8::1").unwrap();
+        assert_eq!(template.render(&addr).unwrap(), "20010db8000000000000000000000001.v6.example.net");
+
+        for bad in ["host-{e}.example.net", "host-{a.example.net", "host-a}.example.net"] {
+            assert!(HostnameTemplate::new(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_ipv4_zone_file() {
+        let template = HostnameTemplate::new("host-{a}-{b}-{c}-{d}.example.net").unwrap();
+        let generator = ZoneFileGenerator::new(template).with_name_server("ns1.example.net.");
+        let network = IPNetwork::from_str("192.0.2.0/30").unwrap();
+        let zones = generator.network_zone_files(&network).unwrap();
+        assert_eq!(zones.len(), 1);
+        assert_eq!(
+            zones[0].text(),
+            "$ORIGIN 2.0.192.in-addr.arpa.\n\
+             $TTL 3600\n\
+             @ IN SOA ns1.example.net. root.localhost. ( 1 3600 900 1209600 3600 )\n\
+             @ IN NS ns1.example.net.\n\
+             1 IN PTR host-192-0-2-1.example.net.\n\
+             2 IN PTR host-192-0-2-2.example.net.\n"
+        );
+        assert_eq!(
+            zones[0].forward_records(),
+            "host-192-0-2-1.example.net. IN A 192.0.2.1\nhost-192-0-2-2.example.net. IN A 192.0.2.2\n"
+        );
+
+        let set: IPSet = ["192.0.2.0/31", "198.51.100.7/32"].iter().map(|s| IPNetwork::from_str(s).unwrap()).collect();
+        let zones = generator.set_zone_files(&set).unwrap();
+        assert_eq!(zones.len(), 2);
+        assert_eq!(zones[1].records().len(), 1);
+
+        // Every member of a set gets a record, and adding an adjacent block keeps the earlier ones
+        let ptrs = |set: &IPSet| -> Vec<String> {
+            let zones = generator.set_zone_files(set).unwrap();
+            zones[0].records().iter().map(|(addr, _)| addr.to_string()).collect()
+        };
+        let mut set = IPSet::from_network(network.clone()).unwrap();
+        assert_eq!(ptrs(&set), ["192.0.2.0", "192.0.2.1", "192.0.2.2", "192.0.2.3"]);
+        set.add_network(IPNetwork::from_str("192.0.2.4/30").unwrap()).unwrap();
+        let expected: Vec<String> = (0..8).map(|host| format!("192.0.2.{}", host)).collect();
+        assert_eq!(ptrs(&set), expected);
+
+        // Large IPv4 networks and sets are rejected too
+        let large = IPNetwork::from_str("10.0.0.0/8").unwrap();
+        assert!(generator.network_zone_files(&large).is_err());
+        assert!(generator.set_zone_files(&IPSet::from_network(large).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_sparse_ipv6_zone_file() {
+        let template = HostnameTemplate::new("{nibbles}.v6.example.net").unwrap();
+        let generator = ZoneFileGenerator::new(template).with_serial(2024010101);
+        let network = IPNetwork::from_str("2001:db8::/64").unwrap();
+        assert!(generator.network_zone_files(&network).is_err());
+
+        let hosts = ["2001:db8::1", "2001:db8::abcd%eth0"].map(|s| IPAddress::from_str(s).unwrap());
+        let zones = generator.sparse_zone_files(&network, hosts).unwrap();
+        assert_eq!(zones[0].zone().name(), "0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa");
+        assert!(zones[0].text().contains(
+            "\nd.c.b.a.0.0.0.0.0.0.0.0.0.0.0.0 IN PTR 20010db800000000000000000000abcd.v6.example.net.\n"
+        ));
+        assert!(zones[0].text().contains("( 2024010101 "));
+        assert!(zones[0].forward_records().contains("IN AAAA 2001:db8::abcd\n"));
+
+        let outside = [IPAddress::from_str("2001:db8:1::1").unwrap()];
+        assert!(generator.sparse_zone_files(&network, outside).is_err());
+    }
+}
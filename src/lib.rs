@@ -28,19 +28,29 @@ pub mod strategy;
 pub mod glob;
 pub mod nmap;
 pub mod sets;
+pub mod prefix_map;
+pub mod allocator;
+pub mod binary;
 pub mod iana;
+pub mod rir;
 pub mod ieee;
+pub mod registry;
 pub mod cli;
 
+#[cfg(feature = "serde")]
+mod serialization;
+
 #[cfg(feature = "wasm")]
 pub mod wasm;
 
 // Re-export commonly used types
 pub use error::{AddrFormatError, AddrConversionError, NotRegisteredError};
-pub use ip::{IPAddress, IPNetwork, IPRange};
+pub use ip::{AddressCount, IPAddress, IPEndpoint, IPInterface, IPNetwork, IPRange, MaskedAddress};
 pub use eui::{EUI, MAC, EUI64};
 pub use sets::IPSet;
+pub use prefix_map::PrefixMap;
 pub use glob::IPGlob;
+pub use registry::RegistryHandle;
 
 // Re-export core constants
 pub use core::{ZEROFILL, INET_ATON, INET_PTON, NOHOST};
@@ -49,14 +59,22 @@ pub use core::{ZEROFILL, INET_ATON, INET_PTON, NOHOST};
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 /// Parse flags for address parsing
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// The flag values are the `core` constants (`INET_PTON`, `ZEROFILL`,
+/// `NOHOST`, `INET_ATON`), matching Python netaddr, so raw flag words convert
+/// losslessly with `from_bits` and `bits`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub struct ParseFlags(u32);
 
 impl ParseFlags {
-    pub const ZEROFILL: Self = Self(1);
-    pub const INET_PTON: Self = Self(2);
-    pub const NOHOST: Self = Self(4);
-    pub const INET_ATON: Self = Self(8);
+    /// Parse IPv4 with strict inet_pton() semantics (the default)
+    pub const INET_PTON: Self = Self(core::INET_PTON);
+    /// Remove leading zeros from IPv4 octets before parsing
+    pub const ZEROFILL: Self = Self(core::ZEROFILL);
+    /// Clear host bits to the right of the prefix instead of rejecting them
+    pub const NOHOST: Self = Self(core::NOHOST);
+    /// Parse IPv4 with legacy inet_aton() semantics
+    pub const INET_ATON: Self = Self(core::INET_ATON);
 
     pub const fn empty() -> Self {
         Self(0)
@@ -66,6 +84,16 @@ impl ParseFlags {
         Self(Self::ZEROFILL.0 | Self::INET_PTON.0 | Self::NOHOST.0 | Self::INET_ATON.0)
     }
 
+    /// Create flags from a raw flag word, ignoring unknown bits
+    pub const fn from_bits(bits: u32) -> Self {
+        Self(bits & Self::all().0)
+    }
+
+    /// Get the raw flag word
+    pub const fn bits(&self) -> u32 {
+        self.0
+    }
+
     pub const fn contains(&self, other: Self) -> bool {
         self.0 & other.0 != 0
     }
@@ -73,4 +101,18 @@ impl ParseFlags {
     pub const fn union(self, other: Self) -> Self {
         Self(self.0 | other.0)
     }
-}
\ No newline at end of file
+}
+
+impl std::ops::BitOr for ParseFlags {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        self.union(other)
+    }
+}
+
+impl From<u32> for ParseFlags {
+    fn from(bits: u32) -> Self {
+        Self::from_bits(bits)
+    }
+}
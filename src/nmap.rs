@@ -515,7 +515,7 @@ mod tests {
         let cidrs = nmap_range_to_cidrs("192.168.1.0-255").unwrap();
         assert!(!cidrs.is_empty());
 
-        let total_addresses: u128 = cidrs.iter().map(|c| c.num_addresses()).sum();
+        let total_addresses: crate::ip::AddressCount = cidrs.iter().map(|c| c.num_addresses()).sum();
         assert_eq!(total_addresses, 256);
     }
 
@@ -0,0 +1,500 @@
+//! Prefix map - a path-compressed binary trie keyed by IP networks
+
+use crate::error::AddrResult;
+use crate::ip::{IPAddress, IPNetwork};
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// A map from IP networks to values with prefix-aware lookups
+///
+/// IPv4 and IPv6 prefixes live in separate path-compressed tries, so every
+/// operation walks at most one node per prefix bit. Iteration yields entries
+/// ordered by network address and then by prefix length, IPv4 before IPv6.
+#[derive(Clone)]
+pub struct PrefixMap<V> {
+    ipv4: Option<Box<Node<V>>>,
+    ipv6: Option<Box<Node<V>>>,
+    len: usize,
+}
+
+#[derive(Clone)]
+struct Node<V> {
+    /// Prefix bits, left-aligned in a u128 with host bits cleared
+    key: u128,
+    bits: u8,
+    value: Option<V>,
+    children: [Option<Box<Node<V>>>; 2],
+}
+
+impl<V> Node<V> {
+    fn new(key: u128, bits: u8, value: Option<V>) -> Box<Self> {
+        Box::new(Self {
+            key,
+            bits,
+            value,
+            children: [None, None],
+        })
+    }
+
+    /// Check if this node's prefix contains the prefix `key/bits`
+    fn covers(&self, key: u128, bits: u8) -> bool {
+        self.bits <= bits && mask(key, self.bits) == self.key
+    }
+}
+
+fn mask(key: u128, bits: u8) -> u128 {
+    if bits == 0 {
+        0
+    } else {
+        key & (u128::MAX << (128 - bits as u32))
+    }
+}
+
+fn bit_at(key: u128, index: u8) -> usize {
+    ((key >> (127 - index as u32)) & 1) as usize
+}
+
+fn common_bits(a: u128, a_bits: u8, b: u128, b_bits: u8) -> u8 {
+    ((a ^ b).leading_zeros() as u8).min(a_bits).min(b_bits)
+}
+
+/// Left-aligned key, prefix length and family of a network
+fn network_key(network: &IPNetwork) -> (u128, u8, bool) {
+    let bits = network.prefix_length();
+    match network.network_address().as_ip_addr() {
+        IpAddr::V4(addr) => (mask((u32::from(*addr) as u128) << 96, bits), bits, true),
+        IpAddr::V6(addr) => (mask(u128::from(*addr), bits), bits, false),
+    }
+}
+
+fn key_network(key: u128, bits: u8, ipv4: bool) -> IPNetwork {
+    let addr = if ipv4 {
+        IPAddress::new_v4(Ipv4Addr::from((key >> 96) as u32))
+    } else {
+        IPAddress::new_v6(Ipv6Addr::from(key))
+    };
+    IPNetwork::new_unchecked(addr, bits)
+}
+
+fn host_network(addr: &IPAddress) -> IPNetwork {
+    let bits = if addr.is_ipv4() { 32 } else { 128 };
+    IPNetwork::new_unchecked(addr.clone(), bits)
+}
+
+fn insert_node<V>(slot: &mut Option<Box<Node<V>>>, key: u128, bits: u8, value: V) -> Option<V> {
+    let Some(node) = slot else {
+        *slot = Some(Node::new(key, bits, Some(value)));
+        return None;
+    };
+
+    let common = common_bits(node.key, node.bits, key, bits);
+    if common == node.bits {
+        if bits == node.bits {
+            return node.value.replace(value);
+        }
+        return insert_node(&mut node.children[bit_at(key, node.bits)], key, bits, value);
+    }
+
+    // The new prefix diverges inside this node's prefix: split at the common bits
+    let existing = slot.take().expect("slot is occupied");
+    let mut branch = Node::new(mask(key, common), common, None);
+    let existing_side = bit_at(existing.key, common);
+    branch.children[existing_side] = Some(existing);
+    if common == bits {
+        branch.value = Some(value);
+    } else {
+        branch.children[1 - existing_side] = Some(Node::new(key, bits, Some(value)));
+    }
+    *slot = Some(branch);
+    None
+}
+
+fn remove_node<V>(slot: &mut Option<Box<Node<V>>>, key: u128, bits: u8) -> Option<V> {
+    let node = slot.as_mut()?;
+    if !node.covers(key, bits) {
+        return None;
+    }
+
+    let removed = if node.bits == bits {
+        node.value.take()
+    } else {
+        remove_node(&mut node.children[bit_at(key, node.bits)], key, bits)
+    };
+
+    // Drop valueless nodes that no longer branch
+    if removed.is_some() && node.value.is_none() {
+        match &mut node.children {
+            [None, None] => *slot = None,
+            [Some(_), None] | [None, Some(_)] => {
+                let child = node.children.iter_mut().find_map(Option::take);
+                *slot = child;
+            }
+            _ => {}
+        }
+    }
+    removed
+}
+
+impl<V> PrefixMap<V> {
+    /// Create a new empty map
+    pub fn new() -> Self {
+        Self {
+            ipv4: None,
+            ipv6: None,
+            len: 0,
+        }
+    }
+
+    /// Get the number of prefixes in the map
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the map is empty
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Remove all prefixes
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    fn root(&self, ipv4: bool) -> Option<&Node<V>> {
+        if ipv4 { self.ipv4.as_deref() } else { self.ipv6.as_deref() }
+    }
+
+    /// Insert a value for a network, returning the previous value for that exact prefix
+    ///
+    /// Host bits of the network address are ignored.
+    pub fn insert(&mut self, network: IPNetwork, value: V) -> Option<V> {
+        let (key, bits, ipv4) = network_key(&network);
+        let root = if ipv4 { &mut self.ipv4 } else { &mut self.ipv6 };
+        let previous = insert_node(root, key, bits, value);
+        if previous.is_none() {
+            self.len += 1;
+        }
+        previous
+    }
+
+    /// Remove a network, returning its value
+    pub fn remove(&mut self, network: &IPNetwork) -> Option<V> {
+        let (key, bits, ipv4) = network_key(network);
+        let root = if ipv4 { &mut self.ipv4 } else { &mut self.ipv6 };
+        let removed = remove_node(root, key, bits);
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    /// Get the value stored for exactly this network
+    pub fn get(&self, network: &IPNetwork) -> Option<&V> {
+        let (key, bits, ipv4) = network_key(network);
+        let mut node = self.root(ipv4)?;
+        while node.covers(key, bits) {
+            if node.bits == bits {
+                return node.value.as_ref();
+            }
+            node = node.children[bit_at(key, node.bits)].as_deref()?;
+        }
+        None
+    }
+
+    /// Get a mutable reference to the value stored for exactly this network
+    pub fn get_mut(&mut self, network: &IPNetwork) -> Option<&mut V> {
+        let (key, bits, ipv4) = network_key(network);
+        let mut node = if ipv4 { self.ipv4.as_deref_mut()? } else { self.ipv6.as_deref_mut()? };
+        while node.covers(key, bits) {
+            if node.bits == bits {
+                return node.value.as_mut();
+            }
+            node = node.children[bit_at(key, node.bits)].as_deref_mut()?;
+        }
+        None
+    }
+
+    /// Check if a value is stored for exactly this network
+    pub fn contains_key(&self, network: &IPNetwork) -> bool {
+        self.get(network).is_some()
+    }
+
+    /// Get all stored prefixes containing the network, shortest first
+    ///
+    /// The network itself is included if it is stored.
+    pub fn covering(&self, network: &IPNetwork) -> Vec<(IPNetwork, &V)> {
+        let (key, bits, ipv4) = network_key(network);
+        let mut matches = Vec::new();
+        let mut current = self.root(ipv4);
+        while let Some(node) = current {
+            if !node.covers(key, bits) {
+                break;
+            }
+            if let Some(value) = &node.value {
+                matches.push((key_network(node.key, node.bits, ipv4), value));
+            }
+            if node.bits == bits {
+                break;
+            }
+            current = node.children[bit_at(key, node.bits)].as_deref();
+        }
+        matches
+    }
+
+    /// Find the longest stored prefix containing the whole network
+    pub fn longest_match(&self, network: &IPNetwork) -> Option<(IPNetwork, &V)> {
+        self.covering(network).pop()
+    }
+
+    /// Find the longest stored prefix containing an address
+    pub fn longest_match_address(&self, addr: &IPAddress) -> Option<(IPNetwork, &V)> {
+        self.longest_match(&host_network(addr))
+    }
+
+    /// Iterate over all stored prefixes inside the network, in order
+    ///
+    /// The network itself is included if it is stored.
+    pub fn covered(&self, network: &IPNetwork) -> Iter<'_, V> {
+        let (key, bits, ipv4) = network_key(network);
+        let mut current = self.root(ipv4);
+        while let Some(node) = current {
+            if node.bits >= bits {
+                // First node at or below the network's depth: its subtree is
+                // inside the network exactly if it starts with the network's bits
+                let inside = mask(node.key, bits) == key;
+                return Iter::new(if inside { vec![(node, ipv4)] } else { Vec::new() }, 0);
+            }
+            if !node.covers(key, bits) {
+                break;
+            }
+            current = node.children[bit_at(key, node.bits)].as_deref();
+        }
+        Iter::new(Vec::new(), 0)
+    }
+
+    /// Iterate over all prefixes in order
+    pub fn iter(&self) -> Iter<'_, V> {
+        let mut stack = Vec::new();
+        if let Some(node) = self.ipv6.as_deref() {
+            stack.push((node, false));
+        }
+        if let Some(node) = self.ipv4.as_deref() {
+            stack.push((node, true));
+        }
+        Iter::new(stack, self.len)
+    }
+
+    /// Iterate over all prefixes in order
+    pub fn keys(&self) -> impl Iterator<Item = IPNetwork> + '_ {
+        self.iter().map(|(network, _)| network)
+    }
+
+    /// Iterate over all values in prefix order
+    pub fn values(&self) -> impl Iterator<Item = &V> + '_ {
+        self.iter().map(|(_, value)| value)
+    }
+
+    /// Encode the map in the compact binary format (see [`crate::binary`])
+    pub fn to_binary_with<F>(&self, encode_value: F) -> Vec<u8>
+    where
+        F: FnMut(&V) -> Vec<u8>,
+    {
+        crate::binary::encode_prefix_map(self, encode_value)
+    }
+
+    /// Decode a map from the compact binary format
+    pub fn from_binary_with<F>(bytes: &[u8], decode_value: F) -> AddrResult<Self>
+    where
+        F: FnMut(&[u8]) -> AddrResult<V>,
+    {
+        crate::binary::decode_prefix_map(bytes, decode_value)
+    }
+}
+
+/// Ordered iterator over the entries of a `PrefixMap`
+pub struct Iter<'a, V> {
+    stack: Vec<(&'a Node<V>, bool)>,
+    remaining: usize,
+}
+
+impl<'a, V> Iter<'a, V> {
+    fn new(stack: Vec<(&'a Node<V>, bool)>, remaining: usize) -> Self {
+        Self { stack, remaining }
+    }
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (IPNetwork, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Pre-order traversal visits prefixes by address, shorter prefixes first
+        while let Some((node, ipv4)) = self.stack.pop() {
+            for child in node.children.iter().rev().flatten() {
+                self.stack.push((child, ipv4));
+            }
+            if let Some(value) = &node.value {
+                self.remaining = self.remaining.saturating_sub(1);
+                return Some((key_network(node.key, node.bits, ipv4), value));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.stack.is_empty() {
+            (0, Some(0))
+        } else {
+            (self.remaining, None)
+        }
+    }
+}
+
+impl<'a, V> IntoIterator for &'a PrefixMap<V> {
+    type Item = (IPNetwork, &'a V);
+    type IntoIter = Iter<'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<V> Default for PrefixMap<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> FromIterator<(IPNetwork, V)> for PrefixMap<V> {
+    fn from_iter<I: IntoIterator<Item = (IPNetwork, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<V> Extend<(IPNetwork, V)> for PrefixMap<V> {
+    fn extend<I: IntoIterator<Item = (IPNetwork, V)>>(&mut self, iter: I) {
+        for (network, value) in iter {
+            self.insert(network, value);
+        }
+    }
+}
+
+impl<V: fmt::Debug> fmt::Debug for PrefixMap<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.iter().map(|(network, value)| (network.to_string(), value)))
+            .finish()
+    }
+}
+
+impl<V: PartialEq> PartialEq for PrefixMap<V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<V: Eq> Eq for PrefixMap<V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn net(s: &str) -> IPNetwork {
+        IPNetwork::from_str(s).unwrap()
+    }
+
+    fn sample() -> PrefixMap<&'static str> {
+        [
+            ("10.0.0.0/8", "a"),
+            ("10.1.0.0/16", "b"),
+            ("10.1.2.0/24", "c"),
+            ("10.2.0.0/16", "d"),
+            ("192.168.0.0/16", "e"),
+            ("0.0.0.0/0", "default"),
+            ("2001:db8::/32", "f"),
+            ("2001:db8:1::/48", "g"),
+        ]
+        .into_iter()
+        .map(|(n, v)| (net(n), v))
+        .collect()
+    }
+
+    #[test]
+    fn test_insert_get_remove() {
+        let mut map = sample();
+        assert_eq!(map.len(), 8);
+        assert_eq!(map.get(&net("10.1.0.0/16")), Some(&"b"));
+        assert_eq!(map.get(&net("10.1.0.0/17")), None);
+        assert_eq!(map.get(&net("10.0.0.0/7")), None);
+        assert!(map.contains_key(&net("0.0.0.0/0")));
+        assert!(!map.contains_key(&net("::/0")));
+
+        assert_eq!(map.insert(net("10.1.0.0/16"), "b2"), Some("b"));
+        assert_eq!(map.len(), 8);
+        *map.get_mut(&net("10.1.0.0/16")).unwrap() = "b3";
+        assert_eq!(map.get(&net("10.1.0.0/16")), Some(&"b3"));
+
+        assert_eq!(map.remove(&net("10.1.0.0/16")), Some("b3"));
+        assert_eq!(map.remove(&net("10.1.0.0/16")), None);
+        assert_eq!(map.remove(&net("10.3.0.0/16")), None);
+        assert_eq!(map.len(), 7);
+        assert_eq!(map.get(&net("10.1.2.0/24")), Some(&"c"));
+
+        for network in map.keys().collect::<Vec<_>>() {
+            map.remove(&network);
+        }
+        assert!(map.is_empty());
+        assert!(map.ipv4.is_none() && map.ipv6.is_none());
+    }
+
+    #[test]
+    fn test_longest_match() {
+        let map = sample();
+        let lpm = |s: &str| map.longest_match_address(&IPAddress::from_str(s).unwrap()).map(|(n, v)| (n.to_string(), *v));
+
+        assert_eq!(lpm("10.1.2.3"), Some(("10.1.2.0/24".to_string(), "c")));
+        assert_eq!(lpm("10.1.3.3"), Some(("10.1.0.0/16".to_string(), "b")));
+        assert_eq!(lpm("10.3.0.1"), Some(("10.0.0.0/8".to_string(), "a")));
+        assert_eq!(lpm("8.8.8.8"), Some(("0.0.0.0/0".to_string(), "default")));
+        assert_eq!(lpm("2001:db8:1::1"), Some(("2001:db8:1::/48".to_string(), "g")));
+        assert_eq!(lpm("2001:db9::1"), None);
+
+        let (network, value) = map.longest_match(&net("10.1.0.0/15")).unwrap();
+        assert_eq!((network.to_string(), *value), ("10.0.0.0/8".to_string(), "a"));
+    }
+
+    #[test]
+    fn test_covering_and_covered() {
+        let map = sample();
+        let covering: Vec<_> = map.covering(&net("10.1.2.128/25")).into_iter().map(|(_, v)| *v).collect();
+        assert_eq!(covering, vec!["default", "a", "b", "c"]);
+
+        let covered: Vec<_> = map.covered(&net("10.0.0.0/8")).map(|(_, v)| *v).collect();
+        assert_eq!(covered, vec!["a", "b", "c", "d"]);
+        let covered: Vec<_> = map.covered(&net("10.0.0.0/14")).map(|(n, _)| n.to_string()).collect();
+        assert_eq!(covered, vec!["10.1.0.0/16", "10.1.2.0/24", "10.2.0.0/16"]);
+        assert_eq!(map.covered(&net("10.1.2.0/25")).count(), 0);
+        assert_eq!(map.covered(&net("172.16.0.0/12")).count(), 0);
+        assert_eq!(map.covered(&net("::/0")).count(), 2);
+    }
+
+    #[test]
+    fn test_ordered_iteration() {
+        let map = sample();
+        let keys: Vec<String> = map.keys().map(|n| n.to_string()).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "0.0.0.0/0", "10.0.0.0/8", "10.1.0.0/16", "10.1.2.0/24", "10.2.0.0/16",
+                "192.168.0.0/16", "2001:db8::/32", "2001:db8:1::/48",
+            ]
+        );
+
+        // Insertion order does not affect the map
+        let mut entries: Vec<_> = map.iter().map(|(n, v)| (n, *v)).collect();
+        entries.reverse();
+        assert_eq!(entries.into_iter().collect::<PrefixMap<_>>(), map);
+    }
+}
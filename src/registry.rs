@@ -0,0 +1,145 @@
+//! Runtime-replaceable handles for the global lookup registries
+//!
+//! The IEEE and IANA registries are read on every lookup but only change when
+//! new data files are loaded. A `RegistryHandle` hands out cheap `Arc`
+//! snapshots to readers and lets a writer swap in a freshly built registry
+//! atomically, so long-running services can refresh their data without
+//! restarting and without blocking in-flight lookups.
+
+use std::sync::{Arc, Mutex, RwLock};
+
+/// A shared, atomically replaceable registry instance
+pub struct RegistryHandle<T> {
+    current: RwLock<Arc<T>>,
+    /// Serializes writers, so the read lock is only ever blocked by the swap
+    writer: Mutex<()>,
+}
+
+impl<T> RegistryHandle<T> {
+    /// Create a handle holding the given registry
+    pub fn new(registry: T) -> Self {
+        Self {
+            current: RwLock::new(Arc::new(registry)),
+            writer: Mutex::new(()),
+        }
+    }
+
+    /// Get a snapshot of the current registry
+    ///
+    /// The snapshot stays valid (and unchanged) even if the handle is
+    /// replaced while it is in use.
+    pub fn get(&self) -> Arc<T> {
+        // A poisoned lock still holds a fully built registry, as the
+        // swap itself cannot panic
+        self.current
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Replace the registry, returning the previous one
+    pub fn replace(&self, registry: T) -> Arc<T> {
+        let _writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        self.swap(Arc::new(registry))
+    }
+
+    /// Replace the registry with one derived from the current registry
+    ///
+    /// The new registry is built from a snapshot, so lookups carry on
+    /// against the current one meanwhile. Concurrent updates are
+    /// serialized, so no update is lost.
+    pub fn update<F>(&self, f: F)
+    where
+        F: FnOnce(&T) -> T,
+    {
+        let _writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        let updated = f(&self.get());
+        self.swap(Arc::new(updated));
+    }
+
+    /// Like `update`, but keeps the current registry if building the new one fails
+    pub fn try_update<F, E>(&self, f: F) -> Result<(), E>
+    where
+        F: FnOnce(&T) -> Result<T, E>,
+    {
+        let _writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        let updated = f(&self.get())?;
+        self.swap(Arc::new(updated));
+        Ok(())
+    }
+
+    /// Swap in a new registry; callers must hold the writer lock
+    fn swap(&self, registry: Arc<T>) -> Arc<T> {
+        let mut current = self.current.write().unwrap_or_else(|e| e.into_inner());
+        std::mem::replace(&mut *current, registry)
+    }
+}
+
+impl<T: Default> Default for RegistryHandle<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> From<T> for RegistryHandle<T> {
+    fn from(registry: T) -> Self {
+        Self::new(registry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_survives_replace() {
+        let handle = RegistryHandle::new(vec![1, 2, 3]);
+        let snapshot = handle.get();
+
+        let previous = handle.replace(vec![4]);
+        assert_eq!(*previous, vec![1, 2, 3]);
+        assert_eq!(*snapshot, vec![1, 2, 3]);
+        assert_eq!(*handle.get(), vec![4]);
+    }
+
+    #[test]
+    fn test_update() {
+        let handle = RegistryHandle::new(vec![1]);
+        handle.update(|current| {
+            let mut next = current.clone();
+            next.push(2);
+            next
+        });
+        assert_eq!(*handle.get(), vec![1, 2]);
+
+        // Lookups are not blocked while the new registry is being built
+        handle.update(|current| {
+            assert_eq!(*handle.get(), *current);
+            current.clone()
+        });
+
+        let result: Result<(), &str> = handle.try_update(|_| Err("bad data"));
+        assert!(result.is_err());
+        assert_eq!(*handle.get(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_concurrent_updates() {
+        let handle = Arc::new(RegistryHandle::new(0u32));
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let handle = Arc::clone(&handle);
+                std::thread::spawn(move || {
+                    for _ in 0..100 {
+                        handle.update(|n| n + 1);
+                    }
+                })
+            })
+            .collect();
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(*handle.get(), 800);
+    }
+}
@@ -0,0 +1,384 @@
+//! RIR delegation statistics
+//!
+//! Parses the `delegated-<registry>-extended-latest` statistics files that
+//! every Regional Internet Registry publishes and indexes them so addresses,
+//! networks and AS numbers resolve to the registry, country code, date and
+//! status of their delegation.
+
+use crate::error::{AddrFormatError, AddrResult};
+use crate::ip::{IPAddress, IPNetwork, IPRange};
+use crate::registry::RegistryHandle;
+use lazy_static::lazy_static;
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+use std::str::FromStr;
+
+/// Resource covered by a delegation record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DelegatedResource {
+    /// A block of `count` AS numbers starting at `start`
+    Asn { start: u32, count: u32 },
+    /// A block of IPv4 or IPv6 addresses
+    Addresses(IPRange),
+}
+
+/// A single record of a delegated statistics file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DelegationRecord {
+    /// Registry identifier as used in the file (`arin`, `ripencc`, `apnic`, ...)
+    pub registry: String,
+    /// ISO 3166 country code, empty for undelegated space
+    pub country_code: String,
+    pub resource: DelegatedResource,
+    /// Allocation date as `YYYY-MM-DD`, `None` for undelegated space
+    pub date: Option<String>,
+    /// `allocated`, `assigned`, `available` or `reserved`
+    pub status: String,
+    /// Opaque identifier of the resource holder (extended format only)
+    pub opaque_id: Option<String>,
+}
+
+impl DelegationRecord {
+    /// Get the address range of an IPv4/IPv6 delegation
+    pub fn range(&self) -> Option<&IPRange> {
+        match &self.resource {
+            DelegatedResource::Addresses(range) => Some(range),
+            DelegatedResource::Asn { .. } => None,
+        }
+    }
+
+    /// Get the delegated addresses as CIDR blocks
+    ///
+    /// IPv4 delegations are address counts, which need not be a power of two.
+    pub fn networks(&self) -> AddrResult<Vec<IPNetwork>> {
+        match &self.resource {
+            DelegatedResource::Addresses(range) => range.to_cidrs(),
+            DelegatedResource::Asn { .. } => Ok(Vec::new()),
+        }
+    }
+
+    /// Check if the resource has been delegated to an organisation
+    pub fn is_delegated(&self) -> bool {
+        self.status == "allocated" || self.status == "assigned"
+    }
+}
+
+/// Parse the records of a `delegated-*` statistics file
+///
+/// Comment, version and summary lines are skipped. Both the regular and the
+/// extended format (with the trailing opaque id) are accepted.
+pub fn parse_delegated_stats(data: &str) -> AddrResult<Vec<DelegationRecord>> {
+    let mut records = Vec::new();
+
+    for (index, line) in data.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split('|').collect();
+        let error = |msg: String| AddrFormatError::new(format!("Line {}: {}", index + 1, msg));
+
+        // Version line: version|registry|serial|records|startdate|enddate|UTCoffset
+        if !fields[0].is_empty() && fields[0].chars().all(|c| c.is_ascii_digit() || c == '.') {
+            continue;
+        }
+        // Summary line: registry|*|type|*|count|summary
+        if fields.get(5) == Some(&"summary") {
+            continue;
+        }
+        if fields.len() < 7 {
+            return Err(error(format!("expected at least 7 fields, found {}", fields.len())));
+        }
+
+        if fields[0].is_empty() {
+            return Err(error("missing registry".to_string()));
+        }
+
+        let (start, value) = (fields[3], fields[4]);
+        let resource = match fields[2] {
+            "asn" => {
+                let start = start.parse().map_err(|_| error(format!("invalid AS number '{}'", start)))?;
+                let count = value.parse().map_err(|_| error(format!("invalid AS count '{}'", value)))?;
+                if count == 0 || u32::MAX - (count - 1) < start {
+                    return Err(error(format!("AS block {}+{} out of range", start, count)));
+                }
+                DelegatedResource::Asn { start, count }
+            }
+            "ipv4" => {
+                let first = Ipv4Addr::from_str(start)
+                    .map_err(|_| error(format!("invalid IPv4 address '{}'", start)))?;
+                let count: u64 = value.parse().map_err(|_| error(format!("invalid address count '{}'", value)))?;
+                let last = (u32::from(first) as u64 + count)
+                    .checked_sub(1)
+                    .filter(|&last| count > 0 && last <= u32::MAX as u64)
+                    .ok_or_else(|| error(format!("address block {}+{} out of range", start, count)))?;
+                DelegatedResource::Addresses(IPRange::new(
+                    IPAddress::new_v4(first),
+                    IPAddress::new_v4(Ipv4Addr::from(last as u32)),
+                )?)
+            }
+            "ipv6" => {
+                let network = IPNetwork::from_str(&format!("{}/{}", start, value))
+                    .ok()
+                    .filter(|network| network.is_ipv6())
+                    .ok_or_else(|| error(format!("invalid IPv6 prefix '{}/{}'", start, value)))?;
+                let first = address_value(network.network_address());
+                let last = first | host_mask(network.prefix_length(), 128);
+                DelegatedResource::Addresses(IPRange::new(
+                    IPAddress::new_v6(Ipv6Addr::from(first)),
+                    IPAddress::new_v6(Ipv6Addr::from(last)),
+                )?)
+            }
+            other => return Err(error(format!("unknown resource type '{}'", other))),
+        };
+
+        let date = fields[5];
+        let date = match date {
+            "" | "00000000" => None,
+            d if d.len() == 8 && d.chars().all(|c| c.is_ascii_digit()) => {
+                Some(format!("{}-{}-{}", &d[..4], &d[4..6], &d[6..]))
+            }
+            d => return Err(error(format!("invalid date '{}'", d))),
+        };
+
+        records.push(DelegationRecord {
+            registry: fields[0].to_string(),
+            country_code: fields[1].to_string(),
+            resource,
+            date,
+            status: fields[6].to_string(),
+            opaque_id: fields.get(7).filter(|id| !id.is_empty()).map(|id| id.to_string()),
+        });
+    }
+
+    Ok(records)
+}
+
+/// Index of delegation records for address and AS number lookups
+///
+/// Each resource type is kept as a list of `(first, last, record)` spans
+/// sorted by start, so lookups are binary searches. Delegations from the
+/// different RIRs do not overlap, so the files of all five registries can be
+/// loaded into one index.
+#[derive(Debug, Clone, Default)]
+pub struct DelegationRegistry {
+    records: Vec<DelegationRecord>,
+    ipv4: Vec<(u128, u128, usize)>,
+    ipv6: Vec<(u128, u128, usize)>,
+    asns: Vec<(u128, u128, usize)>,
+}
+
+impl DelegationRegistry {
+    /// Create a new empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry from the contents of a delegated statistics file
+    pub fn from_delegated_stats(data: &str) -> AddrResult<Self> {
+        let mut registry = Self::new();
+        registry.load_delegated_stats(data)?;
+        Ok(registry)
+    }
+
+    /// Add a single record
+    pub fn add_record(&mut self, record: DelegationRecord) {
+        self.extend(std::iter::once(record));
+    }
+
+    /// Load the records of a delegated statistics file, returning the number of records added
+    ///
+    /// Nothing is added if any record is invalid.
+    pub fn load_delegated_stats(&mut self, data: &str) -> AddrResult<usize> {
+        let records = parse_delegated_stats(data)?;
+        let count = records.len();
+        self.extend(records);
+        Ok(count)
+    }
+
+    /// Load a delegated statistics file from disk
+    pub fn load_from_file(&mut self, path: impl AsRef<Path>) -> Result<usize, Box<dyn std::error::Error>> {
+        let data = fs::read_to_string(path)?;
+        Ok(self.load_delegated_stats(&data)?)
+    }
+
+    fn extend(&mut self, records: impl IntoIterator<Item = DelegationRecord>) {
+        for record in records {
+            let index = self.records.len();
+            match &record.resource {
+                DelegatedResource::Asn { start, count } => {
+                    self.asns.push((*start as u128, *start as u128 + *count as u128 - 1, index));
+                }
+                DelegatedResource::Addresses(range) => {
+                    let span = (address_value(range.start()), address_value(range.end()), index);
+                    if range.is_ipv4() {
+                        self.ipv4.push(span);
+                    } else {
+                        self.ipv6.push(span);
+                    }
+                }
+            }
+            self.records.push(record);
+        }
+
+        for spans in [&mut self.ipv4, &mut self.ipv6, &mut self.asns] {
+            spans.sort_by_key(|&(first, last, _)| (first, last));
+        }
+    }
+
+    fn find(&self, spans: &[(u128, u128, usize)], first: u128, last: u128) -> Option<&DelegationRecord> {
+        let position = spans.partition_point(|&(start, _, _)| start <= first);
+        let &(_, end, index) = spans[..position].last()?;
+        (last <= end).then(|| &self.records[index])
+    }
+
+    /// Look up the delegation containing an address
+    pub fn lookup_address(&self, addr: &IPAddress) -> Option<&DelegationRecord> {
+        let value = address_value(addr);
+        let spans = if addr.is_ipv4() { &self.ipv4 } else { &self.ipv6 };
+        self.find(spans, value, value)
+    }
+
+    /// Look up the delegation containing a whole network
+    pub fn lookup_network(&self, network: &IPNetwork) -> Option<&DelegationRecord> {
+        let first = address_value(network.network_address());
+        let last = first | host_mask(network.prefix_length(), if network.is_ipv4() { 32 } else { 128 });
+        let spans = if network.is_ipv4() { &self.ipv4 } else { &self.ipv6 };
+        self.find(spans, first, last)
+    }
+
+    /// Look up the delegation containing an AS number
+    pub fn lookup_asn(&self, asn: u32) -> Option<&DelegationRecord> {
+        self.find(&self.asns, asn as u128, asn as u128)
+    }
+
+    /// Get all records in load order
+    pub fn records(&self) -> &[DelegationRecord] {
+        &self.records
+    }
+
+    /// Get the number of records
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Check if the registry is empty
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+}
+
+fn address_value(addr: &IPAddress) -> u128 {
+    match addr.as_ip_addr() {
+        IpAddr::V4(addr) => u32::from(*addr) as u128,
+        IpAddr::V6(addr) => u128::from(*addr),
+    }
+}
+
+fn host_mask(prefix_length: u8, width: u32) -> u128 {
+    let host_bits = width - prefix_length as u32;
+    if host_bits == 0 {
+        0
+    } else {
+        u128::MAX >> (128 - host_bits)
+    }
+}
+
+lazy_static! {
+    /// Global delegation registry (empty until statistics files are loaded)
+    pub static ref RIR_DELEGATIONS: RegistryHandle<DelegationRegistry> = RegistryHandle::default();
+}
+
+/// Replace the global delegation registry with the contents of delegated statistics files
+pub fn reload_delegations<P: AsRef<Path>>(paths: &[P]) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut registry = DelegationRegistry::new();
+    let mut count = 0;
+    for path in paths {
+        count += registry.load_from_file(path)?;
+    }
+    RIR_DELEGATIONS.replace(registry);
+    Ok(count)
+}
+
+/// Look up the delegation of an address in the global registry
+pub fn lookup_delegation(addr: &IPAddress) -> Option<DelegationRecord> {
+    RIR_DELEGATIONS.get().lookup_address(addr).cloned()
+}
+
+/// Look up the delegation of a network in the global registry
+pub fn lookup_network_delegation(network: &IPNetwork) -> Option<DelegationRecord> {
+    RIR_DELEGATIONS.get().lookup_network(network).cloned()
+}
+
+/// Look up the delegation of an AS number in the global registry
+pub fn lookup_asn_delegation(asn: u32) -> Option<DelegationRecord> {
+    RIR_DELEGATIONS.get().lookup_asn(asn).cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_STATS: &str = "\
+2.3|apnic|20240101|6|19830613|20231231|+1000
+# comment line
+apnic|*|asn|*|2|summary
+apnic|*|ipv4|*|3|summary
+apnic|*|ipv6|*|1|summary
+apnic|JP|asn|173|1|20020801|allocated|A91A7381
+apnic|AU|asn|4608|2|19960101|allocated|A9149F3E
+apnic|AU|ipv4|1.0.0.0|256|20110811|assigned|A91872ED
+apnic|CN|ipv4|1.0.1.0|768|20110414|allocated|A92E1062
+apnic||ipv4|1.0.4.0|1024||available|
+apnic|JP|ipv6|2001:200::|35|19990813|allocated|A91A7381
+";
+
+    #[test]
+    fn test_parse_delegated_stats() {
+        let records = parse_delegated_stats(SAMPLE_STATS).unwrap();
+        assert_eq!(records.len(), 6);
+
+        assert_eq!(records[1].resource, DelegatedResource::Asn { start: 4608, count: 2 });
+        assert_eq!(records[3].country_code, "CN");
+        assert_eq!(records[3].date.as_deref(), Some("2011-04-14"));
+        assert_eq!(records[3].range().unwrap().end().to_string(), "1.0.3.255");
+        let networks: Vec<String> = records[3].networks().unwrap().iter().map(|n| n.to_string()).collect();
+        assert_eq!(networks, vec!["1.0.1.0/24", "1.0.2.0/23"]);
+
+        assert_eq!(records[4].date, None);
+        assert_eq!(records[4].opaque_id, None);
+        assert!(!records[4].is_delegated());
+        assert_eq!(records[5].range().unwrap().end().to_string(), "2001:200:1fff:ffff:ffff:ffff:ffff:ffff");
+
+        for bad in ["apnic|AU|ipv4|1.0.0|256|20110811|assigned", "apnic|AU|ipv4|1.0.0.0|256|2011|assigned",
+                    "apnic|AU|asn|x|1|20110811|assigned", "apnic|AU|ipv5|::|32|20110811|assigned",
+                    "apnic|AU|ipv4|255.255.255.0|512|20110811|assigned", "|AU|ipv4|1.0.0.0|256|20110811|assigned"] {
+            let err = parse_delegated_stats(bad).unwrap_err();
+            assert!(err.to_string().contains("Line 1"), "{}", bad);
+        }
+        let err = parse_delegated_stats("|AU|ipv4|1.0.0.0|256|20110811|assigned").unwrap_err();
+        assert!(err.to_string().contains("missing registry"));
+    }
+
+    #[test]
+    fn test_delegation_lookup() {
+        let registry = DelegationRegistry::from_delegated_stats(SAMPLE_STATS).unwrap();
+        assert_eq!(registry.len(), 6);
+
+        let record = registry.lookup_address(&IPAddress::from_str("1.0.2.77").unwrap()).unwrap();
+        assert_eq!(record.country_code, "CN");
+        assert_eq!(record.registry, "apnic");
+        assert!(registry.lookup_address(&IPAddress::from_str("1.0.8.0").unwrap()).is_none());
+
+        let network = IPNetwork::from_str("2001:200:1::/48").unwrap();
+        assert_eq!(registry.lookup_network(&network).unwrap().country_code, "JP");
+        let too_wide = IPNetwork::from_str("1.0.0.0/22").unwrap();
+        assert!(registry.lookup_network(&too_wide).is_none());
+
+        assert_eq!(registry.lookup_asn(4609).unwrap().country_code, "AU");
+        assert!(registry.lookup_asn(4610).is_none());
+        assert!(registry.lookup_asn(172).is_none());
+    }
+}
@@ -0,0 +1,367 @@
+//! Serde support for address types (enabled by the `serde` feature)
+//!
+//! Human-readable formats such as JSON or TOML use the canonical string form
+//! of each type and parse it back through the type's `FromStr` implementation,
+//! so deserialized values are validated exactly like user input. Compact
+//! formats such as bincode use the raw byte representation instead.
+
+use crate::error::{AddrFormatError, AddrResult};
+use crate::eui::{EUI, EUI64, MAC, OUI};
+use crate::ip::{IPAddress, IPNetwork, IPRange};
+use crate::sets::IPSet;
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Error as _, SerializeSeq, Serializer};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+/// Conversion to and from the byte encoding used by non human-readable formats
+trait CompactBytes: Sized {
+    fn to_compact(&self) -> Vec<u8>;
+    fn from_compact(bytes: &[u8]) -> AddrResult<Self>;
+}
+
+/// Encoded as the address bytes followed by the UTF-8 bytes of any IPv6 zone
+impl CompactBytes for IPAddress {
+    fn to_compact(&self) -> Vec<u8> {
+        let mut bytes = self.to_binary();
+        bytes.extend(self.zone().unwrap_or_default().as_bytes());
+        bytes
+    }
+
+    fn from_compact(bytes: &[u8]) -> AddrResult<Self> {
+        let (addr, zone) = split_compact_address(bytes)?;
+        with_compact_zone(addr, zone)
+    }
+}
+
+/// Encoded as the network address bytes, the prefix length, then any IPv6 zone
+impl CompactBytes for IPNetwork {
+    fn to_compact(&self) -> Vec<u8> {
+        let mut bytes = self.network_address().to_binary();
+        bytes.push(self.prefix_length());
+        bytes.extend(self.network_address().zone().unwrap_or_default().as_bytes());
+        bytes
+    }
+
+    fn from_compact(bytes: &[u8]) -> AddrResult<Self> {
+        let (addr, rest) = split_compact_address(bytes)?;
+        match rest.split_first() {
+            Some((&prefix_length, zone)) => IPNetwork::new(with_compact_zone(addr, zone)?, prefix_length),
+            None => Err(AddrFormatError::new("Missing IP network prefix length")),
+        }
+    }
+}
+
+/// Split the leading address from a compact encoding; anything shorter than
+/// an IPv6 address starts with an IPv4 address
+fn split_compact_address(bytes: &[u8]) -> AddrResult<(IPAddress, &[u8])> {
+    let len = if bytes.len() < 16 { 4 } else { 16 };
+    if bytes.len() < len {
+        return Err(AddrFormatError::new(format!(
+            "Invalid IP address length: {} (expected 4 or 16 bytes)",
+            bytes.len()
+        )));
+    }
+    let (addr, rest) = bytes.split_at(len);
+    Ok((IPAddress::from_bytes(addr)?, rest))
+}
+
+fn with_compact_zone(addr: IPAddress, zone: &[u8]) -> AddrResult<IPAddress> {
+    if zone.is_empty() {
+        return Ok(addr);
+    }
+    let zone = std::str::from_utf8(zone).map_err(|_| AddrFormatError::new("IPv6 zone is not valid UTF-8"))?;
+    addr.with_zone(zone)
+}
+
+/// Encoded as the start address bytes followed by the end address bytes
+impl CompactBytes for IPRange {
+    fn to_compact(&self) -> Vec<u8> {
+        let mut bytes = self.start().to_binary();
+        bytes.extend(self.end().to_binary());
+        bytes
+    }
+
+    fn from_compact(bytes: &[u8]) -> AddrResult<Self> {
+        if bytes.len() != 8 && bytes.len() != 32 {
+            return Err(AddrFormatError::new(format!(
+                "Invalid IP range length: {} (expected 8 or 32 bytes)",
+                bytes.len()
+            )));
+        }
+
+        let (start, end) = bytes.split_at(bytes.len() / 2);
+        IPRange::new(IPAddress::from_bytes(start)?, IPAddress::from_bytes(end)?)
+    }
+}
+
+impl CompactBytes for MAC {
+    fn to_compact(&self) -> Vec<u8> {
+        self.bytes().to_vec()
+    }
+
+    fn from_compact(bytes: &[u8]) -> AddrResult<Self> {
+        MAC::from_bytes(bytes)
+    }
+}
+
+impl CompactBytes for EUI64 {
+    fn to_compact(&self) -> Vec<u8> {
+        self.bytes().to_vec()
+    }
+
+    fn from_compact(bytes: &[u8]) -> AddrResult<Self> {
+        EUI64::from_bytes(bytes)
+    }
+}
+
+impl CompactBytes for EUI {
+    fn to_compact(&self) -> Vec<u8> {
+        self.bytes()
+    }
+
+    fn from_compact(bytes: &[u8]) -> AddrResult<Self> {
+        EUI::from_bytes(bytes)
+    }
+}
+
+impl CompactBytes for OUI {
+    fn to_compact(&self) -> Vec<u8> {
+        self.bytes().to_vec()
+    }
+
+    fn from_compact(bytes: &[u8]) -> AddrResult<Self> {
+        let bytes: [u8; 3] = bytes
+            .try_into()
+            .map_err(|_| AddrFormatError::new("OUI must be exactly 3 bytes"))?;
+        Ok(OUI::new(bytes))
+    }
+}
+
+/// Visitor accepting either the string form or the compact byte form of `T`
+struct AddrVisitor<T> {
+    expecting: &'static str,
+    marker: PhantomData<T>,
+}
+
+impl<'de, T> Visitor<'de> for AddrVisitor<T>
+where
+    T: FromStr + CompactBytes,
+    T::Err: fmt::Display,
+{
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.expecting)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<T, E> {
+        T::from_str(v).map_err(E::custom)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<T, E> {
+        T::from_compact(v).map_err(E::custom)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<T, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element::<u8>()? {
+            bytes.push(byte);
+        }
+        T::from_compact(&bytes).map_err(de::Error::custom)
+    }
+}
+
+macro_rules! impl_serde {
+    ($($ty:ty => $expecting:expr),* $(,)?) => {
+        $(
+            impl Serialize for $ty {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    if serializer.is_human_readable() {
+                        serializer.collect_str(self)
+                    } else {
+                        serializer.serialize_bytes(&self.to_compact())
+                    }
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $ty {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let visitor = AddrVisitor { expecting: $expecting, marker: PhantomData };
+                    if deserializer.is_human_readable() {
+                        deserializer.deserialize_str(visitor)
+                    } else {
+                        deserializer.deserialize_bytes(visitor)
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_serde! {
+    IPAddress => "an IP address",
+    IPNetwork => "an IP network in CIDR notation",
+    IPRange => "an IP range",
+    MAC => "a MAC address",
+    EUI64 => "an EUI-64 identifier",
+    EUI => "a MAC-48 or EUI-64 identifier",
+    OUI => "an IEEE OUI",
+}
+
+/// Parse a single IP set member: a CIDR, a hyphenated range or a bare address
+fn parse_set_member(s: &str) -> AddrResult<IPSet> {
+    if s.contains('/') {
+        IPSet::from_network(IPNetwork::from_str(s)?)
+    } else if s.contains('-') {
+        Ok(IPSet::from_range(IPRange::from_str(s)?))
+    } else {
+        IPSet::from_address(IPAddress::from_str(s)?)
+    }
+}
+
+/// Serialized as a list of CIDR strings, or as a list of ranges in compact formats
+impl Serialize for IPSet {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            let networks = self.networks().map_err(S::Error::custom)?;
+            let mut seq = serializer.serialize_seq(Some(networks.len()))?;
+            for network in &networks {
+                seq.serialize_element(network)?;
+            }
+            seq.end()
+        } else {
+            let ranges = self.ranges();
+            let mut seq = serializer.serialize_seq(Some(ranges.len()))?;
+            for range in &ranges {
+                seq.serialize_element(range)?;
+            }
+            seq.end()
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for IPSet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let members = Vec::<String>::deserialize(deserializer)?;
+            let mut set = IPSet::new();
+            for member in &members {
+                let member_set = parse_set_member(member).map_err(de::Error::custom)?;
+                set = set.union(&member_set).map_err(de::Error::custom)?;
+            }
+            Ok(set)
+        } else {
+            let ranges = Vec::<IPRange>::deserialize(deserializer)?;
+            IPSet::from_ranges(&ranges).map_err(de::Error::custom)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::iana::IANARegistryInfo;
+
+    fn json_round_trip<T>(value: &T, expected: &str)
+    where
+        T: Serialize + for<'de> Deserialize<'de> + PartialEq + fmt::Debug,
+    {
+        let json = serde_json::to_string(value).unwrap();
+        assert_eq!(json, expected);
+        let back: T = serde_json::from_str(&json).unwrap();
+        assert_eq!(&back, value);
+    }
+
+    fn bincode_round_trip<T>(value: &T)
+    where
+        T: Serialize + for<'de> Deserialize<'de> + PartialEq + fmt::Debug,
+    {
+        let bytes = bincode::serialize(value).unwrap();
+        let back: T = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(&back, value);
+    }
+
+    #[test]
+    fn test_ip_types_json() {
+        json_round_trip(&IPAddress::from_str("192.168.1.1").unwrap(), "\"192.168.1.1\"");
+        json_round_trip(&IPAddress::from_str("2001:db8::1").unwrap(), "\"2001:db8::1\"");
+        json_round_trip(&IPNetwork::from_str("10.0.0.0/8").unwrap(), "\"10.0.0.0/8\"");
+        json_round_trip(
+            &IPRange::from_str("192.168.1.1-192.168.1.10").unwrap(),
+            "\"192.168.1.1-192.168.1.10\"",
+        );
+    }
+
+    #[test]
+    fn test_eui_types_json() {
+        json_round_trip(&MAC::from_str("00:11:22:33:44:55").unwrap(), "\"00:11:22:33:44:55\"");
+        json_round_trip(
+            &EUI64::from_str("00:11:22:ff:fe:33:44:55").unwrap(),
+            "\"00:11:22:ff:fe:33:44:55\"",
+        );
+        json_round_trip(&EUI::from_str("00:11:22:33:44:55").unwrap(), "\"00:11:22:33:44:55\"");
+        json_round_trip(&OUI::from_str("00:1b:63").unwrap(), "\"00:1b:63\"");
+    }
+
+    #[test]
+    fn test_json_validation() {
+        assert!(serde_json::from_str::<IPAddress>("\"192.168.1.256\"").is_err());
+        assert!(serde_json::from_str::<IPNetwork>("\"10.0.0.0/33\"").is_err());
+        assert!(serde_json::from_str::<IPRange>("\"10.0.0.9-10.0.0.1\"").is_err());
+        assert!(serde_json::from_str::<MAC>("\"00:11:22\"").is_err());
+        assert!(serde_json::from_str::<IPAddress>("42").is_err());
+    }
+
+    #[test]
+    fn test_compact_round_trip() {
+        bincode_round_trip(&IPAddress::from_str("192.168.1.1").unwrap());
+        bincode_round_trip(&IPAddress::from_str("2001:db8::1").unwrap());
+        bincode_round_trip(&IPNetwork::from_str("2001:db8::/32").unwrap());
+        bincode_round_trip(&IPRange::from_str("10.0.0.1-10.0.0.99").unwrap());
+        bincode_round_trip(&MAC::from_str("00:11:22:33:44:55").unwrap());
+        bincode_round_trip(&EUI::from_str("00:11:22:ff:fe:33:44:55").unwrap());
+        bincode_round_trip(&OUI::from_str("00:1b:63").unwrap());
+        bincode_round_trip(&IPAddress::from_str("fe80::1%eth0").unwrap());
+        bincode_round_trip(&IPNetwork::from_str("fe80::%eth0/64").unwrap());
+        bincode_round_trip(&IPNetwork::from_str("10.0.0.0/8").unwrap());
+
+        // 8 byte length prefix followed by the raw octets
+        let bytes = bincode::serialize(&IPAddress::from_str("10.0.0.1").unwrap()).unwrap();
+        assert_eq!(bytes.len(), 12);
+        assert_eq!(&bytes[8..], &[10, 0, 0, 1]);
+    }
+
+    #[test]
+    fn test_ip_set() {
+        let mut set = IPSet::new();
+        set.add_network(IPNetwork::from_str("192.168.1.0/24").unwrap()).unwrap();
+        set.add_address(IPAddress::from_str("10.0.0.1").unwrap()).unwrap();
+
+        json_round_trip(&set, "[\"10.0.0.1/32\",\"192.168.1.0/24\"]");
+        bincode_round_trip(&set);
+
+        let parsed: IPSet =
+            serde_json::from_str("[\"10.0.0.1-10.0.0.3\", \"10.0.0.4\", \"2001:db8::/127\"]").unwrap();
+        assert_eq!(parsed.size(), 6);
+        assert!(serde_json::from_str::<IPSet>("[\"10.0.0.0/40\"]").is_err());
+    }
+
+    #[test]
+    fn test_iana_registry_info() {
+        let info = IANARegistryInfo {
+            designation: "10.0.0.0/8".to_string(),
+            date: "1996-02".to_string(),
+            whois: "whois.iana.org".to_string(),
+            rdap: "".to_string(),
+            status: vec!["RESERVED".to_string()],
+            notes: "Private-Use Networks".to_string(),
+        };
+
+        let json = serde_json::to_string(&info).unwrap();
+        let back: IANARegistryInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, info);
+    }
+}
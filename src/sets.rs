@@ -1,82 +1,103 @@
 //! IP set operations - unions, intersections, and other set-based operations
 
-use crate::error::{AddrFormatError, AddrResult};
-use crate::ip::{IPAddress, IPNetwork, IPRange, IPAddressType};
-use std::collections::BTreeSet;
+use crate::error::AddrResult;
+use crate::ip::range::check_unzoned;
+use crate::ip::{AddressCount, ClasslessDelegation, ClasslessLabel, IPAddress, IPNetwork, IPRange, ReverseZone};
+use std::collections::BTreeMap;
 use std::fmt;
 use std::ops::{BitAnd, BitOr, BitXor, Sub};
 
 /// A set of IP addresses and networks that supports efficient set operations
-#[derive(Debug, Clone, PartialEq, Eq)]
+///
+/// The set is stored as disjoint, non-adjacent ranges in an ordered map from
+/// range start to range end, so adding, removing and membership tests are
+/// O(log n) in the number of stored ranges.
+///
+/// Like [`IPRange`], a set never holds zoned IPv6 addresses: adding or
+/// removing one is an error, membership tests for one are false, and the
+/// infallible `FromIterator` and `Extend` impls skip them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct IPSet {
-    ranges: BTreeSet<IPRange>,
+    ranges: BTreeMap<IPAddress, IPAddress>,
+}
+
+/// Reject a zoned range, which only `IPRange::new_unchecked` can build
+fn check_range_unzoned(range: &IPRange) -> AddrResult<()> {
+    check_unzoned(range.start())?;
+    check_unzoned(range.end())
+}
+
+/// Check if a range ending at `end` can be merged with one starting at `start`
+fn touches(end: &IPAddress, start: &IPAddress) -> bool {
+    start <= end || end.next().as_ref() == Some(start)
 }
 
 impl IPSet {
     /// Create a new empty IP set
     pub fn new() -> Self {
         Self {
-            ranges: BTreeSet::new(),
+            ranges: BTreeMap::new(),
         }
     }
 
     /// Create an IP set from a single address
     pub fn from_address(addr: IPAddress) -> AddrResult<Self> {
-        let range = IPRange::new(addr.clone(), addr)?;
-        let mut set = Self::new();
-        set.ranges.insert(range);
-        Ok(set)
+        Ok(Self::from_range(IPRange::new(addr.clone(), addr)?))
     }
 
     /// Create an IP set from a single network
     pub fn from_network(network: IPNetwork) -> AddrResult<Self> {
-        let start = network.network_address().clone();
-        let end = match network.ip_type() {
-            IPAddressType::IPv4 => network.broadcast_address()?,
-            IPAddressType::IPv6 => {
-                network.last_host().ok_or_else(|| {
-                    AddrFormatError::new("Cannot determine last address of IPv6 network")
-                })?
-            }
-        };
-        let range = IPRange::new(start, end)?;
-        let mut set = Self::new();
-        set.ranges.insert(range);
-        Ok(set)
+        check_unzoned(network.network_address())?;
+        Ok(Self::from_range(IPRange::from(&network)))
     }
 
     /// Create an IP set from a range
     pub fn from_range(range: IPRange) -> Self {
         let mut set = Self::new();
-        set.ranges.insert(range);
+        set.ranges.insert(range.start().clone(), range.end().clone());
         set
     }
 
     /// Create an IP set from multiple addresses
     pub fn from_addresses(addresses: &[IPAddress]) -> AddrResult<Self> {
-        let mut set = Self::new();
-        for addr in addresses {
-            set.add_address(addr.clone())?;
-        }
-        Ok(set)
+        addresses.iter().try_for_each(check_unzoned)?;
+        Ok(addresses.iter().cloned().collect())
     }
 
     /// Create an IP set from multiple networks
     pub fn from_networks(networks: &[IPNetwork]) -> AddrResult<Self> {
-        let mut set = Self::new();
-        for network in networks {
-            set.add_network(network.clone())?;
-        }
-        Ok(set)
+        networks.iter().try_for_each(|network| check_unzoned(network.network_address()))?;
+        Ok(networks.iter().cloned().collect())
     }
 
     /// Create an IP set from multiple ranges
     pub fn from_ranges(ranges: &[IPRange]) -> AddrResult<Self> {
-        let mut set = Self::new();
-        for range in ranges {
-            set.add_range(range.clone())?;
+        ranges.iter().try_for_each(check_range_unzoned)?;
+        Ok(ranges.iter().cloned().collect())
+    }
+
+    /// Build a set from arbitrary ranges by sorting and merging them in one pass
+    ///
+    /// Zoned ranges are skipped.
+    fn from_unsorted_ranges(mut ranges: Vec<(IPAddress, IPAddress)>) -> Self {
+        ranges.retain(|(start, end)| start.zone().is_none() && end.zone().is_none());
+        ranges.sort_unstable();
+
+        let mut merged: Vec<(IPAddress, IPAddress)> = Vec::with_capacity(ranges.len());
+        for (start, end) in ranges {
+            match merged.last_mut() {
+                Some((_, last_end)) if touches(last_end, &start) => {
+                    if end > *last_end {
+                        *last_end = end;
+                    }
+                }
+                _ => merged.push((start, end)),
+            }
+        }
+
+        Self {
+            ranges: merged.into_iter().collect(),
         }
-        Ok(set)
     }
 
     /// Add a single address to the set
@@ -87,65 +108,45 @@ impl IPSet {
 
     /// Add a network to the set
     pub fn add_network(&mut self, network: IPNetwork) -> AddrResult<()> {
-        let start = network.network_address().clone();
-        let end = match network.ip_type() {
-            IPAddressType::IPv4 => network.broadcast_address()?,
-            IPAddressType::IPv6 => {
-                network.last_host().ok_or_else(|| {
-                    AddrFormatError::new("Cannot determine last address of IPv6 network")
-                })?
-            }
-        };
-        let range = IPRange::new(start, end)?;
-        self.add_range(range)
+        check_unzoned(network.network_address())?;
+        self.add_range(IPRange::from(&network))
     }
 
     /// Add a range to the set
     pub fn add_range(&mut self, range: IPRange) -> AddrResult<()> {
-        // Collect overlapping ranges
-        let mut overlapping = Vec::new();
-        let mut to_remove = Vec::new();
-
-        for existing_range in &self.ranges {
-            if existing_range.overlaps(&range) || self.ranges_adjacent(existing_range, &range) {
-                overlapping.push(existing_range.clone());
-                to_remove.push(existing_range.clone());
+        check_range_unzoned(&range)?;
+        let mut start = range.start().clone();
+        let mut end = range.end().clone();
+
+        // Merge with the range starting at or before the new one
+        if let Some((prev_start, prev_end)) = self.ranges.range(..=&start).next_back() {
+            if touches(prev_end, &start) {
+                start = prev_start.clone();
+                if *prev_end > end {
+                    end = prev_end.clone();
+                }
             }
         }
 
-        // Remove overlapping ranges
-        for range_to_remove in to_remove {
-            self.ranges.remove(&range_to_remove);
-        }
-
-        // Merge all overlapping ranges with the new range
-        let mut merged_ranges = overlapping;
-        merged_ranges.push(range);
-
-        let final_ranges = crate::ip::range::merge_ranges(&merged_ranges)?;
-
-        // Add the merged ranges back
-        for merged_range in final_ranges {
-            self.ranges.insert(merged_range);
+        // Absorb ranges starting inside or right after the new one
+        let absorbed: Vec<IPAddress> = self
+            .ranges
+            .range(&start..)
+            .take_while(|(next_start, _)| touches(&end, next_start))
+            .map(|(next_start, _)| next_start.clone())
+            .collect();
+        for next_start in absorbed {
+            if let Some(next_end) = self.ranges.remove(&next_start) {
+                if next_end > end {
+                    end = next_end;
+                }
+            }
         }
 
+        self.ranges.insert(start, end);
         Ok(())
     }
 
-    /// Check if two ranges are adjacent (can be merged)
-    fn ranges_adjacent(&self, range1: &IPRange, range2: &IPRange) -> bool {
-        if range1.version() != range2.version() {
-            return false;
-        }
-
-        // Check if range1.end + 1 == range2.start or range2.end + 1 == range1.start
-        if let (Some(next1), Some(next2)) = (range1.end().next(), range2.end().next()) {
-            *range2.start() == next1 || *range1.start() == next2
-        } else {
-            false
-        }
-    }
-
     /// Remove an address from the set
     pub fn remove_address(&mut self, addr: &IPAddress) -> AddrResult<()> {
         let single_range = IPRange::new(addr.clone(), addr.clone())?;
@@ -154,86 +155,68 @@ impl IPSet {
 
     /// Remove a network from the set
     pub fn remove_network(&mut self, network: &IPNetwork) -> AddrResult<()> {
-        let start = network.network_address().clone();
-        let end = match network.ip_type() {
-            IPAddressType::IPv4 => network.broadcast_address()?,
-            IPAddressType::IPv6 => {
-                network.last_host().ok_or_else(|| {
-                    AddrFormatError::new("Cannot determine last address of IPv6 network")
-                })?
-            }
-        };
-        let range = IPRange::new(start, end)?;
-        self.remove_range(&range)
+        check_unzoned(network.network_address())?;
+        self.remove_range(&IPRange::from(network))
     }
 
     /// Remove a range from the set
     pub fn remove_range(&mut self, range_to_remove: &IPRange) -> AddrResult<()> {
-        let mut new_ranges = BTreeSet::new();
+        check_range_unzoned(range_to_remove)?;
+        let (start, end) = (range_to_remove.start(), range_to_remove.end());
 
-        for existing_range in &self.ranges {
-            if !existing_range.overlaps(range_to_remove) {
-                // No overlap, keep the range as-is
-                new_ranges.insert(existing_range.clone());
-            } else {
-                // There's overlap, need to split or exclude
-                if range_to_remove.start() > existing_range.start() {
-                    // Part before the removal range
-                    let before_end = range_to_remove.start().prev()
-                        .ok_or_else(|| AddrFormatError::new("Cannot create range before minimum address"))?;
-                    let before_range = IPRange::new(existing_range.start().clone(), before_end)?;
-                    new_ranges.insert(before_range);
-                }
+        // Ranges overlapping the removal: possibly one starting before it,
+        // plus every range starting inside it
+        let mut overlapping = Vec::new();
+        if let Some((prev_start, prev_end)) = self.ranges.range(..start).next_back() {
+            if prev_end >= start {
+                overlapping.push(prev_start.clone());
+            }
+        }
+        overlapping.extend(self.ranges.range(start..=end).map(|(s, _)| s.clone()));
 
-                if range_to_remove.end() < existing_range.end() {
-                    // Part after the removal range
-                    let after_start = range_to_remove.end().next()
-                        .ok_or_else(|| AddrFormatError::new("Cannot create range after maximum address"))?;
-                    let after_range = IPRange::new(after_start, existing_range.end().clone())?;
-                    new_ranges.insert(after_range);
-                }
+        for existing_start in overlapping {
+            let existing_end = self.ranges.remove(&existing_start).expect("range is stored");
+            if existing_start < *start {
+                let before_end = start.prev().expect("start is above another address");
+                self.ranges.insert(existing_start, before_end);
+            }
+            if existing_end > *end {
+                let after_start = end.next().expect("end is below another address");
+                self.ranges.insert(after_start, existing_end);
             }
         }
 
-        self.ranges = new_ranges;
         Ok(())
     }
 
+    /// Find the stored range containing an address
+    ///
+    /// The set holds no zones, so a zoned address is never contained.
+    fn range_containing(&self, addr: &IPAddress) -> Option<(&IPAddress, &IPAddress)> {
+        if addr.zone().is_some() {
+            return None;
+        }
+        self.ranges
+            .range(..=addr)
+            .next_back()
+            .filter(|(_, end)| *end >= addr)
+    }
+
     /// Check if the set contains an address
     pub fn contains_address(&self, addr: &IPAddress) -> bool {
-        self.ranges.iter().any(|range| range.contains(addr))
+        self.range_containing(addr).is_some()
     }
 
     /// Check if the set contains a network
     pub fn contains_network(&self, network: &IPNetwork) -> bool {
-        let network_start = network.network_address();
-        let network_end = match network.ip_type() {
-            IPAddressType::IPv4 => {
-                if let Ok(broadcast) = network.broadcast_address() {
-                    broadcast
-                } else {
-                    return false;
-                }
-            }
-            IPAddressType::IPv6 => {
-                if let Some(last) = network.last_host() {
-                    last
-                } else {
-                    return false;
-                }
-            }
-        };
-
-        self.ranges.iter().any(|range| {
-            range.contains(network_start) && range.contains(&network_end)
-        })
+        network.network_address().zone().is_none() && self.contains_range(&IPRange::from(network))
     }
 
     /// Check if the set contains a range
     pub fn contains_range(&self, range_to_check: &IPRange) -> bool {
-        self.ranges.iter().any(|range| {
-            range.contains(range_to_check.start()) && range.contains(range_to_check.end())
-        })
+        // Stored ranges never touch, so a contained range lies within a single one
+        self.range_containing(range_to_check.start())
+            .is_some_and(|(_, end)| range_to_check.end().zone().is_none() && end >= range_to_check.end())
     }
 
     /// Check if the set is empty
@@ -242,19 +225,51 @@ impl IPSet {
     }
 
     /// Get the size (number of IP addresses) in the set
-    pub fn size(&self) -> u128 {
-        self.ranges.iter().map(|range| range.size()).sum()
+    pub fn size(&self) -> AddressCount {
+        self.iter_ranges().map(|range| range.size()).sum()
     }
 
     /// Get all ranges in the set
     pub fn ranges(&self) -> Vec<IPRange> {
-        self.ranges.iter().cloned().collect()
+        self.iter_ranges().collect()
+    }
+
+    /// Iterate over the ranges of the set in order
+    pub fn iter_ranges(&self) -> impl Iterator<Item = IPRange> + '_ {
+        self.ranges
+            .iter()
+            .map(|(start, end)| IPRange::new_unchecked(start.clone(), end.clone()))
+    }
+
+    /// Get the number of disjoint ranges in the set
+    pub fn range_count(&self) -> usize {
+        self.ranges.len()
+    }
+
+    /// Encode the set in the compact binary format (see [`crate::binary`])
+    pub fn to_binary(&self) -> Vec<u8> {
+        crate::binary::encode_ip_set(self)
+    }
+
+    /// Decode a set from the compact binary format
+    pub fn from_binary(bytes: &[u8]) -> AddrResult<Self> {
+        crate::binary::decode_ip_set(bytes)
+    }
+
+    /// Get the minimal reverse DNS zones covering the set, in address order
+    pub fn reverse_zones(&self) -> Vec<ReverseZone> {
+        crate::ip::reverse_dns::set_reverse_zones(self)
+    }
+
+    /// Get the RFC 2317 delegations for the IPv4 blocks longer than /24 in the set
+    pub fn rfc2317_delegations(&self, label: ClasslessLabel) -> Vec<ClasslessDelegation> {
+        crate::ip::reverse_dns::set_rfc2317_delegations(self, label)
     }
 
     /// Get all networks that represent this set
     pub fn networks(&self) -> AddrResult<Vec<IPNetwork>> {
         let mut networks = Vec::new();
-        for range in &self.ranges {
+        for range in self.iter_ranges() {
             networks.extend(range.to_cidrs()?);
         }
         Ok(networks)
@@ -262,38 +277,50 @@ impl IPSet {
 
     /// Iterate over all individual IP addresses in the set
     pub fn addresses(&self) -> impl Iterator<Item = IPAddress> + '_ {
-        self.ranges.iter().flat_map(|range| range.hosts())
+        self.iter_ranges().flat_map(|range| range.hosts())
     }
 
     /// Union operation - combine two sets
     pub fn union(&self, other: &IPSet) -> AddrResult<IPSet> {
-        let mut result = self.clone();
-        for range in &other.ranges {
-            result.add_range(range.clone())?;
+        let (mut result, smaller) = if self.ranges.len() >= other.ranges.len() {
+            (self.clone(), other)
+        } else {
+            (other.clone(), self)
+        };
+        for range in smaller.iter_ranges() {
+            result.add_range(range)?;
         }
         Ok(result)
     }
 
     /// Intersection operation - find common addresses
     pub fn intersection(&self, other: &IPSet) -> AddrResult<IPSet> {
-        let mut result = IPSet::new();
-
-        for range1 in &self.ranges {
-            for range2 in &other.ranges {
-                if let Some(intersection) = range1.intersection(range2) {
-                    result.add_range(intersection)?;
-                }
+        let mut result = BTreeMap::new();
+        let mut left = self.ranges.iter().peekable();
+        let mut right = other.ranges.iter().peekable();
+
+        // Walk both ordered range lists in step
+        while let (Some(&(l_start, l_end)), Some(&(r_start, r_end))) = (left.peek(), right.peek()) {
+            let start = l_start.max(r_start);
+            let end = l_end.min(r_end);
+            if start <= end {
+                result.insert(start.clone(), end.clone());
+            }
+            if l_end < r_end {
+                left.next();
+            } else {
+                right.next();
             }
         }
 
-        Ok(result)
+        Ok(IPSet { ranges: result })
     }
 
     /// Difference operation - subtract other set from this set
     pub fn difference(&self, other: &IPSet) -> AddrResult<IPSet> {
         let mut result = self.clone();
-        for range in &other.ranges {
-            result.remove_range(range)?;
+        for range in other.iter_ranges() {
+            result.remove_range(&range)?;
         }
         Ok(result)
     }
@@ -313,8 +340,7 @@ impl IPSet {
 
     /// Check if this set is a subset of another set
     pub fn is_subset(&self, other: &IPSet) -> AddrResult<bool> {
-        let difference = self.difference(other)?;
-        Ok(difference.is_empty())
+        Ok(self.iter_ranges().all(|range| other.contains_range(&range)))
     }
 
     /// Check if this set is a superset of another set
@@ -323,13 +349,10 @@ impl IPSet {
     }
 
     /// Compact the set by merging adjacent ranges
+    ///
+    /// Ranges are merged as they are added, so this is a no-op kept for
+    /// compatibility.
     pub fn compact(&mut self) -> AddrResult<()> {
-        let ranges: Vec<_> = self.ranges.iter().cloned().collect();
-        let merged = crate::ip::range::merge_ranges(&ranges)?;
-        self.ranges.clear();
-        for range in merged {
-            self.ranges.insert(range);
-        }
         Ok(())
     }
 
@@ -338,12 +361,9 @@ impl IPSet {
         let mut ipv4_set = IPSet::new();
         let mut ipv6_set = IPSet::new();
 
-        for range in &self.ranges {
-            if range.is_ipv4() {
-                ipv4_set.ranges.insert(range.clone());
-            } else {
-                ipv6_set.ranges.insert(range.clone());
-            }
+        for (start, end) in &self.ranges {
+            let target = if start.is_ipv4() { &mut ipv4_set } else { &mut ipv6_set };
+            target.ranges.insert(start.clone(), end.clone());
         }
 
         (ipv4_set, ipv6_set)
@@ -351,12 +371,56 @@ impl IPSet {
 
     /// Get the minimum address in the set
     pub fn min_address(&self) -> Option<IPAddress> {
-        self.ranges.iter().map(|range| range.start()).min().cloned()
+        self.ranges.keys().next().cloned()
     }
 
     /// Get the maximum address in the set
     pub fn max_address(&self) -> Option<IPAddress> {
-        self.ranges.iter().map(|range| range.end()).max().cloned()
+        self.ranges.values().next_back().cloned()
+    }
+}
+
+impl FromIterator<IPRange> for IPSet {
+    fn from_iter<I: IntoIterator<Item = IPRange>>(iter: I) -> Self {
+        Self::from_unsorted_ranges(
+            iter.into_iter()
+                .map(|range| (range.start().clone(), range.end().clone()))
+                .collect(),
+        )
+    }
+}
+
+impl FromIterator<IPNetwork> for IPSet {
+    fn from_iter<I: IntoIterator<Item = IPNetwork>>(iter: I) -> Self {
+        iter.into_iter()
+            .filter(|network| network.network_address().zone().is_none())
+            .map(|network| IPRange::from(&network))
+            .collect()
+    }
+}
+
+impl FromIterator<IPAddress> for IPSet {
+    fn from_iter<I: IntoIterator<Item = IPAddress>>(iter: I) -> Self {
+        Self::from_unsorted_ranges(iter.into_iter().map(|addr| (addr.clone(), addr)).collect())
+    }
+}
+
+impl Extend<IPRange> for IPSet {
+    fn extend<I: IntoIterator<Item = IPRange>>(&mut self, iter: I) {
+        let added: Vec<_> = iter
+            .into_iter()
+            .map(|range| (range.start().clone(), range.end().clone()))
+            .collect();
+        if added.len() > self.ranges.len() {
+            // Rebuilding in one pass beats inserting one at a time
+            let mut all: Vec<_> = std::mem::take(&mut self.ranges).into_iter().collect();
+            all.extend(added);
+            *self = Self::from_unsorted_ranges(all);
+        } else {
+            for (start, end) in added {
+                let _ = self.add_range(IPRange::new_unchecked(start, end));
+            }
+        }
     }
 }
 
@@ -371,7 +435,7 @@ impl fmt::Display for IPSet {
         if self.is_empty() {
             write!(f, "IPSet([])")
         } else {
-            let ranges_str: Vec<String> = self.ranges.iter().map(|r| r.to_string()).collect();
+            let ranges_str: Vec<String> = self.iter_ranges().map(|r| r.to_string()).collect();
             write!(f, "IPSet([{}])", ranges_str.join(", "))
         }
     }
@@ -672,4 +736,131 @@ mod tests {
         let set = addresses.into_ip_set().unwrap();
         assert_eq!(set.size(), 2);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_ip_set_from_iter() {
+        let ranges = vec![
+            IPRange::from_str("10.0.0.20-10.0.0.30").unwrap(),
+            IPRange::from_str("2001:db8::1-2001:db8::5").unwrap(),
+            IPRange::from_str("10.0.0.1-10.0.0.10").unwrap(),
+            IPRange::from_str("10.0.0.11-10.0.0.12").unwrap(),
+            IPRange::from_str("10.0.0.25-10.0.0.40").unwrap(),
+        ];
+        let set: IPSet = ranges.iter().cloned().collect();
+        let strings: Vec<String> = set.ranges().iter().map(|r| r.to_string()).collect();
+        assert_eq!(strings, vec!["10.0.0.1-10.0.0.12", "10.0.0.20-10.0.0.40", "2001:db8::1-2001:db8::5"]);
+
+        // Incremental insertion produces the same set
+        let mut incremental = IPSet::new();
+        for range in ranges {
+            incremental.add_range(range).unwrap();
+        }
+        assert_eq!(incremental, set);
+
+        let addresses: IPSet = (0..1000u32)
+            .rev()
+            .map(|i| IPAddress::new_v4(std::net::Ipv4Addr::from(0x0a00_0000 + i * 2)))
+            .collect();
+        assert_eq!(addresses.range_count(), 1000);
+        assert!(addresses.contains_address(&IPAddress::from_str("10.0.7.206").unwrap()));
+        assert!(!addresses.contains_address(&IPAddress::from_str("10.0.7.207").unwrap()));
+
+        let mut extended = addresses.clone();
+        extended.extend((0..1000u32).map(|i| {
+            let addr = IPAddress::new_v4(std::net::Ipv4Addr::from(0x0a00_0001 + i * 2));
+            IPRange::new(addr.clone(), addr).unwrap()
+        }));
+        assert_eq!(extended.range_count(), 1);
+        assert_eq!(extended.size(), 2000);
+    }
+
+    #[test]
+    fn test_ip_set_remove_splits_ranges() {
+        let mut set: IPSet = vec![
+            IPNetwork::from_str("10.0.0.0/24").unwrap(),
+            IPNetwork::from_str("10.0.2.0/24").unwrap(),
+        ]
+        .into_iter()
+        .collect();
+
+        set.remove_range(&IPRange::from_str("10.0.0.100-10.0.2.9").unwrap()).unwrap();
+        let strings: Vec<String> = set.ranges().iter().map(|r| r.to_string()).collect();
+        assert_eq!(strings, vec!["10.0.0.0-10.0.0.99", "10.0.2.10-10.0.2.255"]);
+
+        assert!(set.contains_range(&IPRange::from_str("10.0.2.10-10.0.2.20").unwrap()));
+        assert!(!set.contains_range(&IPRange::from_str("10.0.0.90-10.0.2.20").unwrap()));
+        assert!(!set.contains_network(&IPNetwork::from_str("10.0.0.0/24").unwrap()));
+        assert!(set.contains_network(&IPNetwork::from_str("10.0.0.64/27").unwrap()));
+        assert_eq!(set.min_address().unwrap().to_string(), "10.0.0.0");
+        assert_eq!(set.max_address().unwrap().to_string(), "10.0.2.255");
+    }
+
+    #[test]
+    fn test_ip_set_whole_address_space() {
+        let mut set = IPSet::from_network(IPNetwork::from_str("0.0.0.0/0").unwrap()).unwrap();
+        assert_eq!(set.size(), 1u128 << 32);
+        set.add_network(IPNetwork::from_str("::/0").unwrap()).unwrap();
+        assert_eq!(set.range_count(), 2);
+
+        set.remove_address(&IPAddress::from_str("255.255.255.255").unwrap()).unwrap();
+        set.add_address(IPAddress::from_str("255.255.255.255").unwrap()).unwrap();
+        assert_eq!(set.range_count(), 2);
+        assert!(set.contains_address(&IPAddress::from_str("ffff::1").unwrap()));
+        assert_eq!(set.size(), AddressCount::power_of_two(128) + AddressCount::power_of_two(32));
+    }
+
+    #[test]
+    fn test_ip_set_huge_ipv6_operations() {
+        let all = IPSet::from_network(IPNetwork::from_str("::/0").unwrap()).unwrap();
+        assert_eq!(all.size(), AddressCount::power_of_two(128));
+        assert_eq!(all.networks().unwrap()[0].to_string(), "::/0");
+
+        let doc = IPSet::from_network(IPNetwork::from_str("2001:db8::/32").unwrap()).unwrap();
+        let rest = all.difference(&doc).unwrap();
+        assert_eq!(rest.size(), AddressCount::new(u128::MAX - (1u128 << 96) + 1));
+        assert_eq!(rest.networks().unwrap().len(), 32);
+        assert!(rest.intersection(&doc).unwrap().is_empty());
+        assert_eq!(rest.union(&doc).unwrap(), all);
+        assert_eq!(all.symmetric_difference(&rest).unwrap(), doc);
+        assert!(doc.is_subset(&all).unwrap());
+    }
+
+    #[test]
+    fn test_ip_set_rejects_zones() {
+        let addr = |s: &str| IPAddress::from_str(s).unwrap();
+        let zoned_network = IPNetwork::from_str("fe80::%eth0/126").unwrap();
+        let zoned_range = IPRange::new_unchecked(addr("fe80::1%eth0"), addr("fe80::5%eth0"));
+
+        let mut set = IPSet::new();
+        assert!(set.add_address(addr("fe80::1%eth0")).is_err());
+        assert!(set.add_network(zoned_network.clone()).is_err());
+        assert!(set.add_range(zoned_range.clone()).is_err());
+        assert!(IPSet::from_network(zoned_network.clone()).is_err());
+        assert!(IPSet::from_addresses(&[addr("fe80::1%eth0")]).is_err());
+        assert!(IPSet::from_networks(std::slice::from_ref(&zoned_network)).is_err());
+        assert!(IPSet::from_ranges(std::slice::from_ref(&zoned_range)).is_err());
+        assert!(set.is_empty());
+
+        // Unzoned members never match a zoned query
+        set.add_range(IPRange::from_str("fe80::1-fe80::5").unwrap()).unwrap();
+        set.add_address(addr("fe80::6")).unwrap();
+        assert_eq!(set.ranges().len(), 1);
+        assert!(set.contains_address(&addr("fe80::2")));
+        assert!(!set.contains_address(&addr("fe80::2%eth0")));
+        assert!(!set.contains_network(&IPNetwork::from_str("fe80::4%eth0/127").unwrap()));
+        assert!(!set.contains_range(&zoned_range));
+        assert!(set.remove_address(&addr("fe80::2%eth0")).is_err());
+        assert!(set.remove_network(&zoned_network).is_err());
+        assert!(set.remove_range(&zoned_range).is_err());
+        assert_eq!(set.size(), AddressCount::new(6));
+
+        // The infallible conversions skip zoned items
+        let collected: IPSet = vec![addr("fe80::1%eth0"), addr("fe80::9")].into_iter().collect();
+        assert_eq!(collected.to_string(), "IPSet([fe80::9])");
+        let collected: IPSet = vec![zoned_network].into_iter().collect();
+        assert!(collected.is_empty());
+        let mut extended = set.clone();
+        extended.extend(vec![zoned_range]);
+        assert_eq!(extended, set);
+    }
+}
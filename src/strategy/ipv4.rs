@@ -19,28 +19,68 @@ impl IPv4Strategy {
     }
 
     /// Parse with inet_aton semantics (allowing octal and hex)
+    ///
+    /// Like the C library, this accepts one to four parts, each decimal, octal
+    /// (leading `0`) or hex (leading `0x`), with the last part filling all
+    /// remaining bytes: `10.1` is 10.0.0.1 and `010.001.002.003` is 8.1.2.3.
     pub fn parse_inet_aton(s: &str) -> AddrResult<crate::ip::ipv4::IPv4> {
-        // Handle different formats supported by inet_aton
-        if s.contains('.') {
-            // Standard dotted decimal or partial
-            crate::ip::ipv4::IPv4::expand_partial(s)
+        let parts: Vec<&str> = s.split('.').collect();
+        if parts.len() > 4 {
+            return Err(AddrFormatError::new(format!("Invalid IPv4 address '{}': too many parts", s)));
+        }
+
+        let mut values = Vec::with_capacity(parts.len());
+        for part in &parts {
+            values.push(Self::parse_aton_part(part).ok_or_else(|| {
+                AddrFormatError::new(format!("Invalid IPv4 address '{}': bad part '{}'", s, part))
+            })?);
+        }
+
+        let (last, leading) = values.split_last().expect("split always yields a part");
+        let last_bits = 8 * (4 - leading.len() as u32);
+        if leading.iter().any(|&value| value > 0xff) || (last_bits < 32 && *last >= 1 << last_bits) {
+            return Err(AddrFormatError::new(format!("Invalid IPv4 address '{}': part out of range", s)));
+        }
+
+        let value = leading
+            .iter()
+            .enumerate()
+            .fold(*last as u32, |acc, (i, &value)| acc | (value as u32) << (24 - 8 * i));
+        Ok(crate::ip::ipv4::IPv4::from_u32(value))
+    }
+
+    /// Parse a single inet_aton part as decimal, octal or hex
+    fn parse_aton_part(part: &str) -> Option<u64> {
+        let (digits, radix) = if let Some(hex) = part.strip_prefix("0x").or_else(|| part.strip_prefix("0X")) {
+            (hex, 16)
+        } else if part.len() > 1 && part.starts_with('0') {
+            (&part[1..], 8)
         } else {
-            // Single number format
-            let num = if s.starts_with("0x") || s.starts_with("0X") {
-                // Hexadecimal
-                u32::from_str_radix(&s[2..], 16)
-                    .map_err(|_| AddrFormatError::new("Invalid hexadecimal number"))?
-            } else if s.starts_with('0') && s.len() > 1 {
-                // Octal
-                u32::from_str_radix(s, 8)
-                    .map_err(|_| AddrFormatError::new("Invalid octal number"))?
-            } else {
-                // Decimal
-                s.parse::<u32>()
-                    .map_err(|_| AddrFormatError::new("Invalid decimal number"))?
-            };
-            Ok(crate::ip::ipv4::IPv4::from_u32(num))
+            (part, 10)
+        };
+        // An empty hex part ("0x") is zero, as in glibc
+        if digits.is_empty() {
+            return (radix == 16).then_some(0);
         }
+        if !digits.chars().all(|c| c.is_digit(radix)) {
+            return None;
+        }
+        u64::from_str_radix(digits, radix).ok().filter(|&value| value <= u32::MAX as u64)
+    }
+
+    /// Remove leading zeros from each dotted part (`010.001.002.003` to `10.1.2.3`)
+    pub fn strip_zerofill(s: &str) -> String {
+        s.split('.')
+            .map(|part| {
+                let trimmed = part.trim_start_matches('0');
+                if trimmed.is_empty() && !part.is_empty() {
+                    "0"
+                } else {
+                    trimmed
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(".")
     }
 
     /// Parse with inet_pton semantics (strict dotted decimal only)
@@ -151,6 +191,17 @@ mod tests {
 
         let addr = IPv4Strategy::parse_inet_aton("0xC0A80101").unwrap(); // 192.168.1.1 as hex
         assert_eq!(addr.to_string(), "192.168.1.1");
+
+        assert_eq!(IPv4Strategy::parse_inet_aton("10.1").unwrap().to_string(), "10.0.0.1");
+        assert_eq!(IPv4Strategy::parse_inet_aton("10.1.300").unwrap().to_string(), "10.1.1.44");
+        assert_eq!(IPv4Strategy::parse_inet_aton("010.001.002.003").unwrap().to_string(), "8.1.2.3");
+        assert_eq!(IPv4Strategy::parse_inet_aton("0x7f.1").unwrap().to_string(), "127.0.0.1");
+        assert!(IPv4Strategy::parse_inet_aton("10.256.1.1").is_err());
+        assert!(IPv4Strategy::parse_inet_aton("09.1.1.1").is_err());
+        assert!(IPv4Strategy::parse_inet_aton("1.2.3.4.5").is_err());
+        assert!(IPv4Strategy::parse_inet_aton("1..2").is_err());
+
+        assert_eq!(IPv4Strategy::strip_zerofill("010.001.002.000"), "10.1.2.0");
     }
 
     #[test]